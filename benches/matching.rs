@@ -0,0 +1,110 @@
+//! Criterion benchmarks of the shape processing hot path : `cargo bench`,
+//! the results are compared to the previous run (`target/criterion`).
+use std::f64::consts::PI;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use mouse_actions::builder::{BindingBuilder, ConfigBuilder};
+use mouse_actions::config::Config;
+use mouse_actions::event::{ClickEvent, EventType, MouseButton, Point, PointHistory, HISTO_SIZE};
+use mouse_actions::grab::normalize_points;
+use mouse_actions::points_to_angles::points_to_angles;
+use mouse_actions::process_event::{find_candidates, find_the_chosen_one_among_the_candidates};
+
+// a shape of `len` points drawn on the screen, from the parametric function `f(t)`, t ∈ [0, 1]
+fn shape(len: usize, f: impl Fn(f64) -> (f64, f64)) -> PointHistory {
+    let mut points = PointHistory::new();
+    for i in 0..len {
+        let (x, y) = f(i as f64 / len as f64);
+        points.push(Point {
+            x: 500 + x.round() as i32,
+            y: 500 + y.round() as i32,
+        });
+    }
+    points
+}
+
+fn circle(len: usize, turns: f64) -> PointHistory {
+    shape(len, |t| {
+        (
+            300. * (2. * PI * turns * t).cos(),
+            300. * (2. * PI * turns * t).sin(),
+        )
+    })
+}
+
+fn zigzag(len: usize, teeth: f64) -> PointHistory {
+    shape(len, |t| {
+        (600. * t, 200. * ((teeth * t).fract() - 0.5).abs())
+    })
+}
+
+// a config with 30 shape bindings, like the config described in the README
+fn config() -> Config {
+    (0..30)
+        .fold(ConfigBuilder::new(MouseButton::Right), |builder, i| {
+            let points = if i % 2 == 0 {
+                circle(200, 0.5 + i as f64 / 10.)
+            } else {
+                zigzag(200, 1. + i as f64 / 5.)
+            };
+            builder.binding(
+                BindingBuilder::new(MouseButton::Right)
+                    .comment(&format!("shape {i}"))
+                    .shape(normalize_points(&points, false))
+                    .cmd_str("true")
+                    .build(),
+            )
+        })
+        .build()
+}
+
+fn release_event(point_history: &PointHistory) -> ClickEvent {
+    ClickEvent {
+        button: MouseButton::Right,
+        edges: vec![],
+        modifiers: vec![],
+        event_type: EventType::Release,
+        shapes_angles: vec![points_to_angles(point_history)],
+        shapes_xy: vec![point_history.clone()],
+        shapes_svg: vec![],
+        shapes_directions: vec![],
+        shape_ref: None,
+        time: None,
+        idle: None,
+        length: None,
+        shape_button_held: false,
+        buttons: vec![],
+        edges_forbidden: vec![],
+        region: None,
+        quadrants: vec![],
+        device: None,
+        held_buttons: vec![],
+        keys: vec![],
+        closed: None,
+    }
+}
+
+fn matching(c: &mut Criterion) {
+    // a fast gesture that fills the point history
+    let point_history = circle(HISTO_SIZE, 1.3);
+    let config = config();
+
+    c.bench_function("normalize_points", |b| {
+        b.iter(|| normalize_points(black_box(&point_history), false))
+    });
+    c.bench_function("points_to_angles", |b| {
+        b.iter(|| points_to_angles(black_box(&point_history)))
+    });
+    c.bench_function("full match path (30 shapes)", |b| {
+        b.iter(|| {
+            let event = release_event(black_box(&point_history));
+            let candidates = find_candidates(&config, &event);
+            find_the_chosen_one_among_the_candidates(&candidates, &event, config.angle_weighting)
+                .is_some()
+        })
+    });
+}
+
+criterion_group!(benches, matching);
+criterion_main!(benches);
@@ -35,9 +35,9 @@ fn start() {
     if args.no_listen {
         cmd.push(String::from("--no-listen"));
     }
-    if args.config_path.is_some() {
+    for config_path in args.config_path {
         cmd.push(String::from("--config-path"));
-        cmd.push(args.config_path.unwrap());
+        cmd.push(config_path);
     }
     if args.log_level.is_some() {
         cmd.push(String::from("--log-level"));
@@ -50,7 +50,7 @@ fn start() {
 #[tauri::command(async)]
 fn get_config() -> config::Config {
     let args = mouse_actions::args::parse();
-    let config_path = config::get_config_path(&args.config_path);
+    let config_path = config::get_config_path(&args.main_config_path());
     config::init_config_file_if_not_exists(&config_path);
     config::get_config(&config_path)
 }
@@ -58,7 +58,7 @@ fn get_config() -> config::Config {
 #[tauri::command(async)]
 fn save_config(new_config: config::Config) {
     let args = mouse_actions::args::parse();
-    config::save_config(&new_config, &args.config_path)
+    config::save_config(&new_config, &args.main_config_path())
 }
 
 pub fn open_config_editor() {
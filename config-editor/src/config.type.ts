@@ -10,6 +10,10 @@ export const Buttons = [
   "Trigger",
   "Thumb",
   "Thumb2",
+  "TwoFingers",
+  "ThreeFingers",
+  "FourFingers",
+  "FiveFingers",
   "WheelUp",
   "WheelDown",
   "Unknown",
@@ -23,8 +27,12 @@ export const Modifiers = [
   "ControlLeft",
   "ControlRight",
   "MetaLeft",
+  "MetaRight",
   "Alt",
   "AltGr",
+  "Shift",
+  "Control",
+  "Meta",
 ] as const;
 
 export type ModifierType = (typeof Modifiers)[number];
@@ -34,19 +42,62 @@ export type Point = { x: number; y: number };
 export const EventTypes = ["Press", "Release", "Click", "Shape"] as const;
 export type EventTypeType = (typeof EventTypes)[number];
 
-export const Edges = ["Top", "Right", "Bottom", "Left"] as const;
+export const Edges = [
+  "Top",
+  "Right",
+  "Bottom",
+  "Left",
+  "TopLeft",
+  "TopRight",
+  "BottomLeft",
+  "BottomRight",
+] as const;
 export type EdgeType = (typeof Edges)[number];
 
+export const Regions = [
+  "TopLeft",
+  "Top",
+  "TopRight",
+  "Left",
+  "Center",
+  "Right",
+  "BottomLeft",
+  "Bottom",
+  "BottomRight",
+] as const;
+export type RegionType = (typeof Regions)[number];
+
+export const Quadrants = [
+  "TopLeft",
+  "TopRight",
+  "BottomLeft",
+  "BottomRight",
+] as const;
+export type QuadrantType = (typeof Quadrants)[number];
+
 export type EventType = {
   button: ButtonType;
+  buttons?: ButtonType[];
   modifiers?: ModifierType[];
   event_type: EventTypeType;
   edges?: EdgeType[];
+  edges_forbidden?: EdgeType[];
+  region?: RegionType;
+  quadrants?: QuadrantType[];
+  device?: string;
+  held_buttons?: ButtonType[];
+  keys?: string[];
   shapes_xy?: number[][];
+  shapes_svg?: string[];
+  shapes_directions?: string[];
+  shape_ref?: string;
+  shape_button_held?: boolean;
+  closed?: boolean;
 };
 
 export type BindingType = {
   uid?: string;
+  id?: string;
   comment: string;
   cmd_str: string;
   event: EventType;
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// an invalid config must be an error, never a panic
+fuzz_target!(|data: &[u8]| {
+    if let Ok(json_config) = std::str::from_utf8(data) {
+        let _ = mouse_actions::config::load_from_str(json_config);
+    }
+});
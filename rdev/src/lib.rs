@@ -89,6 +89,7 @@
 //!     pub time: SystemTime,
 //!     pub name: Option<String>,
 //!     pub event_type: EventType,
+//!     pub device: Option<String>,
 //! }
 //! ```
 //!
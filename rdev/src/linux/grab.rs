@@ -86,7 +86,11 @@ convert_buttons!(
     BTN_TASK, Task,
     BTN_TRIGGER, Trigger,
     BTN_THUMB, Thumb,
-    BTN_THUMB2, Thumb2
+    BTN_THUMB2, Thumb2,
+    BTN_TOOL_DOUBLETAP, DoubleTap,
+    BTN_TOOL_TRIPLETAP, TripleTap,
+    BTN_TOOL_QUADTAP, QuadTap,
+    BTN_TOOL_QUINTTAP, QuintTap
 );
 
 //TODO: IntlBackslash, kpDelete
@@ -327,7 +331,7 @@ where
     let mut y = current_y as f64;
     let w = width as f64;
     let h = height as f64;
-    filter_map_events(|event| {
+    filter_map_events(|event, device| {
         let event_type = match evdev_event_to_rdev_event(&event, &mut x, &mut y, w, h) {
             Some(rdev_event) => rdev_event,
             // If we can't convert event, simulate it
@@ -338,6 +342,7 @@ where
             time: SystemTime::now(),
             name,
             event_type,
+            device: device.map(str::to_string),
         };
         if callback(rdev_event).is_some() {
             (Some(event), GrabStatus::Continue)
@@ -351,7 +356,7 @@ where
 
 pub fn filter_map_events<F>(mut func: F) -> io::Result<()>
 where
-    F: FnMut(InputEvent) -> (Option<InputEvent>, GrabStatus),
+    F: FnMut(InputEvent, Option<&str>) -> (Option<InputEvent>, GrabStatus),
 {
     // TODO detecter perm err, supp des device
     'root_loop: loop {
@@ -404,7 +409,7 @@ where
                                 continue 'events;
                             }
                         };
-                        let (event, grab_status) = func(event);
+                        let (event, grab_status) = func(event, device.name());
 
                         if let (Some(event), Some(out_device)) =
                             (event, output_devices.get(device_idx))
@@ -33,6 +33,10 @@ unsafe fn send_native(event_type: &EventType, display: *mut xlib::Display) -> Op
             Button::Trigger => xtest::XTestFakeButtonEvent(display, 13, TRUE, 0),
             Button::Thumb => xtest::XTestFakeButtonEvent(display, 14, TRUE, 0),
             Button::Thumb2 => xtest::XTestFakeButtonEvent(display, 15, TRUE, 0),
+            // the touchpad finger count can't be simulated
+            Button::DoubleTap | Button::TripleTap | Button::QuadTap | Button::QuintTap => {
+                return None
+            }
 
             Button::Unknown(code) => {
                 xtest::XTestFakeButtonEvent(display, (*code).try_into().ok()?, TRUE, 0)
@@ -52,6 +56,10 @@ unsafe fn send_native(event_type: &EventType, display: *mut xlib::Display) -> Op
             Button::Trigger => xtest::XTestFakeButtonEvent(display, 13, FALSE, 0),
             Button::Thumb => xtest::XTestFakeButtonEvent(display, 14, FALSE, 0),
             Button::Thumb2 => xtest::XTestFakeButtonEvent(display, 15, FALSE, 0),
+            // the touchpad finger count can't be simulated
+            Button::DoubleTap | Button::TripleTap | Button::QuadTap | Button::QuintTap => {
+                return None
+            }
 
             Button::Unknown(code) => {
                 xtest::XTestFakeButtonEvent(display, (*code).try_into().ok()?, FALSE, 0)
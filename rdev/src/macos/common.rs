@@ -146,6 +146,7 @@ pub unsafe fn convert(
             event_type,
             time: SystemTime::now(),
             name,
+            device: None,
         });
     }
     None
@@ -230,6 +230,12 @@ pub enum Button {
     Trigger,
     Thumb,
     Thumb2,
+    /// The touchpad finger count (evdev BTN_TOOL_DOUBLETAP, BTN_TOOL_TRIPLETAP...),
+    /// only sent by the linux grab.
+    DoubleTap,
+    TripleTap,
+    QuadTap,
+    QuintTap,
     Unknown(u8),
 }
 
@@ -268,12 +274,16 @@ pub enum EventType {
 /// on the OS layout and keyboard state machinery.
 /// Caveat: Dead keys don't function on Linux(X11) yet. You will receive None for
 /// a dead key, and the raw letter instead of accentuated letter.
+/// The device Option contains the name of the input device of the event, it's only
+/// known by the grab on Linux (evdev), None otherwise.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Event {
     pub time: SystemTime,
     pub name: Option<String>,
     pub event_type: EventType,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub device: Option<String>,
 }
 
 /// We can define a dummy Keyboard, that we will use to detect
@@ -21,6 +21,7 @@ unsafe extern "system" fn raw_callback(code: i32, param: usize, lpdata: isize) -
                 event_type,
                 time: SystemTime::now(),
                 name,
+                device: None,
             };
             if let Some(callback) = &mut GLOBAL_CALLBACK {
                 if callback(event).is_none() {
@@ -1,5 +1,7 @@
 use clap::{Parser, Subcommand};
 
+use crate::event::Bounds;
+
 /// mouse_actions allows to execute some commands from mouse events such as
 /// clicks on the side/corners of the screen, or drawing shapes.
 /// It's a mix between Easystroke and Compiz edge commands.
@@ -11,9 +13,11 @@ pub struct Args {
     #[clap(short, long)]
     pub no_listen: bool,
 
-    /// config path, default : ~/.config/mouse-actions.json
+    /// config path, default : $XDG_CONFIG_HOME/mouse-actions.json or ~/.config/mouse-actions.json,
+    /// "-" to read the config from stdin (not watched). Repeat it to merge overlays in order :
+    /// their bindings are appended and their other fields replace the ones of the first config
     #[clap(short, long)]
-    pub config_path: Option<String>,
+    pub config_path: Vec<String>,
 
     #[clap(subcommand)]
     pub command: Option<MouseActionsCommands>,
@@ -22,9 +26,29 @@ pub struct Args {
     #[clap(short, long)]
     pub version: bool,
 
+    /// print the JSON Schema of the config file, for the validation and the completion in the editors
+    #[clap(long)]
+    pub emit_schema: bool,
+
     /// log level : error, warn, info, debug, trace. [default=info]
     #[clap(short, long)]
     pub log_level: Option<String>,
+
+    /// write the logs to this file instead of stderr, the file is rotated every 10 MiB
+    /// and the 3 last rotated files are kept (<log file>.1 … <log file>.3)
+    #[clap(long)]
+    pub log_file: Option<String>,
+
+    /// print a JSON line to stdout for each matched binding (id, comment and event), to pipe
+    /// the gestures into another program, the commands still run
+    #[clap(long)]
+    pub emit_matches: bool,
+
+    /// print every grabbed event (buttons, keys, moves, wheel) with its decoded button or key
+    /// name, without loading the config and without matching, to check that the device events
+    /// reach mouse-actions
+    #[clap(long)]
+    pub monitor_events: bool,
 }
 
 #[derive(Debug, Subcommand, PartialEq, Clone)]
@@ -45,7 +69,47 @@ pub enum MouseActionsCommands {
     #[clap()]
     Record,
 
-    /// List the current config bindings
+    /// Tune the shapes : print the scores of the shape bindings after each shape drawn
+    /// with the shape button, without running any command
+    #[clap()]
+    Tune,
+
+    /// Start the bindings and print the latency from each grabbed event to its command
+    /// spawn, with the percentiles of the session
+    #[clap()]
+    Latency,
+
+    /// Record samples of a gesture : append each shape drawn with the shape button
+    /// (normalized points and angles) as a JSON line to the output file
+    #[clap()]
+    RecordSamples {
+        /// the JSONL file to append the samples to
+        #[clap(short, long)]
+        output: String,
+
+        /// the label of the recorded gesture, written in each line
+        #[clap(long)]
+        label: String,
+    },
+
+    /// Print the edges of each point of a cursor path read from stdin (one `x,y` per line)
+    /// in the given bounds, without a display
+    #[clap()]
+    Edges {
+        /// the screen (or window) bounds : WIDTHxHEIGHT[+X+Y], e.g. 1920x1080+1920+0
+        #[clap(long)]
+        bounds: Bounds,
+
+        /// the size of the corner boxes, like the corner_size of the config
+        #[clap(long, default_value_t = 0)]
+        corner_size: u32,
+
+        /// the bounds are a window ones, like the relative_to Window of the config
+        #[clap(long)]
+        window: bool,
+    },
+
+    /// List the current config bindings, with their ids
     #[clap()]
     ListBindings,
 
@@ -53,6 +117,14 @@ pub enum MouseActionsCommands {
     #[clap()]
     GrabOneEvent,
 
+    /// print the button names of the config
+    #[clap()]
+    ListButtons,
+
+    /// Grab one button press, print its config name and exit
+    #[clap()]
+    WhichButton,
+
     /// Stop mouse action
     #[clap()]
     Stop,
@@ -61,6 +133,10 @@ pub enum MouseActionsCommands {
     #[clap()]
     Status,
 
+    /// Reset the state of the running instance (held buttons, modifiers, shape, repeats) after a desync
+    #[clap()]
+    ResetState,
+
     /// print the json config
     #[clap()]
     ShowConfig,
@@ -68,6 +144,26 @@ pub enum MouseActionsCommands {
     /// set the json config from stdin
     #[clap()]
     SetConfig,
+
+    /// write a starter config with example bindings, if the config file doesn't exist or has no binding
+    #[clap()]
+    InitExamples,
+
+    /// rewrite the config file in the canonical format : pretty JSON in the field order
+    #[clap()]
+    Format,
+}
+
+impl Args {
+    /// the first config path : the config file written by the commands and the config editor
+    pub fn main_config_path(&self) -> Option<String> {
+        self.config_path.first().cloned()
+    }
+
+    /// the config files merged into the main config
+    pub fn overlay_config_paths(&self) -> &[String] {
+        self.config_path.get(1..).unwrap_or_default()
+    }
 }
 
 pub fn parse() -> Args {
@@ -1,17 +1,232 @@
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
+use crate::config::{default_true, is_true};
 use crate::event::ClickEvent;
+use crate::limits::CommandLimits;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Binding {
     #[serde(default)]
     pub comment: String,
+
+    // a stable identifier for the external tools, derived from the event and the commands
+    // if absent : see stable_id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
     pub event: ClickEvent,
 
     #[serde(skip_serializing)]
     #[serde(default)]
     pub cmd: Vec<String>,
 
-    #[serde(default)]
+    // split like a shell command line, it takes precedence over cmd
+    #[serde(default, alias = "cmd_line")]
     pub cmd_str: String,
+
+    // the commands run on the press and on the release of the button, instead of cmd_str
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub press_cmd: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub release_cmd: String,
+
+    // a built-in action run instead of the command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<Action>,
+
+    // for the Press bindings : re-run the command every repeat_ms until the button release
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_ms: Option<u64>,
+
+    // the maximum shape difference to match this binding, instead of the default 0.8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_max: Option<f64>,
+
+    // among the bindings that match an event, the highest priority wins
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: i32,
+
+    // the shapes also match when drawn in the other direction
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bidirectional: bool,
+
+    // the shapes only match in the region of the binding event, e.g. a swipe in the
+    // notification area
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub location_sensitive: bool,
+
+    // a desktop notification shown when the command starts, `{comment}` is replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<String>,
+
+    // run the command in a new session, detached from the daemon : it survives its restarts
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub detach: bool,
+
+    // run the command without the command_prefix of the config
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_command_prefix: bool,
+
+    // don't capture & log the output of the command if it fails
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub quiet: bool,
+
+    // log the match and the command of the binding, false for a frequently fired
+    // binding : its errors are still logged
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub log: bool,
+
+    // the umask and the resource limits of the command (Linux only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<CommandLimits>,
+
+    // a command run before the binding command : the binding only fires if it exits 0
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub when: Vec<String>,
+
+    // only fire if there was no input during idle_min_ms before the gesture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_min_ms: Option<u64>,
+
+    // only fire for the shape_button drags whose path length in pixels is in this range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length_min: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length_max: Option<u32>,
+
+    // only fire on this workspace (virtual desktop), numbered from 0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<u32>,
+
+    // only fire between these local times of day, "HH:MM", e.g. ["22:00", "06:00"]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_hours: Option<[String; 2]>,
+
+    // let the matched button event reach the application, after running the command
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub passthrough: bool,
+
+    // only fire during the arm duration after the arm gesture of the config
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub arm_required: bool,
+
+    // the file this binding has been loaded from, if it comes from an include
+    #[serde(skip)]
+    pub included_from: Option<PathBuf>,
+}
+
+impl Binding {
+    /// a binding that handles both the press and the release of the button
+    pub fn is_press_release(&self) -> bool {
+        !self.press_cmd.is_empty() || !self.release_cmd.is_empty()
+    }
+
+    /// The id of the binding, or a hash of its event and its commands : it's the same
+    /// across the restarts and the edits of the other bindings.
+    pub fn stable_id(&self) -> String {
+        if let Some(id) = &self.id {
+            return id.clone();
+        }
+        let event = serde_json::to_string(&self.event).unwrap_or_default();
+        let hashed = [
+            event.as_str(),
+            &self.cmd_str,
+            &self.press_cmd,
+            &self.release_cmd,
+        ]
+        .join("\0");
+        format!("{:016x}", fnv1a(hashed.as_bytes()))
+    }
+
+    /// the key=value fields prefixing the logs of the binding, to filter the logs of a gesture
+    pub fn log_fields(&self) -> String {
+        format!(
+            "binding={:?} event_type={:?}",
+            self.comment, self.event.event_type
+        )
+    }
+}
+
+/// The actions done by mouse-actions itself, without running a command
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    // SIGTERM the running commands of the bindings and stop the repeats
+    KillSpawned,
+}
+
+/// The gesture to do before the `arm_required` bindings : they can fire during
+/// `duration_ms` after it. Its command, if any, is run when the gesture arms them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArmGesture {
+    #[serde(flatten)]
+    pub binding: Binding,
+
+    #[serde(default = "default_arm_duration_ms")]
+    pub duration_ms: u64,
+}
+
+fn default_arm_duration_ms() -> u64 {
+    5000
+}
+
+// the 64 bits FNV-1a hash : unlike DefaultHasher, it's the same with all the Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn is_zero(value: &i32) -> bool {
+    *value == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binding::Binding;
+    use crate::builder::BindingBuilder;
+    use crate::event::{Edge, MouseButton};
+
+    #[test]
+    fn test_stable_id() {
+        let binding = BindingBuilder::new(MouseButton::Middle)
+            .edges(vec![Edge::Top])
+            .cmd_str("xlogo")
+            .build();
+        let id = binding.stable_id();
+        assert_eq!(id.len(), 16);
+        assert_eq!(binding.clone().stable_id(), id);
+
+        // the comment isn't part of the id, the event and the command are
+        let mut commented = binding.clone();
+        commented.comment = "logo".to_string();
+        assert_eq!(commented.stable_id(), id);
+        let mut edited = binding.clone();
+        edited.cmd_str = "xeyes".to_string();
+        assert_ne!(edited.stable_id(), id);
+        let mut moved = binding.clone();
+        moved.event.edges = vec![Edge::Left];
+        assert_ne!(moved.stable_id(), id);
+
+        let mut named = binding;
+        named.id = Some("logo".to_string());
+        assert_eq!(named.stable_id(), "logo");
+    }
+
+    #[test]
+    fn test_log_default() {
+        let binding: Binding =
+            serde_json::from_str(r#"{"event": {"button": "Middle"}, "cmd": ["xlogo"]}"#).unwrap();
+        assert!(binding.log);
+        assert!(!serde_json::to_string(&binding).unwrap().contains("\"log\""));
+
+        let silent: Binding =
+            serde_json::from_str(r#"{"event": {"button": "Middle"}, "log": false}"#).unwrap();
+        assert!(!silent.log);
+        assert!(serde_json::to_string(&silent)
+            .unwrap()
+            .contains("\"log\":false"));
+    }
 }
@@ -0,0 +1,466 @@
+use rdev::Key;
+
+use crate::binding::{Action, ArmGesture, Binding};
+use crate::compare_angles::AngleWeighting;
+use crate::config::{prepare_binding, Config, LongGesture, Templates};
+use crate::event::{
+    AbortTrigger, ClickEvent, Edge, EventType, KeyboardModifier, MouseButton, PointHistory,
+    Quadrant, Region, RelativeTo,
+};
+use crate::limits::CommandLimits;
+
+/// Build a Config without the JSON file, the bindings are prepared like `load_from_str` does.
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    pub fn new(shape_button: MouseButton) -> Self {
+        ConfigBuilder {
+            config: Config {
+                shape_button,
+                ..Config::default()
+            },
+        }
+    }
+
+    pub fn binding(mut self, binding: Binding) -> Self {
+        self.config.bindings.push(binding);
+        self
+    }
+
+    pub fn includes(mut self, includes: Vec<String>) -> Self {
+        self.config.includes = includes;
+        self
+    }
+
+    pub fn detect_edges(mut self, detect_edges: bool) -> Self {
+        self.config.detect_edges = detect_edges;
+        self
+    }
+
+    pub fn invert_wheel(mut self, invert_wheel: bool) -> Self {
+        self.config.invert_wheel = invert_wheel;
+        self
+    }
+
+    pub fn shape_min_points(mut self, shape_min_points: usize) -> Self {
+        self.config.shape_min_points = shape_min_points;
+        self
+    }
+
+    pub fn min_direction_changes(mut self, min_direction_changes: usize) -> Self {
+        self.config.min_direction_changes = min_direction_changes;
+        self
+    }
+
+    pub fn arm(mut self, arm: ArmGesture) -> Self {
+        self.config.arm = Some(arm);
+        self
+    }
+
+    pub fn exclude_window_classes(mut self, exclude_window_classes: Vec<String>) -> Self {
+        self.config.exclude_window_classes = exclude_window_classes;
+        self
+    }
+
+    pub fn angle_weighting(mut self, angle_weighting: AngleWeighting) -> Self {
+        self.config.angle_weighting = angle_weighting;
+        self
+    }
+
+    pub fn consume_threshold(mut self, consume_threshold: f64) -> Self {
+        self.config.consume_threshold = Some(consume_threshold);
+        self
+    }
+
+    pub fn suggest_closest(mut self, suggest_closest: bool) -> Self {
+        self.config.suggest_closest = suggest_closest;
+        self
+    }
+
+    pub fn backup_count(mut self, backup_count: usize) -> Self {
+        self.config.backup_count = backup_count;
+        self
+    }
+
+    pub fn templates(mut self, templates: Templates) -> Self {
+        self.config.templates = templates;
+        self
+    }
+
+    pub fn editor_command(mut self, editor_command: &str) -> Self {
+        self.config.editor_command = Some(editor_command.to_string());
+        self
+    }
+
+    pub fn sample_interval_ms(mut self, sample_interval_ms: u64) -> Self {
+        self.config.sample_interval_ms = sample_interval_ms;
+        self
+    }
+
+    pub fn max_gesture_ms(mut self, max_gesture_ms: u64) -> Self {
+        self.config.max_gesture_ms = Some(max_gesture_ms);
+        self
+    }
+
+    pub fn abort_shape_on(mut self, abort_shape_on: Vec<AbortTrigger>) -> Self {
+        self.config.abort_shape_on = abort_shape_on;
+        self
+    }
+
+    pub fn long_gesture(mut self, long_gesture: LongGesture) -> Self {
+        self.config.long_gesture = Some(long_gesture);
+        self
+    }
+
+    pub fn trim_points(mut self, trim_start_points: usize, trim_end_points: usize) -> Self {
+        self.config.trim_start_points = trim_start_points;
+        self.config.trim_end_points = trim_end_points;
+        self
+    }
+
+    pub fn click_max_move(mut self, click_max_move: u32) -> Self {
+        self.config.click_max_move = click_max_move;
+        self
+    }
+
+    pub fn grab_nice(mut self, grab_nice: i32) -> Self {
+        self.config.grab_nice = Some(grab_nice);
+        self
+    }
+
+    pub fn sound_on_match(mut self, sound: &str) -> Self {
+        self.config.sound_on_match = Some(sound.to_string());
+        self
+    }
+
+    pub fn sound_on_fail(mut self, sound: &str) -> Self {
+        self.config.sound_on_fail = Some(sound.to_string());
+        self
+    }
+
+    pub fn command_prefix(mut self, command_prefix: Vec<String>) -> Self {
+        self.config.command_prefix = command_prefix;
+        self
+    }
+
+    pub fn corner_size(mut self, corner_size: u32) -> Self {
+        self.config.corner_size = corner_size;
+        self
+    }
+
+    pub fn resample_spacing(mut self, resample_spacing: u32) -> Self {
+        self.config.resample_spacing = resample_spacing;
+        self
+    }
+
+    pub fn relative_to(mut self, relative_to: RelativeTo) -> Self {
+        self.config.relative_to = relative_to;
+        self
+    }
+
+    pub fn build(self) -> Config {
+        self.config
+    }
+}
+
+/// Build a Binding, `build` computes the `shapes_angles` from the `shapes_xy`
+/// and the `cmd` array from the `cmd_str`.
+pub struct BindingBuilder {
+    binding: Binding,
+}
+
+impl BindingBuilder {
+    pub fn new(button: MouseButton) -> Self {
+        BindingBuilder {
+            binding: Binding {
+                comment: String::new(),
+                id: None,
+                event: ClickEvent {
+                    button,
+                    edges: vec![],
+                    modifiers: vec![],
+                    event_type: EventType::default(),
+                    shapes_angles: vec![],
+                    shapes_xy: vec![],
+                    shapes_svg: vec![],
+                    shapes_directions: vec![],
+                    shape_ref: None,
+                    time: None,
+                    idle: None,
+                    length: None,
+                    shape_button_held: false,
+                    buttons: vec![],
+                    edges_forbidden: vec![],
+                    region: None,
+                    quadrants: vec![],
+                    device: None,
+                    held_buttons: vec![],
+                    keys: vec![],
+                    closed: None,
+                },
+                cmd: vec![],
+                cmd_str: String::new(),
+                press_cmd: String::new(),
+                release_cmd: String::new(),
+                action: None,
+                repeat_ms: None,
+                diff_max: None,
+                quiet: false,
+                log: true,
+                limits: None,
+                location_sensitive: false,
+                priority: 0,
+                no_command_prefix: false,
+                detach: false,
+                notify: None,
+                bidirectional: false,
+                when: vec![],
+                arm_required: false,
+                passthrough: false,
+                idle_min_ms: None,
+                length_min: None,
+                length_max: None,
+                workspace: None,
+                active_hours: None,
+                included_from: None,
+            },
+        }
+    }
+
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.binding.comment = comment.to_string();
+        self
+    }
+
+    pub fn id(mut self, id: &str) -> Self {
+        self.binding.id = Some(id.to_string());
+        self
+    }
+
+    pub fn edges(mut self, edges: Vec<Edge>) -> Self {
+        self.binding.event.edges = edges;
+        self
+    }
+
+    pub fn quadrants(mut self, quadrants: Vec<Quadrant>) -> Self {
+        self.binding.event.quadrants = quadrants;
+        self
+    }
+
+    pub fn device(mut self, device: &str) -> Self {
+        self.binding.event.device = Some(device.to_string());
+        self
+    }
+
+    pub fn held_buttons(mut self, held_buttons: Vec<MouseButton>) -> Self {
+        self.binding.event.held_buttons = held_buttons;
+        self
+    }
+
+    pub fn keys(mut self, keys: Vec<Key>) -> Self {
+        self.binding.event.keys = keys;
+        self
+    }
+
+    pub fn edges_forbidden(mut self, edges_forbidden: Vec<Edge>) -> Self {
+        self.binding.event.edges_forbidden = edges_forbidden;
+        self
+    }
+
+    pub fn modifiers(mut self, modifiers: Vec<KeyboardModifier>) -> Self {
+        self.binding.event.modifiers = modifiers;
+        self
+    }
+
+    pub fn event_type(mut self, event_type: EventType) -> Self {
+        self.binding.event.event_type = event_type;
+        self
+    }
+
+    /// add a shape to the binding, and set its event_type to Shape
+    pub fn shape(mut self, shape_xy: PointHistory) -> Self {
+        self.binding.event.shapes_xy.push(shape_xy);
+        self.binding.event.event_type = EventType::Shape;
+        self
+    }
+
+    pub fn shape_directions(mut self, shape_directions: &str) -> Self {
+        self.binding
+            .event
+            .shapes_directions
+            .push(shape_directions.to_string());
+        self.binding.event.event_type = EventType::Shape;
+        self
+    }
+
+    pub fn shape_svg(mut self, shape_svg: &str) -> Self {
+        self.binding.event.shapes_svg.push(shape_svg.to_string());
+        self.binding.event.event_type = EventType::Shape;
+        self
+    }
+
+    pub fn shape_button_held(mut self, shape_button_held: bool) -> Self {
+        self.binding.event.shape_button_held = shape_button_held;
+        self
+    }
+
+    pub fn cmd_str(mut self, cmd_str: &str) -> Self {
+        self.binding.cmd_str = cmd_str.to_string();
+        self
+    }
+
+    pub fn press_cmd(mut self, press_cmd: &str) -> Self {
+        self.binding.press_cmd = press_cmd.to_string();
+        self
+    }
+
+    pub fn release_cmd(mut self, release_cmd: &str) -> Self {
+        self.binding.release_cmd = release_cmd.to_string();
+        self
+    }
+
+    pub fn repeat_ms(mut self, repeat_ms: u64) -> Self {
+        self.binding.repeat_ms = Some(repeat_ms);
+        self
+    }
+
+    pub fn diff_max(mut self, diff_max: f64) -> Self {
+        self.binding.diff_max = Some(diff_max);
+        self
+    }
+
+    pub fn bidirectional(mut self, bidirectional: bool) -> Self {
+        self.binding.bidirectional = bidirectional;
+        self
+    }
+
+    pub fn notify(mut self, notify: &str) -> Self {
+        self.binding.notify = Some(notify.to_string());
+        self
+    }
+
+    pub fn detach(mut self, detach: bool) -> Self {
+        self.binding.detach = detach;
+        self
+    }
+
+    pub fn no_command_prefix(mut self, no_command_prefix: bool) -> Self {
+        self.binding.no_command_prefix = no_command_prefix;
+        self
+    }
+
+    pub fn location_sensitive(mut self, region: Region) -> Self {
+        self.binding.location_sensitive = true;
+        self.binding.event.region = Some(region);
+        self
+    }
+
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.binding.priority = priority;
+        self
+    }
+
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.binding.quiet = quiet;
+        self
+    }
+
+    pub fn log(mut self, log: bool) -> Self {
+        self.binding.log = log;
+        self
+    }
+
+    pub fn limits(mut self, limits: CommandLimits) -> Self {
+        self.binding.limits = Some(limits);
+        self
+    }
+
+    pub fn action(mut self, action: Action) -> Self {
+        self.binding.action = Some(action);
+        self
+    }
+
+    pub fn when(mut self, when: Vec<String>) -> Self {
+        self.binding.when = when;
+        self
+    }
+
+    pub fn length_range(mut self, length_min: Option<u32>, length_max: Option<u32>) -> Self {
+        self.binding.length_min = length_min;
+        self.binding.length_max = length_max;
+        self
+    }
+
+    pub fn workspace(mut self, workspace: u32) -> Self {
+        self.binding.workspace = Some(workspace);
+        self
+    }
+
+    pub fn active_hours(mut self, start: &str, end: &str) -> Self {
+        self.binding.active_hours = Some([start.to_string(), end.to_string()]);
+        self
+    }
+
+    pub fn idle_min_ms(mut self, idle_min_ms: u64) -> Self {
+        self.binding.idle_min_ms = Some(idle_min_ms);
+        self
+    }
+
+    pub fn passthrough(mut self, passthrough: bool) -> Self {
+        self.binding.passthrough = passthrough;
+        self
+    }
+
+    pub fn arm_required(mut self, arm_required: bool) -> Self {
+        self.binding.arm_required = arm_required;
+        self
+    }
+
+    /// panic on an invalid shape_svg, like a config with a typo in the code
+    pub fn build(mut self) -> Binding {
+        prepare_binding(&mut self.binding, &Templates::new())
+            .unwrap_or_else(|err| panic!("invalid binding : {err:#}"));
+        self.binding
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::{BindingBuilder, ConfigBuilder};
+    use crate::event::{Edge, EventType, MouseButton, Point, PointHistory};
+    use crate::points_to_angles::points_to_angles;
+
+    #[test]
+    fn test_builder() {
+        let mut shape = PointHistory::new();
+        shape.push(Point { x: 0, y: 0 });
+        shape.push(Point { x: 10, y: 0 });
+        shape.push(Point { x: 10, y: 10 });
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .binding(
+                BindingBuilder::new(MouseButton::Middle)
+                    .comment("top")
+                    .edges(vec![Edge::Top])
+                    .cmd_str("xdotool key 'Alt+Tab'")
+                    .build(),
+            )
+            .binding(
+                BindingBuilder::new(MouseButton::Right)
+                    .shape(shape.clone())
+                    .cmd_str("xlogo")
+                    .build(),
+            )
+            .build();
+
+        assert_eq!(config.shape_button, MouseButton::Right);
+        assert!(config.detect_edges);
+        let top = &config.bindings[0];
+        assert_eq!(top.event.event_type, EventType::Click);
+        assert_eq!(top.cmd, vec!["xdotool", "key", "Alt+Tab"]);
+        let shaped = &config.bindings[1];
+        assert_eq!(shaped.event.event_type, EventType::Shape);
+        assert_eq!(shaped.event.shapes_angles, vec![points_to_angles(&shape)]);
+    }
+}
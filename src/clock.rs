@@ -0,0 +1,74 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The source of the event times : the timing logic asks the clock instead of
+/// calling `Instant::now()`, so it can be tested with a `MockClock`.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// the real clock, used by the daemon
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock for the tests : the time only changes with `advance`.
+/// The clones share the same time.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        MockClock {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::clock::{Clock, MockClock, SystemClock};
+
+    #[test]
+    fn test_mock_clock() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+
+        let shared_clock = clock.clone();
+        shared_clock.advance(Duration::from_millis(250));
+        assert_eq!(clock.now() - start, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_system_clock() {
+        let clock = SystemClock;
+        let start = clock.now();
+        assert!(clock.now() >= start);
+    }
+}
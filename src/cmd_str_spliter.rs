@@ -48,7 +48,7 @@ pub fn str_cmd_to_array(cmd_str: &str) -> Vec<String> {
         .collect()
 }
 
-pub fn str_array_cmd_to_str_cmd(cmd_array: &Vec<String>) -> String {
+pub fn str_array_cmd_to_str_cmd(cmd_array: &[String]) -> String {
     cmd_array
         .iter()
         .map(|s| quote_cmd_part(s))
@@ -109,21 +109,15 @@ mod tests {
     fn test_str_array_cmd_to_str_cmd() {
         assert_eq!(
             r#"aa "" bb"#,
-            str_array_cmd_to_str_cmd(&vec!["aa".to_string(), "".to_string(), "bb".to_string()]),
-        );
-        assert_eq!(
-            "aa",
-            str_array_cmd_to_str_cmd(&Vec::from(vec!["aa".to_string()])),
-        );
-        assert_eq!(
-            "\"a a\"",
-            str_array_cmd_to_str_cmd(&Vec::from(vec!["a a".to_string()])),
+            str_array_cmd_to_str_cmd(&["aa".to_string(), "".to_string(), "bb".to_string()]),
         );
+        assert_eq!("aa", str_array_cmd_to_str_cmd(&["aa".to_string()]),);
+        assert_eq!("\"a a\"", str_array_cmd_to_str_cmd(&["a a".to_string()]),);
         assert_eq!(r#"uu "d dd\"d dd" bb"#, "uu \"d dd\\\"d dd\" bb");
         assert_eq!(
             r#"aa "d dd\"d dd" bb"#,
             str_array_cmd_to_str_cmd(
-                &vec!["aa", "d dd\"d dd", "bb"]
+                &["aa", "d dd\"d dd", "bb"]
                     .iter()
                     .map(|s| s.to_string())
                     .collect::<Vec<String>>()
@@ -133,7 +127,7 @@ mod tests {
         assert_eq!(
             r#"aa "\"bbb\"" --cc "d dd\"d dd" "ee ee" "ff f" 'g " ""\'gg' hh'hh h'hh"h h" "" ii"ii"i --jj="j j""#,
             str_array_cmd_to_str_cmd(
-                &vec![
+                &[
                     "aa",
                     "\"bbb\"",
                     "--cc",
@@ -3,8 +3,44 @@ use std::f64::consts::PI;
 use std::ops::Div;
 
 use log::trace;
+use serde::{Deserialize, Serialize};
+
+// the weight of the last angle of a FrontLoaded comparison is e^-FRONT_LOADED_DECAY
+const FRONT_LOADED_DECAY: f64 = 2.0;
+
+/// The weights of the angle differences along the shapes when they are compared
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum AngleWeighting {
+    // all the angles count the same
+    #[default]
+    #[serde(alias = "uniform")]
+    Uniform,
+    // the weights decrease exponentially along the shape : the first strokes count more
+    #[serde(alias = "front_loaded")]
+    FrontLoaded,
+}
+
+impl AngleWeighting {
+    /// the weight of the angle at progress (0 at the start of the shape, 1 at its end)
+    pub fn weight(self, progress: f64) -> f64 {
+        match self {
+            AngleWeighting::Uniform => 1.0,
+            AngleWeighting::FrontLoaded => (-FRONT_LOADED_DECAY * progress).exp(),
+        }
+    }
+}
 
 pub fn compare_angles_with_offset(vec_a: &Vec<f64>, vec_b: &Vec<f64>) -> f64 {
+    compare_angles_with_offset_weighted(vec_a, vec_b, AngleWeighting::Uniform)
+}
+
+/// `compare_angles_with_offset` with the angle differences weighted along the shapes
+pub fn compare_angles_with_offset_weighted(
+    vec_a: &Vec<f64>,
+    vec_b: &Vec<f64>,
+    weighting: AngleWeighting,
+) -> f64 {
+    let compare_angles = |a: &[f64], b: &[f64]| compare_angles_weighted(a, b, weighting);
     let mut diff_values: Vec<f64> = Vec::with_capacity(25);
     diff_values.push(compare_angles(vec_a, vec_b));
     let vec1: &Vec<f64>;
@@ -34,6 +70,11 @@ pub fn compare_angles_with_offset(vec_a: &Vec<f64>, vec_b: &Vec<f64>) -> f64 {
 }
 
 pub fn compare_angles(vec_a: &[f64], vec_b: &[f64]) -> f64 {
+    compare_angles_weighted(vec_a, vec_b, AngleWeighting::Uniform)
+}
+
+/// the weighted mean of the angle differences, in radians
+pub fn compare_angles_weighted(vec_a: &[f64], vec_b: &[f64], weighting: AngleWeighting) -> f64 {
     let smaller_vec: &[f64];
     let bigger_vec: &[f64];
 
@@ -48,9 +89,12 @@ pub fn compare_angles(vec_a: &[f64], vec_b: &[f64]) -> f64 {
     // TODO check vet1 len > min accept
 
     let mut diff = 0.0;
+    let mut total_weight = 0.0;
     for i in 0..smaller_vec.len() {
         let angle_ref: &f64 = smaller_vec.get(i).unwrap();
         let progress: f64 = (i as f64) / (smaller_vec.len() as f64);
+        let weight = weighting.weight(progress);
+        total_weight += weight;
         let angle_opt = bigger_vec.get(((bigger_vec.len() as f64) * progress) as usize);
         if let Some(angle) = angle_opt {
             let mut raw_diff = (angle_ref - angle).abs();
@@ -59,20 +103,25 @@ pub fn compare_angles(vec_a: &[f64], vec_b: &[f64]) -> f64 {
             } else {
                 raw_diff
             };
-            diff += raw_diff;
+            diff += weight * raw_diff;
         } else {
             // TODO
-            diff += PI;
+            diff += weight * PI;
         }
     }
-    diff.div(smaller_vec.len() as f64)
+    diff.div(total_weight)
 }
 
 #[cfg(test)]
 mod tests {
     use std::time::Instant;
 
-    use crate::compare_angles::{compare_angles, compare_angles_with_offset};
+    use std::f64::consts::FRAC_PI_2;
+
+    use crate::compare_angles::{
+        compare_angles, compare_angles_weighted, compare_angles_with_offset,
+        compare_angles_with_offset_weighted, AngleWeighting,
+    };
 
     #[test]
     fn test_compare_angles() {
@@ -113,4 +162,40 @@ mod tests {
         assert_eq!(diff, 0.159040066692836);
         println!("Time elapsed : {:?}", start.elapsed());
     }
+
+    #[test]
+    fn test_front_loaded_weighting() {
+        // 2 gestures that only differ by their first stroke : up then right, or right
+        let tail = vec![0.0; 80];
+        let up_first = [vec![FRAC_PI_2; 20], tail.clone()].concat();
+        let right_first = [vec![0.0; 20], tail.clone()].concat();
+        let uniform = compare_angles_weighted(&up_first, &right_first, AngleWeighting::Uniform);
+        let front_loaded =
+            compare_angles_weighted(&up_first, &right_first, AngleWeighting::FrontLoaded);
+        assert_eq!(uniform, compare_angles(&up_first, &right_first));
+        assert!((uniform - FRAC_PI_2 * 0.2).abs() < 1e-9, "{uniform}");
+        assert!(front_loaded > 1.5 * uniform, "{front_loaded} vs {uniform}");
+        assert!(
+            compare_angles_with_offset_weighted(
+                &up_first,
+                &right_first,
+                AngleWeighting::FrontLoaded
+            ) > compare_angles_with_offset(&up_first, &right_first)
+        );
+
+        // the end of the shapes counts less
+        let up_last = [tail, vec![FRAC_PI_2; 20]].concat();
+        let uniform = compare_angles_weighted(&up_last, &right_first, AngleWeighting::Uniform);
+        let front_loaded =
+            compare_angles_weighted(&up_last, &right_first, AngleWeighting::FrontLoaded);
+        assert!(front_loaded < uniform / 2.0, "{front_loaded} vs {uniform}");
+
+        // the same shapes
+        assert_eq!(
+            compare_angles_weighted(&up_first, &up_first, AngleWeighting::FrontLoaded),
+            0.0
+        );
+        let weighting: AngleWeighting = serde_json::from_str("\"front_loaded\"").unwrap();
+        assert_eq!(weighting, AngleWeighting::FrontLoaded);
+    }
 }
@@ -1,143 +1,805 @@
+use std::collections::{BTreeMap, HashSet};
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, Read, Write};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::str::FromStr;
-use std::sync::mpsc::channel;
+use std::sync::mpsc::{channel, Receiver, RecvError};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{env, fs, thread};
 
+use anyhow::{anyhow, bail, Context};
 use lazy_static::lazy_static;
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn};
 use notify::event::AccessKind::Close;
-use notify::EventKind::Access;
+use notify::EventKind::{Access, Remove};
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::args::Args;
-use crate::binding::Binding;
+use crate::binding::{ArmGesture, Binding};
+use crate::builder::{BindingBuilder, ConfigBuilder};
 use crate::cmd_str_spliter::{str_array_cmd_to_str_cmd, str_cmd_to_array};
-use crate::event::{EventType, MouseButton};
+use crate::compare_angles::AngleWeighting;
+use crate::direction_codes::direction_codes_to_points;
+use crate::event::{
+    AbortTrigger, Edge, EventType, KeyboardModifier, KeyboardState, MouseButton, Point,
+    PointHistory, RelativeTo, HISTO_SIZE,
+};
 use crate::points_to_angles::points_to_angles;
+use crate::repeat::MIN_REPEAT_MS;
+use crate::schedule::check_active_hours;
+use crate::svg_path::svg_path_to_points;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
     pub shape_button: MouseButton,
+    #[serde(serialize_with = "serialize_own_bindings")]
     pub bindings: Vec<Binding>,
+
+    // paths of other config files whose bindings are appended to this config,
+    // relative paths are resolved from the directory of the including file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub includes: Vec<String>,
+
+    // the canonical paths of the included files loaded, watched with the config file
+    #[serde(skip)]
+    pub included_paths: Vec<PathBuf>,
+
+    // false to skip the screen edges detection : the edges of the events stay empty
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub detect_edges: bool,
+
+    // the size in pixels of the corner boxes : TopLeft, TopRight… are added to the
+    // edges of the events in them, 0 to disable the corners
+    #[serde(default, skip_serializing_if = "is_zero_u32")]
+    pub corner_size: u32,
+
+    // Window to detect the edges and corners of the focused window instead of the screen,
+    // e.g. with a tiling window manager. The screen is used when the window is unknown.
+    #[serde(default, skip_serializing_if = "RelativeTo::is_screen")]
+    pub relative_to: RelativeTo,
+
+    // swap WheelUp & WheelDown, for the inverted hardware or natural scrolling
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub invert_wheel: bool,
+
+    // the number of recorded points from which a shape_button gesture is a shape,
+    // below it the press is processed like a simple click
+    #[serde(
+        default = "default_shape_min_points",
+        skip_serializing_if = "is_default_shape_min_points"
+    )]
+    pub shape_min_points: usize,
+
+    // the minimum time between 2 recorded points of a shape, 0 to record every mouse move
+    #[serde(default, skip_serializing_if = "is_zero_u64")]
+    pub sample_interval_ms: u64,
+
+    // the spacing in pixels of the points along a shape from which its angles are computed,
+    // whatever the cursor speed, 0 to use the recorded points
+    #[serde(default, skip_serializing_if = "is_zero_u32")]
+    pub resample_spacing: u32,
+
+    // the first and the last points dropped from a shape before computing its angles,
+    // e.g. the jitter of the button release
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub trim_start_points: usize,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub trim_end_points: usize,
+
+    // a shape_button release that moved at most this many pixels is a click, not a shape,
+    // 0 to classify by the number of points only
+    #[serde(default, skip_serializing_if = "is_zero_u32")]
+    pub click_max_move: u32,
+
+    // the shape_button gestures held longer are dropped, e.g. the accidental slow drags
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_gesture_ms: Option<u64>,
+
+    // a modifier held at the shape_button press records more points for this gesture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub long_gesture: Option<LongGesture>,
+
+    // the events that abort the shape being drawn, its release is processed like a click
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub abort_shape_on: Vec<AbortTrigger>,
+
+    // prepended to the command of every binding, e.g. ["systemd-run", "--user"]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command_prefix: Vec<String>,
+
+    // a shape with less direction changes is a straight drag : the shape bindings are skipped
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub min_direction_changes: usize,
+
+    // the gesture that arms the arm_required bindings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arm: Option<ArmGesture>,
+
+    // the nice value of the grab thread, e.g. -5 to keep the input handling responsive
+    // under load, applied when the grab starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grab_nice: Option<i32>,
+
+    // the classes of the windows in which mouse-actions does nothing, e.g. a VM window
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_window_classes: Vec<String>,
+
+    // the weights of the angles along the shapes when they are compared, FrontLoaded for
+    // the gestures whose first strokes are the most distinctive
+    #[serde(default, skip_serializing_if = "is_uniform")]
+    pub angle_weighting: AngleWeighting,
+
+    // a shape match with a lower score (0 to 100 %) runs the binding but lets the
+    // event reach the application
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consume_threshold: Option<f64>,
+
+    // log & notify the closest binding when a shape matches nothing
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suggest_closest: bool,
+
+    // played when a shape matches a binding, or nothing : a sound file or a command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound_on_match: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound_on_fail: Option<String>,
+
+    // the number of backups of the previous config files kept by save_config
+    #[serde(
+        default = "default_backup_count",
+        skip_serializing_if = "is_default_backup_count"
+    )]
+    pub backup_count: usize,
+
+    // the command line opening the config file, e.g. "code --wait", the file path is
+    // appended : see open_config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor_command: Option<String>,
+
+    // the named shapes referenced by the shape_ref of the binding events, e.g. a
+    // "circle" shared by several bindings
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: Templates,
+}
+
+/// the shapes of the templates by name, each template has one or more shapes like the
+/// shapes_xy of an event
+pub type Templates = BTreeMap<String, Vec<PointHistory>>;
+
+/// the config written on the first run : the Right shape_button, no binding and
+/// the default values of the options
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            shape_button: MouseButton::Right,
+            bindings: vec![],
+            includes: vec![],
+            included_paths: vec![],
+            detect_edges: true,
+            corner_size: 0,
+            relative_to: RelativeTo::Screen,
+            invert_wheel: false,
+            shape_min_points: DEFAULT_SHAPE_MIN_POINTS,
+            sample_interval_ms: 0,
+            resample_spacing: 0,
+            trim_start_points: 0,
+            trim_end_points: 0,
+            click_max_move: 0,
+            max_gesture_ms: None,
+            long_gesture: None,
+            abort_shape_on: vec![],
+            command_prefix: vec![],
+            min_direction_changes: 0,
+            arm: None,
+            grab_nice: None,
+            exclude_window_classes: vec![],
+            angle_weighting: AngleWeighting::Uniform,
+            consume_threshold: None,
+            suggest_closest: false,
+            sound_on_match: None,
+            sound_on_fail: None,
+            backup_count: DEFAULT_BACKUP_COUNT,
+            editor_command: None,
+            templates: Templates::new(),
+        }
+    }
+}
+
+impl Config {
+    /// the number of points recorded for a shape : more with the long_gesture modifier held
+    pub fn shape_capacity(&self, keyboard_state: &KeyboardState) -> usize {
+        match &self.long_gesture {
+            Some(long_gesture) if long_gesture.modifier.is_held(keyboard_state) => {
+                long_gesture.max_points
+            }
+            _ => HISTO_SIZE,
+        }
+    }
+}
+
+/// The elaborate gestures drawn with the modifier held at the shape_button press
+/// can have up to max_points points, instead of the default capacity.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LongGesture {
+    pub modifier: KeyboardModifier,
+
+    #[serde(default = "default_long_gesture_max_points")]
+    pub max_points: usize,
+}
+
+fn default_long_gesture_max_points() -> usize {
+    10 * HISTO_SIZE
+}
+
+// the largest max_points of long_gesture, the capacity is allocated at each press
+pub const MAX_LONG_GESTURE_POINTS: usize = 100 * HISTO_SIZE;
+
+/// max_points can hold a shape and isn't too large to allocate
+fn check_long_gesture(config: &Config) -> anyhow::Result<()> {
+    if let Some(long_gesture) = &config.long_gesture {
+        if !(config.shape_min_points..=MAX_LONG_GESTURE_POINTS).contains(&long_gesture.max_points) {
+            bail!(
+                "the max_points {} of long_gesture is out of range, it must be between the \
+                 shape_min_points {} and {MAX_LONG_GESTURE_POINTS}",
+                long_gesture.max_points,
+                config.shape_min_points
+            );
+        }
+    }
+    Ok(())
+}
+
+pub const DEFAULT_SHAPE_MIN_POINTS: usize = 10;
+
+fn default_shape_min_points() -> usize {
+    DEFAULT_SHAPE_MIN_POINTS
+}
+
+fn is_default_shape_min_points(value: &usize) -> bool {
+    *value == DEFAULT_SHAPE_MIN_POINTS
+}
+
+pub const DEFAULT_BACKUP_COUNT: usize = 5;
+
+fn default_backup_count() -> usize {
+    DEFAULT_BACKUP_COUNT
+}
+
+fn is_default_backup_count(value: &usize) -> bool {
+    *value == DEFAULT_BACKUP_COUNT
+}
+
+pub(crate) fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+fn is_zero_u64(value: &u64) -> bool {
+    *value == 0
+}
+
+fn is_zero_u32(value: &u32) -> bool {
+    *value == 0
+}
+
+pub(crate) fn default_true() -> bool {
+    true
+}
+
+pub(crate) fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn is_uniform(weighting: &AngleWeighting) -> bool {
+    *weighting == AngleWeighting::Uniform
+}
+
+/// the part of a config file used when it is included from another config
+#[derive(Deserialize, Debug)]
+struct IncludedConfig {
+    #[serde(default)]
+    bindings: Vec<Binding>,
+    #[serde(default)]
+    includes: Vec<String>,
+}
+
+// the included bindings stay in their own file : don't write them in the main config
+fn serialize_own_bindings<S>(bindings: &[Binding], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_seq(bindings.iter().filter(|b| b.included_from.is_none()))
+}
+
+pub fn load(file_path: &str) -> anyhow::Result<Config> {
+    let json_config =
+        fs::read_to_string(file_path).with_context(|| format!("couldn't read {file_path:?}"))?;
+    load_with_includes(&json_config, fs::canonicalize(file_path)?)
+}
+
+/// the `--config-path` value to read the JSON config from stdin
+pub const STDIN_CONFIG_PATH: &str = "-";
+
+pub fn is_stdin_config(config_path_from_args: &Option<String>) -> bool {
+    config_path_from_args.as_deref() == Some(STDIN_CONFIG_PATH)
+}
+
+/// load a piped config, its relative includes are resolved from the current directory
+pub fn load_from_reader(mut reader: impl Read) -> anyhow::Result<Config> {
+    let mut json_config = String::new();
+    reader.read_to_string(&mut json_config)?;
+    load_with_includes(&json_config, env::current_dir()?.join(STDIN_CONFIG_PATH))
+}
+
+fn load_with_includes(json_config: &str, config_path: PathBuf) -> anyhow::Result<Config> {
+    let mut config = load_from_str(json_config)?;
+
+    let mut include_stack = vec![config_path];
+    let mut included_paths = vec![];
+    let included_bindings = load_includes(
+        &config.includes,
+        &mut include_stack,
+        &mut included_paths,
+        &config.templates,
+    )
+    .context("config includes error")?;
+    config.bindings.extend(included_bindings);
+    config.included_paths = included_paths;
+
+    check_bindings(&mut config)?;
+    Ok(config)
 }
 
-pub fn load(file_path: &str) -> Config {
-    let json_config = fs::read_to_string(file_path).unwrap();
-    let mut config = load_from_str(&json_config);
+/// the checks and fixes of the bindings of the config, with the included bindings
+fn check_bindings(config: &mut Config) -> anyhow::Result<()> {
+    // an explicit id addresses a single binding : it's unique, and it isn't the derived
+    // id of another binding. The bindings that differ only by their other conditions
+    // (e.g. `when`) have the same derived id, it's only ambiguous for the external tools.
+    let mut ids = HashSet::new();
+    let mut derived_ids = HashSet::new();
+    for binding in config.bindings.iter() {
+        if binding.id.is_none() && !derived_ids.insert(binding.stable_id()) {
+            warn!(
+                "several bindings have the derived id {:?} (the binding {:?}), give them \
+                 explicit ids to address them from the external tools",
+                binding.stable_id(),
+                binding.comment
+            );
+        }
+    }
+    if let Some(binding) = config.bindings.iter().find(|binding| {
+        binding
+            .id
+            .as_ref()
+            .is_some_and(|id| !ids.insert(id.clone()) || derived_ids.contains(id))
+    }) {
+        bail!(
+            "several bindings have the id {:?} (the binding {:?}), the explicit ids must be \
+             unique and distinct from the derived ids",
+            binding.stable_id(),
+            binding.comment
+        );
+    }
 
     // FIXME
     let first_button_only_error = config.bindings.iter().any(|b| {
-        b.event.button == MouseButton::Left
+        b.event.matches_button(MouseButton::Left)
             && b.event.modifiers.is_empty()
-            && b.event.shapes_xy.is_empty()
+            && b.event.shapes_angles.is_empty()
             && b.event.edges.is_empty()
     });
-    // FIXME
-    assert!(
-        !first_button_only_error,
-        "there is an event for left button only !"
-    );
+    if first_button_only_error {
+        bail!("there is an event for left button only !");
+    }
 
     // FIXME
     let shape_empty_error = config
         .bindings
         .iter()
         .filter(|b| b.event.event_type == EventType::Shape)
-        .any(|b| b.event.shapes_xy.is_empty());
-    // FIXME
-    assert!(
-        !shape_empty_error,
-        "event_type=Shape but shapes_xy is empty !"
-    );
+        .any(|b| {
+            b.event.shapes_xy.is_empty()
+                && b.event.shapes_svg.is_empty()
+                && b.event.shapes_directions.is_empty()
+                && b.event.shape_ref.is_none()
+        });
+    if shape_empty_error {
+        bail!(
+            "event_type=Shape but shapes_xy, shapes_svg, shapes_directions and shape_ref are empty !"
+        );
+    }
 
     config
         .bindings
         .iter_mut()
-        .filter(|b| b.event.event_type != EventType::Shape && !b.event.shapes_xy.is_empty())
+        .filter(|b| b.event.event_type != EventType::Shape && !b.event.shapes_angles.is_empty())
         .for_each(|b| b.event.event_type = EventType::Shape);
 
-    config
+    Ok(())
+}
+
+/// Load the bindings of the included files, recursively.
+/// `include_stack` contains the canonical paths of the files being loaded,
+/// the last one is the file that declares `includes`. `included_paths` contains
+/// the files already loaded : a file included twice (e.g. A→B→D and A→C→D) is loaded once.
+fn load_includes(
+    includes: &[String],
+    include_stack: &mut Vec<PathBuf>,
+    included_paths: &mut Vec<PathBuf>,
+    templates: &Templates,
+) -> anyhow::Result<Vec<Binding>> {
+    let mut bindings = vec![];
+    let parent_dir = include_stack
+        .last()
+        .and_then(|path| path.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    for include in includes {
+        let include_path = parent_dir.join(include);
+        let canonical_path = match fs::canonicalize(&include_path) {
+            Ok(path) => path,
+            Err(err) => {
+                warn!("ignore the missing config include {include_path:?} : {err}");
+                continue;
+            }
+        };
+        if include_stack.contains(&canonical_path) {
+            return Err(anyhow!(
+                "include loop detected : {:?} → {:?}",
+                include_stack,
+                canonical_path
+            ));
+        }
+        if included_paths.contains(&canonical_path) {
+            debug!("skip the config include {canonical_path:?} : already loaded");
+            continue;
+        }
+        included_paths.push(canonical_path.clone());
+        debug!("load the config include {canonical_path:?}");
+        let json_config = fs::read_to_string(&canonical_path)
+            .with_context(|| format!("couldn't read {canonical_path:?}"))?;
+        let mut included: IncludedConfig = serde_json::from_str(&json_config)
+            .with_context(|| format!("couldn't parse {canonical_path:?}"))?;
+        for binding in included.bindings.iter_mut() {
+            prepare_binding(binding, templates)
+                .with_context(|| format!("in {canonical_path:?}"))?;
+            binding.included_from = Some(canonical_path.clone());
+        }
+        bindings.append(&mut included.bindings);
+
+        include_stack.push(canonical_path);
+        bindings.append(&mut load_includes(
+            &included.includes,
+            include_stack,
+            included_paths,
+            templates,
+        )?);
+        include_stack.pop();
+    }
+    Ok(bindings)
 }
 
-pub fn load_from_str(json_config: &str) -> Config {
+/// parse the JSON config, without its includes : an error for an invalid JSON or binding
+pub fn load_from_str(json_config: &str) -> anyhow::Result<Config> {
     let start = Instant::now();
-    let mut config: Config = serde_json::from_str(&json_config).unwrap();
-    // xy → angles
-    for mut binding in &mut config.bindings {
-        binding.event.shapes_angles = binding
-            .event
-            .shapes_xy
-            .iter()
-            .map(|shape_xy| points_to_angles(&shape_xy))
-            .collect();
-        if binding.cmd_str.is_empty() {
-            binding.cmd_str = str_array_cmd_to_str_cmd(&binding.cmd);
-            debug!(
-                "[v0.4.3 migration] Convert cmd array to cmd_str : {:?} → {}",
-                &binding.cmd, binding.cmd_str
+    let mut config: Config =
+        serde_json::from_str(json_config).context("couldn't parse the JSON config")?;
+    check_long_gesture(&config)?;
+    for binding in config.bindings.iter_mut() {
+        prepare_binding(binding, &config.templates)?;
+    }
+    if let Some(arm) = config.arm.as_mut() {
+        prepare_binding(&mut arm.binding, &config.templates).context("in the arm gesture")?;
+    }
+    debug!("load_from_str duration : {:?}", start.elapsed());
+    Ok(config)
+}
+
+// xy → angles & cmd_str → cmd
+pub(crate) fn prepare_binding(binding: &mut Binding, templates: &Templates) -> anyhow::Result<()> {
+    let mut shapes_angles: Vec<Vec<f64>> = binding
+        .event
+        .shapes_xy
+        .iter()
+        .map(points_to_angles)
+        .collect();
+    for path in binding.event.shapes_svg.iter() {
+        let points = svg_path_to_points(path).with_context(|| {
+            format!(
+                "invalid shapes_svg {path:?} of the binding {:?}",
+                binding.comment
+            )
+        })?;
+        shapes_angles.push(points_to_angles(&points));
+    }
+    for codes in binding.event.shapes_directions.iter() {
+        let points = direction_codes_to_points(codes).with_context(|| {
+            format!(
+                "invalid shapes_directions {codes:?} of the binding {:?}",
+                binding.comment
+            )
+        })?;
+        shapes_angles.push(points_to_angles(&points));
+    }
+    if let Some(shape_ref) = &binding.event.shape_ref {
+        let template = templates.get(shape_ref).ok_or_else(|| {
+            anyhow!(
+                "unknown template {shape_ref:?} in the shape_ref of the binding {:?}",
+                binding.comment
+            )
+        })?;
+        shapes_angles.extend(template.iter().map(points_to_angles));
+    }
+    binding.event.shapes_angles = shapes_angles;
+    if binding.location_sensitive && binding.event.region.is_none() {
+        bail!(
+            "the binding {:?} is location_sensitive but its event has no region",
+            binding.comment
+        );
+    }
+    if let Some(limits) = &binding.limits {
+        limits
+            .check()
+            .with_context(|| format!("invalid limits of the binding {:?}", binding.comment))?;
+    }
+    if let Some(repeat_ms) = binding.repeat_ms.filter(|ms| *ms < MIN_REPEAT_MS) {
+        bail!(
+            "the repeat_ms {repeat_ms} of the binding {:?} is too short, the minimum is {MIN_REPEAT_MS}ms",
+            binding.comment
+        );
+    }
+    if let Some(active_hours) = &binding.active_hours {
+        check_active_hours(active_hours).with_context(|| {
+            format!("invalid active_hours of the binding {:?}", binding.comment)
+        })?;
+    }
+    if binding.cmd_str.is_empty() {
+        // the cmd array is kept as is, for the exact arguments
+        binding.cmd_str = str_array_cmd_to_str_cmd(&binding.cmd);
+        debug!(
+            "[v0.4.3 migration] Convert cmd array to cmd_str : {:?} → {}",
+            &binding.cmd, binding.cmd_str
+        );
+    } else {
+        let cmd = str_cmd_to_array(&binding.cmd_str);
+        if !binding.cmd.is_empty() && binding.cmd != cmd {
+            warn!(
+                "the binding {:?} has a cmd_str and a cmd, the cmd_str is used : {} (cmd {:?} ignored)",
+                binding.comment, binding.cmd_str, binding.cmd
             );
         }
-        binding.cmd = str_cmd_to_array(&binding.cmd_str);
+        binding.cmd = cmd;
         debug!(
             "set cmd array from cmd_str : {} → {:?}",
             binding.cmd_str, &binding.cmd
         );
     }
-    debug!("load_from_str duration : {:?}", start.elapsed());
-    config
+    Ok(())
 }
 
+const CONFIG_FILE_NAME: &str = "mouse-actions.json";
+
 pub fn get_config_path(config_path_from_args: &Option<String>) -> PathBuf {
     if let Some(config_path) = config_path_from_args {
         PathBuf::from_str(config_path).unwrap()
     } else {
-        [
-            dirs_sys::home_dir().unwrap().to_str().unwrap(),
-            ".config",
-            "mouse-actions.json",
-        ]
-        .iter()
-        .collect()
+        default_config_path(
+            env::var("XDG_CONFIG_HOME").ok(),
+            &dirs_sys::home_dir().unwrap(),
+        )
+    }
+}
+
+/// `$XDG_CONFIG_HOME/mouse-actions.json`, or `~/.config/mouse-actions.json` if
+/// XDG_CONFIG_HOME is not set. The legacy path `~/.config/mouse-actions.json` is
+/// still used if it exists and the XDG one doesn't.
+fn default_config_path(xdg_config_home: Option<String>, home_dir: &Path) -> PathBuf {
+    let legacy_path = home_dir.join(".config").join(CONFIG_FILE_NAME);
+    // the XDG spec : ignore XDG_CONFIG_HOME if empty or relative
+    match xdg_config_home.map(PathBuf::from) {
+        Some(xdg_config_home) if xdg_config_home.is_absolute() => {
+            let xdg_path = xdg_config_home.join(CONFIG_FILE_NAME);
+            if !xdg_path.exists() && legacy_path.exists() {
+                info!(
+                    "Use the legacy config path {legacy_path:?}, move it to {xdg_path:?} to follow XDG_CONFIG_HOME"
+                );
+                legacy_path
+            } else {
+                xdg_path
+            }
+        }
+        _ => legacy_path,
+    }
+}
+
+/// Merge the overlay config file into config : its bindings (and its included bindings)
+/// are appended, the other fields it sets replace the ones of config.
+pub fn apply_overlay(config: &mut Config, overlay_path: &Path) -> anyhow::Result<()> {
+    let canonical_path = fs::canonicalize(overlay_path)
+        .with_context(|| format!("couldn't find the overlay {overlay_path:?}"))?;
+    let json_config = fs::read_to_string(&canonical_path)
+        .with_context(|| format!("couldn't read {canonical_path:?}"))?;
+    let parse_error = || format!("couldn't parse {canonical_path:?}");
+    let mut fields: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(&json_config).with_context(parse_error)?;
+    let mut overlay: IncludedConfig =
+        serde_json::from_str(&json_config).with_context(parse_error)?;
+
+    fields.remove("bindings");
+    fields.remove("includes");
+    let mut merged = serde_json::to_value(&*config)?;
+    if let Some(merged_fields) = merged.as_object_mut() {
+        merged_fields.extend(fields);
+    }
+    let mut merged =
+        load_from_str(&merged.to_string()).with_context(|| format!("in {canonical_path:?}"))?;
+
+    merged.bindings = std::mem::take(&mut config.bindings);
+    merged.included_paths = std::mem::take(&mut config.included_paths);
+    // the overlay can replace the templates of the previous bindings
+    for binding in merged.bindings.iter_mut() {
+        prepare_binding(binding, &merged.templates)
+            .with_context(|| format!("in {canonical_path:?}"))?;
+    }
+    for binding in overlay.bindings.iter_mut() {
+        prepare_binding(binding, &merged.templates)
+            .with_context(|| format!("in {canonical_path:?}"))?;
+        binding.included_from = Some(canonical_path.clone());
     }
+    merged.bindings.append(&mut overlay.bindings);
+    let mut include_stack = vec![canonical_path];
+    merged.bindings.append(&mut load_includes(
+        &overlay.includes,
+        &mut include_stack,
+        &mut merged.included_paths,
+        &merged.templates,
+    )?);
+    check_bindings(&mut merged)?;
+    *config = merged;
+    Ok(())
+}
+
+/// load the config file, then merge the overlay files in order
+pub fn load_with_overlays(config_path: &Path, overlay_paths: &[PathBuf]) -> anyhow::Result<Config> {
+    let mut config = load(config_path.to_str().unwrap())?;
+    for overlay_path in overlay_paths {
+        apply_overlay(&mut config, overlay_path)?;
+    }
+    Ok(config)
 }
 
 pub fn get_config(config_path: &Path) -> Config {
     load(config_path.to_str().unwrap())
+        .unwrap_or_else(|err| panic!("couldn't load the config {config_path:?} : {err:#}"))
+}
+
+// the watcher is rebuilt after these consecutive watch errors
+const WATCHER_MAX_ERRORS: u32 = 5;
+const WATCHER_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+type WatchReceiver = Receiver<notify::Result<notify::Event>>;
+
+#[derive(Debug)]
+enum WatchAction {
+    Reload,
+    Ignore,
+    Error(notify::Error),
+    // the watcher is dead or doesn't watch the file anymore
+    Rebuild(&'static str),
+}
+
+fn watch_action(received: Result<notify::Result<notify::Event>, RecvError>) -> WatchAction {
+    match received {
+        Ok(Ok(notify::Event {
+            kind: Access(Close(notify::event::AccessMode::Write)),
+            ..
+        })) => WatchAction::Reload,
+        // e.g. an editor replacing the file on save : the removed file is no longer watched
+        Ok(Ok(notify::Event {
+            kind: Remove(_), ..
+        })) => WatchAction::Rebuild("the watched file has been removed"),
+        Ok(Ok(event)) => {
+            trace!("watcher: ignored event: {:?}", event);
+            WatchAction::Ignore
+        }
+        Ok(Err(err)) => WatchAction::Error(err),
+        Err(RecvError) => WatchAction::Rebuild("the watcher channel is disconnected"),
+    }
+}
+
+fn new_watcher(
+    config_path: &Path,
+    overlay_paths: &[PathBuf],
+    included_paths: &[PathBuf],
+) -> notify::Result<(RecommendedWatcher, WatchReceiver)> {
+    let (tx, rx) = channel();
+    let mut watcher = RecommendedWatcher::new(tx, notify::Config::default())?;
+    watcher.watch(config_path, RecursiveMode::NonRecursive)?;
+    for overlay_path in overlay_paths.iter() {
+        if let Err(err) = watcher.watch(overlay_path, RecursiveMode::NonRecursive) {
+            warn!("couldn't watch the overlay {overlay_path:?} : {err}");
+        }
+    }
+    for included_path in included_paths.iter() {
+        if let Err(err) = watcher.watch(included_path, RecursiveMode::NonRecursive) {
+            warn!("couldn't watch the include {included_path:?} : {err}");
+        }
+    }
+    Ok((watcher, rx))
+}
+
+/// Replace the config with the reloaded one. The runtime state isn't in the config : the
+/// armed bindings, the repeats and the spawned commands survive the reload.
+fn reload_config(config: &Arc<Mutex<Config>>, config_path: &Path, overlay_paths: &[PathBuf]) {
+    info!("Reload the config !");
+    match load_with_overlays(config_path, overlay_paths) {
+        Ok(new_config) => *config.lock().unwrap() = new_config,
+        Err(err) => error!("keep the previous config, reload error : {err:#}"),
+    }
 }
 
-pub fn watch_config(config: Arc<Mutex<Config>>, config_path: PathBuf) {
+/// Reload the config on each change of its files. A dead watcher is rebuilt, and the config
+/// reloaded as a change may have been missed : the live reload survives the transient errors.
+pub fn watch_config(config: Arc<Mutex<Config>>, config_path: PathBuf, overlay_paths: Vec<PathBuf>) {
     thread::Builder::new()
         .name("watch_config".to_string())
         .spawn(move || {
             info!("Watch the config {:?} !", config_path);
-            let (tx, rx) = channel();
-            let mut watcher = RecommendedWatcher::new(tx, notify::Config::default()).unwrap();
-            watcher
-                .watch(config_path.as_path(), RecursiveMode::NonRecursive)
-                .unwrap();
-
+            let mut rebuilt = false;
             loop {
-                match rx.recv() {
-                    Ok(Ok(notify::Event {
-                        kind: Access(Close(notify::event::AccessMode::Write)),
-                        ..
-                    })) => {
-                        info!("Reload the config !");
-                        *config.lock().unwrap() = get_config(&config_path);
-                    }
-                    Ok(event) => trace!("watcher: broken event: {:?}", event),
-                    Err(e) => {
-                        error!("watcher: watch error: {:?}", e);
-                        thread::sleep(std::time::Duration::from_secs(1));
+                let included_paths = config.lock().unwrap().included_paths.clone();
+                // the watcher is dropped at the end of the iteration, before its rebuild
+                let (_watcher, rx) =
+                    match new_watcher(&config_path, &overlay_paths, &included_paths) {
+                        Ok(watcher) => watcher,
+                        Err(err) => {
+                            error!("couldn't watch the config {config_path:?}, retry : {err:?}");
+                            thread::sleep(WATCHER_RETRY_DELAY);
+                            continue;
+                        }
+                    };
+                if rebuilt {
+                    info!("watcher rebuilt");
+                    reload_config(&config, &config_path, &overlay_paths);
+                }
+                rebuilt = true;
+
+                let mut errors = 0;
+                loop {
+                    match watch_action(rx.recv()) {
+                        WatchAction::Reload => {
+                            errors = 0;
+                            reload_config(&config, &config_path, &overlay_paths);
+                            if config.lock().unwrap().included_paths != included_paths {
+                                // the config is up to date, only the watched files change
+                                info!("the config includes changed : watch the new ones");
+                                rebuilt = false;
+                                break;
+                            }
+                        }
+                        WatchAction::Ignore => {}
+                        WatchAction::Error(err) => {
+                            error!("watcher: watch error: {:?}", err);
+                            errors += 1;
+                            if errors >= WATCHER_MAX_ERRORS {
+                                warn!("{errors} consecutive watch errors : rebuild the watcher");
+                                break;
+                            }
+                            thread::sleep(WATCHER_RETRY_DELAY);
+                        }
+                        WatchAction::Rebuild(reason) => {
+                            warn!("{reason} : rebuild the watcher");
+                            break;
+                        }
                     }
                 }
+                // no delay to watch the new includes, the config is already reloaded
+                if rebuilt {
+                    thread::sleep(WATCHER_RETRY_DELAY);
+                }
             }
         })
         .unwrap();
@@ -145,37 +807,118 @@ pub fn watch_config(config: Arc<Mutex<Config>>, config_path: PathBuf) {
 
 pub fn init_config_file_if_not_exists(config_path: &Path) {
     if !config_path.exists() {
-        let empty_config = Config {
-            shape_button: MouseButton::Right,
-            bindings: vec![],
-        };
-        let serialized = serde_json::to_string_pretty(&empty_config).unwrap();
+        write_new_config_file(config_path, &Config::default());
+    }
+}
 
-        let mut config_file = match File::create(&config_path) {
-            Err(err) => panic!(
-                "couldn't create config file {}: {}",
-                config_path.display(),
-                err
-            ),
-            Ok(file) => file,
-        };
+/// write a starter config with some example bindings, if the config file doesn't exist
+/// or is still the empty config written on the first run
+pub fn init_config_file_with_examples(config_path: &Path) -> bool {
+    if config_path.exists() && !is_empty_config_file(config_path) {
+        false
+    } else {
+        write_new_config_file(config_path, &examples_config());
+        true
+    }
+}
+
+fn is_empty_config_file(config_path: &Path) -> bool {
+    fs::read_to_string(config_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Config>(&content).ok())
+        .is_some_and(|config| config.bindings.is_empty() && config.includes.is_empty())
+}
+
+pub fn examples_config() -> Config {
+    // an L : from the top to the bottom, then to the right
+    let mut l_shape = PointHistory::new();
+    (0..=20).for_each(|i| l_shape.push(Point { x: 0, y: i * 50 }));
+    (1..=20).for_each(|i| l_shape.push(Point { x: i * 25, y: 1000 }));
+
+    ConfigBuilder::new(MouseButton::Right)
+        .binding(
+            BindingBuilder::new(MouseButton::Middle)
+                .comment("Example : middle click on the top edge of the screen → notification")
+                .edges(vec![Edge::Top])
+                .cmd_str("notify-send mouse-actions 'middle click on the top edge'")
+                .build(),
+        )
+        .binding(
+            BindingBuilder::new(MouseButton::Right)
+                .comment("Example : draw an L with the right button pressed → notification")
+                .shape(l_shape)
+                .cmd_str("notify-send mouse-actions 'L shape'")
+                .build(),
+        )
+        .build()
+}
+
+fn write_new_config_file(config_path: &Path, config: &Config) {
+    if let Some(config_dir) = config_path.parent() {
+        let _ = fs::create_dir_all(config_dir);
+    }
+    let serialized = serde_json::to_string_pretty(config).unwrap();
+
+    let mut config_file = match File::create(config_path) {
+        Err(err) => panic!(
+            "couldn't create config file {}: {}",
+            config_path.display(),
+            err
+        ),
+        Ok(file) => file,
+    };
+
+    match config_file.write_all(serialized.as_bytes()) {
+        Err(err) => panic!("couldn't write to {}: {}", config_path.display(), err),
+        Ok(_) => println!("successfully wrote to {}", config_path.display()),
+    }
+}
 
-        match config_file.write_all(serialized.as_bytes()) {
-            Err(err) => panic!("couldn't write to {}: {}", config_path.display(), err),
-            Ok(_) => println!("successfully wrote to {}", config_path.display()),
+/// Backup the config file before overwriting it : the previous versions are kept in
+/// `<config file>.bak.1` (the newest) … `<config file>.bak.<backup_count>` (the oldest).
+pub fn rotate_backups(config_path: &Path, backup_count: usize) -> io::Result<()> {
+    if backup_count == 0 || !config_path.exists() {
+        return Ok(());
+    }
+    let file_name = config_path.file_name().unwrap().to_str().unwrap();
+    let backup_path = |index: usize| {
+        config_path
+            .parent()
+            .unwrap()
+            .join(format!("{file_name}.bak.{index}"))
+    };
+    let _ = fs::remove_file(backup_path(backup_count));
+    for index in (1..backup_count).rev() {
+        if backup_path(index).exists() {
+            fs::rename(backup_path(index), backup_path(index + 1))?;
         }
     }
+    fs::copy(config_path, backup_path(1))?;
+    Ok(())
+}
+
+/// the canonical JSON of the config : pretty, in the field order of the structs,
+/// without the computed fields (shapes_angles, cmd) and the included bindings
+pub fn to_canonical_json(config: &Config) -> String {
+    serde_json::to_string_pretty(config).unwrap()
+}
+
+/// rewrite the config file in the canonical format, return false if it already was
+pub fn format_config_file(config_path: &Path) -> anyhow::Result<bool> {
+    let config_path_str = config_path.to_str().context("invalid config path")?;
+    let config = load(config_path_str)?;
+    if fs::read_to_string(config_path)? == to_canonical_json(&config) {
+        return Ok(false);
+    }
+    save_config(&config, &Some(config_path_str.to_string()));
+    Ok(true)
 }
 
 pub fn save_config(config: &Config, config_path_from_args: &Option<String>) {
-    let serialized = serde_json::to_string_pretty(&config).unwrap();
+    let serialized = to_canonical_json(config);
     let config_path = get_config_path(config_path_from_args);
-    let config_path_bak = config_path.parent().unwrap().join(format!(
-        "{}.bak",
-        config_path.file_name().unwrap().to_str().unwrap()
-    ));
-    let _ = fs::remove_file(&config_path_bak);
-    fs::copy(&config_path, &config_path_bak).expect("Error while backup the previous config file");
+    rotate_backups(&config_path, config.backup_count)
+        .expect("Error while backup the previous config file");
     let mut config_file = fs::OpenOptions::new()
         .write(true)
         .create(true)
@@ -189,27 +932,75 @@ pub fn save_config(config: &Config, config_path_from_args: &Option<String>) {
     }
 }
 
-pub fn open_config(config_path: PathBuf) {
-    println!("Open config file with xdg-open : {:?}", config_path);
-    Command::new("xdg-open")
-        .args(config_path.to_str())
+/// the command line opening the config file : the editor_command of the config, else
+/// $EDITOR, else $VISUAL, else xdg-open
+pub fn editor_command_line(
+    editor_command: Option<&str>,
+    editor: Option<String>,
+    visual: Option<String>,
+) -> Vec<String> {
+    [editor_command.map(str::to_string), editor, visual]
+        .into_iter()
+        .flatten()
+        .map(|cmd| str_cmd_to_array(&cmd))
+        .find(|cmd| !cmd.is_empty())
+        .unwrap_or_else(|| vec!["xdg-open".to_string()])
+}
+
+pub fn open_config(config_path: PathBuf, editor_command: Option<&str>) -> anyhow::Result<()> {
+    let mut cmd = editor_command_line(
+        editor_command,
+        env::var("EDITOR").ok(),
+        env::var("VISUAL").ok(),
+    );
+    cmd.push(config_path.to_string_lossy().to_string());
+    println!("Open config file with {:?}", cmd);
+    let status = Command::new(&cmd[0])
+        .args(&cmd[1..])
         .status()
-        .map_err(|err| error!("Command err: {:?}", err))
-        .ok();
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => anyhow!(
+                "the editor command {:?} isn't found, set the editor_command of the config, $EDITOR or $VISUAL",
+                cmd[0]
+            ),
+            _ => anyhow!("couldn't run the editor command {cmd:?} : {err}"),
+        })?;
+    if !status.success() {
+        bail!("the editor command {cmd:?} failed ({status})");
+    }
+    Ok(())
 }
 
 pub fn get_config_from_args(args: &Args, watch_config_enabled: bool) -> Arc<Mutex<Config>> {
-    let config_path = get_config_path(&args.config_path);
+    let overlay_paths = args
+        .overlay_config_paths()
+        .iter()
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    if is_stdin_config(&args.main_config_path()) {
+        debug!("read the config from stdin");
+        let mut config = load_from_reader(io::stdin())
+            .unwrap_or_else(|err| panic!("couldn't load the config from stdin : {err:#}"));
+        for overlay_path in overlay_paths.iter() {
+            apply_overlay(&mut config, overlay_path).unwrap_or_else(|err| {
+                panic!("couldn't load the overlay {overlay_path:?} : {err:#}")
+            });
+        }
+        return Arc::new(Mutex::new(config));
+    }
+    let config_path = get_config_path(&args.main_config_path());
     init_config_file_if_not_exists(&config_path);
-    let config: Arc<Mutex<Config>> = Arc::new(Mutex::new(get_config(&config_path)));
+    let config = load_with_overlays(&config_path, &overlay_paths)
+        .unwrap_or_else(|err| panic!("couldn't load the config {config_path:?} : {err:#}"));
+    let config: Arc<Mutex<Config>> = Arc::new(Mutex::new(config));
     if watch_config_enabled {
-        watch_config(config.clone(), config_path.clone());
+        watch_config(config.clone(), config_path.clone(), overlay_paths);
     }
     config
 }
 
 pub fn get_json_config(args: &Args) -> String {
-    let config = get_config_from_args(&args, false);
+    let config = get_config_from_args(args, false);
     let c = config.lock().unwrap();
     let serialized = serde_json::to_string_pretty(c.deref()).unwrap();
     serialized
@@ -221,29 +1012,67 @@ lazy_static! {
 
 #[cfg(test)]
 mod tests {
+    use crate::builder::{BindingBuilder, ConfigBuilder};
     use crate::event;
-    use crate::event::{ClickEvent, Edge, KeyboardModifier, MouseButton, Point};
+    use crate::event::{KeyboardModifier, MouseButton, Region};
 
     use super::*;
 
+    #[test]
+    fn test_watch_action() {
+        use notify::event::{AccessKind, AccessMode, CreateKind, RemoveKind};
+        use notify::EventKind;
+
+        let event = |kind| Ok(Ok(notify::Event::new(kind)));
+        assert!(matches!(
+            watch_action(event(EventKind::Access(AccessKind::Close(
+                AccessMode::Write
+            )))),
+            WatchAction::Reload
+        ));
+        assert!(matches!(
+            watch_action(event(EventKind::Create(CreateKind::File))),
+            WatchAction::Ignore
+        ));
+        assert!(matches!(
+            watch_action(event(EventKind::Remove(RemoveKind::File))),
+            WatchAction::Rebuild(_)
+        ));
+        assert!(matches!(
+            watch_action(Ok(Err(notify::Error::generic("inotify error")))),
+            WatchAction::Error(_)
+        ));
+
+        // the sender of the watcher is dropped with it
+        let (tx, rx) = channel::<notify::Result<notify::Event>>();
+        drop(tx);
+        assert!(matches!(watch_action(rx.recv()), WatchAction::Rebuild(_)));
+    }
+
+    #[test]
+    fn test_default() {
+        let config = Config::default();
+        let serialized = serde_json::to_string(&config).unwrap();
+        // every option has its default value : only the mandatory fields are written
+        assert_eq!(serialized, r#"{"shape_button":"Right","bindings":[]}"#);
+        let loaded = load_from_str(&serialized).unwrap();
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), serialized);
+        assert_eq!(loaded.shape_min_points, DEFAULT_SHAPE_MIN_POINTS);
+        assert_eq!(loaded.backup_count, DEFAULT_BACKUP_COUNT);
+    }
+
     #[test]
     fn test_json_serialize() {
-        let config = Config {
-            shape_button: MouseButton::Right,
-            bindings: vec![Binding {
-                event: ClickEvent {
-                    button: MouseButton::Left,
-                    edges: vec![Edge::Top, Edge::Left],
-                    modifiers: vec![KeyboardModifier::ControlLeft],
-                    event_type: event::EventType::Press,
-                    shapes_angles: vec![vec![0.0, 1.0, 2.0]],
-                    shapes_xy: vec![],
-                },
-                cmd: vec![],
-                cmd_str: String::from("xlogo"),
-                comment: String::new(),
-            }],
-        };
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .binding(
+                BindingBuilder::new(MouseButton::Left)
+                    .edges(vec![Edge::Top, Edge::Left])
+                    .modifiers(vec![KeyboardModifier::ControlLeft])
+                    .event_type(event::EventType::Press)
+                    .cmd_str("xlogo")
+                    .build(),
+            )
+            .build();
 
         let serialized = serde_json::to_string_pretty(&config).unwrap();
         let expected = r#"{
@@ -358,7 +1187,7 @@ mod tests {
     }
   ]
 }"#;
-        let config: Config = load_from_str(serialized);
+        let config: Config = load_from_str(serialized).unwrap();
         println!("config = {:?}", config);
         assert_eq!(config.shape_button, MouseButton::Right);
         let binding = &config.bindings[0];
@@ -380,4 +1209,578 @@ mod tests {
             vec![Point { x: 0, y: 1 }, Point { x: 2, y: 3 }]
         );
     }
+
+    fn write_config(dir: &Path, name: &str, json: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, json).unwrap();
+        path
+    }
+
+    const INCLUDED_BINDING: &str = r#"{
+  "bindings": [
+    {
+      "comment": "included",
+      "event": { "button": "Middle", "edges": ["Top"] },
+      "cmd_str": "xlogo"
+    }
+  ]
+}"#;
+
+    #[test]
+    fn test_load_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("apps")).unwrap();
+        write_config(&dir.path().join("apps"), "included.json", INCLUDED_BINDING);
+        let main_path = write_config(
+            dir.path(),
+            "main.json",
+            r#"{
+  "shape_button": "Right",
+  "includes": ["apps/included.json", "missing.json"],
+  "bindings": [
+    { "comment": "main", "event": { "button": "Middle" }, "cmd_str": "xeyes" }
+  ]
+}"#,
+        );
+        let config = load(main_path.to_str().unwrap()).unwrap();
+        assert_eq!(config.bindings.len(), 2);
+        assert_eq!(config.bindings[0].comment, "main");
+        assert!(config.bindings[0].included_from.is_none());
+        assert_eq!(config.bindings[1].comment, "included");
+        assert_eq!(config.bindings[1].cmd, vec!["xlogo"]);
+        assert!(config.bindings[1].included_from.is_some());
+
+        // the included bindings are not written in the main config
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(serialized.contains("\"main\""));
+        assert!(!serialized.contains("\"included\""));
+    }
+
+    #[test]
+    fn test_load_with_overlays() {
+        let dir = tempfile::tempdir().unwrap();
+        write_config(dir.path(), "included.json", INCLUDED_BINDING);
+        let base_path = write_config(
+            dir.path(),
+            "base.json",
+            r#"{
+  "shape_button": "Right",
+  "corner_size": 20,
+  "bindings": [ { "comment": "base", "event": { "button": "Middle" }, "cmd_str": "xeyes" } ]
+}"#,
+        );
+        let overlay_path = write_config(
+            dir.path(),
+            "personal.json",
+            r#"{
+  "shape_button": "Middle",
+  "includes": ["included.json"],
+  "bindings": [ { "comment": "personal", "event": { "button": "Side" }, "cmd_str": "xclock" } ]
+}"#,
+        );
+        let config = load_with_overlays(&base_path, &[overlay_path]).unwrap();
+        // the overlay scalars replace the base ones, the others are kept
+        assert_eq!(config.shape_button, MouseButton::Middle);
+        assert_eq!(config.corner_size, 20);
+        let comments = config
+            .bindings
+            .iter()
+            .map(|b| b.comment.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(comments, vec!["base", "personal", "included"]);
+        assert_eq!(config.bindings[1].cmd, vec!["xclock"]);
+        assert!(config.bindings[0].included_from.is_none());
+        assert!(config.bindings[1].included_from.is_some());
+
+        // the overlay bindings are not written in the base config
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(!serialized.contains("\"personal\""));
+
+        let base = load_with_overlays(&base_path, &[]).unwrap();
+        assert_eq!(base.shape_button, MouseButton::Right);
+        assert_eq!(base.bindings.len(), 1);
+        assert!(load_with_overlays(&base_path, &[dir.path().join("missing.json")]).is_err());
+    }
+
+    #[test]
+    fn test_load_includes_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        write_config(
+            dir.path(),
+            "a.json",
+            r#"{ "includes": ["b.json"], "bindings": [] }"#,
+        );
+        write_config(
+            dir.path(),
+            "b.json",
+            r#"{ "includes": ["a.json"], "bindings": [] }"#,
+        );
+        let mut include_stack = vec![fs::canonicalize(dir.path().join("a.json")).unwrap()];
+        let res = load_includes(
+            &["b.json".to_string()],
+            &mut include_stack,
+            &mut vec![],
+            &Templates::new(),
+        );
+        assert!(res.unwrap_err().to_string().contains("include loop"));
+    }
+
+    #[test]
+    fn test_load_includes_diamond() {
+        let dir = tempfile::tempdir().unwrap();
+        write_config(dir.path(), "d.json", INCLUDED_BINDING);
+        for name in ["b.json", "c.json"] {
+            write_config(
+                dir.path(),
+                name,
+                r#"{ "includes": ["d.json"], "bindings": [] }"#,
+            );
+        }
+        let main_path = write_config(
+            dir.path(),
+            "a.json",
+            r#"{ "shape_button": "Right", "includes": ["b.json", "c.json"], "bindings": [] }"#,
+        );
+        let config = load(main_path.to_str().unwrap()).unwrap();
+        // d.json is included by b.json and c.json, its binding is loaded once
+        assert_eq!(config.bindings.len(), 1);
+        let canonical = |name: &str| fs::canonicalize(dir.path().join(name)).unwrap();
+        assert_eq!(
+            config.included_paths,
+            vec![
+                canonical("b.json"),
+                canonical("d.json"),
+                canonical("c.json")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_edges() {
+        let config = load_from_str(r#"{ "shape_button": "Right", "bindings": [] }"#).unwrap();
+        assert!(config.detect_edges);
+        let config =
+            load_from_str(r#"{ "shape_button": "Right", "bindings": [], "detect_edges": false }"#)
+                .unwrap();
+        assert!(!config.detect_edges);
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(serialized.contains(r#""detect_edges":false"#));
+    }
+
+    #[test]
+    fn test_load_from_reader() {
+        let json = r#"{ "shape_button": "Middle", "bindings": [
+            { "event": { "button": "Task" }, "cmd_str": "notify-send piped" } ] }"#;
+        let config = load_from_reader(json.as_bytes()).unwrap();
+        assert_eq!(config.shape_button, MouseButton::Middle);
+        assert_eq!(config.bindings[0].cmd, vec!["notify-send", "piped"]);
+
+        assert!(is_stdin_config(&Some("-".to_string())));
+        assert!(!is_stdin_config(&Some("config.json".to_string())));
+        assert!(!is_stdin_config(&None));
+    }
+
+    #[test]
+    fn test_load_cmd_precedence() {
+        let json = r#"{ "shape_button": "Right", "bindings": [
+            { "event": { "button": "Task" }, "cmd_line": "notify-send 'a b'" },
+            { "event": { "button": "Task" }, "cmd": ["notify-send", "it's \"quoted\""] },
+            { "event": { "button": "Task" }, "cmd_str": "notify-send str", "cmd": ["xeyes"] } ] }"#;
+        let config = load_from_str(json).unwrap();
+        assert_eq!(config.bindings[0].cmd, vec!["notify-send", "a b"]);
+        assert_eq!(config.bindings[0].cmd_str, "notify-send 'a b'");
+        assert_eq!(
+            config.bindings[1].cmd,
+            vec!["notify-send", "it's \"quoted\""]
+        );
+        assert_eq!(config.bindings[2].cmd, vec!["notify-send", "str"]);
+    }
+
+    #[test]
+    fn test_load_location_sensitive() {
+        let binding = r#"{ "event": { "button": "Right", "event_type": "Shape",
+            "shapes_svg": ["M0,0 H100"], "region": "TopRight" }, "location_sensitive": true }"#;
+        let config = load_from_str(&format!(
+            r#"{{ "shape_button": "Right", "bindings": [{binding}] }}"#
+        ))
+        .unwrap();
+        assert_eq!(config.bindings[0].event.region, Some(Region::TopRight));
+
+        let without_region = binding.replace(r#", "region": "TopRight""#, "");
+        let err = load_from_str(&format!(
+            r#"{{ "shape_button": "Right", "bindings": [{without_region}] }}"#
+        ))
+        .unwrap_err();
+        assert!(err.to_string().contains("no region"), "{err}");
+    }
+
+    #[test]
+    fn test_load_shapes_svg() {
+        let config = load_from_str(
+            r#"{ "shape_button": "Right", "bindings": [
+                { "event": { "button": "Right", "event_type": "Shape",
+                             "shapes_svg": ["M0,0 L0,100 L100,100"] } } ] }"#,
+        )
+        .unwrap();
+        let event = &config.bindings[0].event;
+        assert!(event.shapes_xy.is_empty());
+        assert_eq!(event.shapes_angles.len(), 1);
+        assert_eq!(event.shapes_angles[0].len(), 99);
+
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(serialized.contains(r#""shapes_svg":["M0,0 L0,100 L100,100"]"#));
+    }
+
+    #[test]
+    fn test_load_shapes_directions() {
+        let config = load_from_str(
+            r#"{ "shape_button": "Right", "bindings": [
+                { "event": { "button": "Right", "event_type": "Shape",
+                             "shapes_directions": ["DR"] } } ] }"#,
+        )
+        .unwrap();
+        let event = &config.bindings[0].event;
+        assert_eq!(event.shapes_angles.len(), 1);
+        assert_eq!(
+            event.shapes_angles[0],
+            points_to_angles(&svg_path_to_points("M0,0 V100 H100").unwrap())
+        );
+        assert!(load_from_str(
+            r#"{ "shape_button": "Right", "bindings": [
+                { "event": { "button": "Right", "event_type": "Shape",
+                             "shapes_directions": ["DX"] } } ] }"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_load_arm() {
+        let config = load_from_str(
+            r#"{ "shape_button": "Right", "bindings": [],
+                 "arm": { "event": { "button": "Task" }, "cmd_str": "notify-send armed" } }"#,
+        )
+        .unwrap();
+        let arm = config.arm.unwrap();
+        assert_eq!(arm.binding.event.button, MouseButton::Task);
+        assert_eq!(arm.binding.cmd, vec!["notify-send", "armed"]);
+        assert_eq!(arm.duration_ms, 5000);
+    }
+
+    #[test]
+    fn test_default_config_path() {
+        let home = tempfile::tempdir().unwrap();
+        let xdg = tempfile::tempdir().unwrap();
+        let xdg_str = Some(xdg.path().to_str().unwrap().to_string());
+        let legacy_path = home.path().join(".config").join("mouse-actions.json");
+        let xdg_path = xdg.path().join("mouse-actions.json");
+
+        assert_eq!(default_config_path(None, home.path()), legacy_path);
+        assert_eq!(
+            default_config_path(Some(String::new()), home.path()),
+            legacy_path
+        );
+        assert_eq!(default_config_path(xdg_str.clone(), home.path()), xdg_path);
+
+        fs::create_dir(home.path().join(".config")).unwrap();
+        fs::write(&legacy_path, "{}").unwrap();
+        assert_eq!(
+            default_config_path(xdg_str.clone(), home.path()),
+            legacy_path
+        );
+
+        fs::write(&xdg_path, "{}").unwrap();
+        assert_eq!(default_config_path(xdg_str, home.path()), xdg_path);
+    }
+
+    #[test]
+    fn test_init_config_file_with_examples() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("mouse-actions.json");
+        assert!(init_config_file_with_examples(&config_path));
+        let config = load(config_path.to_str().unwrap()).unwrap();
+        assert_eq!(config.bindings.len(), 2);
+        assert_eq!(config.bindings[1].event.event_type, EventType::Shape);
+        assert!(!config.bindings[1].event.shapes_angles[0].is_empty());
+
+        // don't overwrite the existing config
+        fs::write(&config_path, "{}").unwrap();
+        assert!(!init_config_file_with_examples(&config_path));
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), "{}");
+
+        // but replace the empty config written on the first run
+        fs::remove_file(&config_path).unwrap();
+        init_config_file_if_not_exists(&config_path);
+        assert!(init_config_file_with_examples(&config_path));
+        assert_eq!(
+            load(config_path.to_str().unwrap()).unwrap().bindings.len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_load_from_str_never_panics() {
+        let mut config = examples_config();
+        config.bindings.push(
+            BindingBuilder::new(MouseButton::Right)
+                .cmd_str("notify-send svg")
+                .shape_svg("M0,0 L0,100 h100")
+                .build(),
+        );
+        let json = serde_json::to_string(&config).unwrap();
+
+        // the truncated configs
+        for end in (0..json.len()).filter(|end| json.is_char_boundary(*end)) {
+            let _ = load_from_str(&json[..end]);
+        }
+
+        // the configs with a few random characters replaced
+        let mut seed: u64 = 42;
+        let mut next_random = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) as usize
+        };
+        let replacements = b"{}[]\",:0-9e.aZ \\";
+        for _ in 0..500 {
+            let mut bytes = json.clone().into_bytes();
+            for _ in 0..4 {
+                let index = next_random() % bytes.len();
+                bytes[index] = replacements[next_random() % replacements.len()];
+            }
+            if let Ok(mutated) = String::from_utf8(bytes) {
+                let _ = load_from_str(&mutated);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("mouse-actions.json");
+        let backup = |index: usize| dir.path().join(format!("mouse-actions.json.bak.{index}"));
+        for version in 1..=4 {
+            rotate_backups(&config_path, 2).unwrap();
+            fs::write(&config_path, format!("v{version}")).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), "v4");
+        assert_eq!(fs::read_to_string(backup(1)).unwrap(), "v3");
+        assert_eq!(fs::read_to_string(backup(2)).unwrap(), "v2");
+        assert!(!backup(3).exists());
+
+        rotate_backups(&config_path, 0).unwrap();
+        assert_eq!(fs::read_to_string(backup(1)).unwrap(), "v3");
+    }
+
+    #[test]
+    fn test_format_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("mouse-actions.json");
+        fs::write(
+            &config_path,
+            r#"{"bindings": [{"cmd_str": "notify-send up",
+                "event": {"event_type": "Click", "button": "WheelUp"}, "comment": "up"}],
+                "shape_button": "Right"}"#,
+        )
+        .unwrap();
+        assert!(format_config_file(&config_path).unwrap());
+        let formatted = fs::read_to_string(&config_path).unwrap();
+        assert!(formatted.starts_with("{\n  \"shape_button\": \"Right\",\n  \"bindings\""));
+        assert!(!formatted.contains("shapes_angles"));
+
+        assert!(!format_config_file(&config_path).unwrap());
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_shape_capacity() {
+        let config: Config = serde_json::from_str(
+            r#"{"shape_button":"Right","bindings":[],"long_gesture":{"modifier":"Shift"}}"#,
+        )
+        .unwrap();
+        let mut keyboard_state = KeyboardState::default();
+        assert_eq!(config.shape_capacity(&keyboard_state), HISTO_SIZE);
+        keyboard_state.set_key(rdev::Key::ControlLeft, true);
+        assert_eq!(config.shape_capacity(&keyboard_state), HISTO_SIZE);
+        keyboard_state.set_key(rdev::Key::ShiftRight, true);
+        assert_eq!(config.shape_capacity(&keyboard_state), 10 * HISTO_SIZE);
+        assert_eq!(
+            Config::default().shape_capacity(&keyboard_state),
+            HISTO_SIZE
+        );
+    }
+
+    #[test]
+    fn test_reload_config_keeps_the_runtime_state() {
+        use crate::repeat::{start_repeat, stop_repeat, REPEATS_TEST_LOCK};
+        use crate::runner::{CommandRunner, RecordingRunner};
+
+        let _lock = REPEATS_TEST_LOCK.lock().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mouse-actions.json");
+        fs::write(&path, r#"{ "shape_button": "Middle", "bindings": [] }"#).unwrap();
+        let config = Arc::new(Mutex::new(Config::default()));
+        let runner: Arc<dyn CommandRunner> = Arc::new(RecordingRunner::default());
+        let binding = BindingBuilder::new(MouseButton::Trigger)
+            .event_type(event::EventType::Press)
+            .cmd_str("true")
+            .build();
+        start_repeat(
+            MouseButton::Trigger,
+            binding,
+            vec!["true".to_string()],
+            50,
+            runner,
+        );
+        reload_config(&config, &path, &[]);
+        assert_eq!(config.lock().unwrap().shape_button, MouseButton::Middle);
+        // the repeat started before the reload is still running
+        assert!(stop_repeat(MouseButton::Trigger));
+    }
+
+    #[test]
+    fn test_duplicate_ids() {
+        let binding = |id: &str, cmd: &str| {
+            format!(r#"{{ "id": "{id}", "cmd_str": "{cmd}", "event": {{ "button": "Middle" }} }}"#)
+        };
+        let config = |bindings: &[String]| {
+            format!(
+                r#"{{ "shape_button": "Right", "bindings": [{}] }}"#,
+                bindings.join(",")
+            )
+        };
+        let check = |bindings: &[String]| check_bindings(&mut load_from_str(&config(bindings))?);
+        assert!(check(&[binding("a", "true"), binding("b", "true")]).is_ok());
+
+        let err = check(&[binding("a", "true"), binding("a", "false")]).unwrap_err();
+        assert!(
+            err.to_string().contains("several bindings have the id"),
+            "{err}"
+        );
+
+        // the same event and command without id : the same derived id, only a warning,
+        // e.g. for bindings that differ by their `when` predicate
+        let derived =
+            |cmd: &str| format!(r#"{{ "cmd_str": "{cmd}", "event": {{ "button": "Middle" }} }}"#);
+        assert!(check(&[derived("true"), derived("false")]).is_ok());
+        assert!(check(&[derived("true"), derived("true")]).is_ok());
+        assert!(check(&[derived("true"), binding("a", "true"), derived("true")]).is_ok());
+        // an explicit id equal to the derived id of another binding
+        let derived_id =
+            load_from_str(&config(&[derived("true")])).unwrap().bindings[0].stable_id();
+        assert!(check(&[derived("true"), binding(&derived_id, "false")]).is_err());
+    }
+
+    #[test]
+    fn test_editor_command_line() {
+        let some = |cmd: &str| Some(cmd.to_string());
+        assert_eq!(
+            editor_command_line(Some("code --wait"), some("vim"), some("gedit")),
+            vec!["code", "--wait"]
+        );
+        assert_eq!(
+            editor_command_line(None, some("vim"), some("gedit")),
+            vec!["vim"]
+        );
+        assert_eq!(
+            editor_command_line(None, some(""), some("gedit")),
+            vec!["gedit"]
+        );
+        assert_eq!(editor_command_line(None, None, None), vec!["xdg-open"]);
+
+        let err = open_config(
+            PathBuf::from("mouse-actions.json"),
+            Some("mouse-actions-missing-editor"),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("isn't found"), "{err}");
+        assert!(open_config(PathBuf::from("mouse-actions.json"), Some("false")).is_err());
+        assert!(open_config(PathBuf::from("mouse-actions.json"), Some("true")).is_ok());
+    }
+
+    #[test]
+    fn test_load_active_hours() {
+        let config = |active_hours: &str| {
+            format!(
+                r#"{{ "shape_button": "Right", "bindings": [
+                    {{ "event": {{ "button": "Middle" }}, "active_hours": {active_hours} }} ] }}"#
+            )
+        };
+        let loaded = load_from_str(&config(r#"["22:00", "06:00"]"#)).unwrap();
+        assert_eq!(
+            loaded.bindings[0].active_hours,
+            Some(["22:00".to_string(), "06:00".to_string()])
+        );
+        assert!(load_from_str(&config(r#"["22:00", "6h"]"#)).is_err());
+        assert!(load_from_str(&config(r#"["22:00"]"#)).is_err());
+    }
+
+    #[test]
+    fn test_load_repeat_ms() {
+        let config = |repeat_ms: u64| {
+            format!(
+                r#"{{ "shape_button": "Right", "bindings": [
+                    {{ "event": {{ "button": "Middle", "event_type": "Press" }},
+                       "repeat_ms": {repeat_ms} }} ] }}"#
+            )
+        };
+        assert_eq!(
+            load_from_str(&config(20)).unwrap().bindings[0].repeat_ms,
+            Some(20)
+        );
+        let err = load_from_str(&config(0)).unwrap_err();
+        assert!(format!("{err:#}").contains("too short"), "{err:#}");
+        assert!(load_from_str(&config(1)).is_err());
+    }
+
+    #[test]
+    fn test_load_shape_ref() {
+        let config = |shape_ref: &str| {
+            format!(
+                r#"{{ "shape_button": "Right",
+                    "templates": {{ "L": [[0, 0, 0, 100, 100, 100], [0, 0, 0, 50, 50, 50]] }},
+                    "bindings": [
+                        {{ "event": {{ "button": "Right", "event_type": "Shape",
+                                     "shape_ref": "{shape_ref}" }} }},
+                        {{ "event": {{ "button": "Right", "event_type": "Shape",
+                                     "shapes_svg": ["M0,0 H100"], "shape_ref": "{shape_ref}" }} }}
+                    ] }}"#
+            )
+        };
+        let loaded = load_from_str(&config("L")).unwrap();
+        let template = &loaded.templates["L"];
+        let event = &loaded.bindings[0].event;
+        assert_eq!(
+            event.shapes_angles,
+            vec![
+                points_to_angles(&template[0]),
+                points_to_angles(&template[1])
+            ]
+        );
+        // the template shapes are added to the other shapes of the event
+        assert_eq!(loaded.bindings[1].event.shapes_angles.len(), 3);
+        assert!(loaded.bindings[0].event.shapes_xy.is_empty());
+
+        let serialized = to_canonical_json(&loaded);
+        assert!(serialized.contains("\"shape_ref\": \"L\""));
+        assert!(serialized.contains("\"templates\""));
+
+        let err = load_from_str(&config("O")).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("unknown template \"O\""),
+            "{err:#}"
+        );
+    }
+
+    #[test]
+    fn test_load_long_gesture_max_points() {
+        let config = |long_gesture: &str| {
+            format!(r#"{{"shape_button":"Right","bindings":[],"long_gesture":{long_gesture}}}"#)
+        };
+        assert!(load_from_str(&config(r#"{"modifier":"Shift"}"#)).is_ok());
+        assert!(load_from_str(&config(r#"{"modifier":"Shift","max_points":10}"#)).is_ok());
+        for max_points in [0, 9, MAX_LONG_GESTURE_POINTS + 1] {
+            let long_gesture = format!(r#"{{"modifier":"Shift","max_points":{max_points}}}"#);
+            let err = load_from_str(&config(&long_gesture)).unwrap_err();
+            assert!(err.to_string().contains("out of range"), "{err}");
+        }
+    }
 }
@@ -0,0 +1,49 @@
+use anyhow::bail;
+
+use crate::event::PointHistory;
+use crate::svg_path::svg_path_to_points;
+
+// the length of the stroke of a direction code, a repeated code makes a longer stroke
+const DIRECTION_CODE_LENGTH: i32 = 100;
+
+/// the SVG path of direction codes : R, D, L, U (right, down, left, up) in any case,
+/// e.g. "RDLU" for a square, "RRD" for a long right stroke and a short down one
+fn direction_codes_to_svg_path(codes: &str) -> anyhow::Result<String> {
+    let mut path = "M0,0".to_string();
+    for code in codes.chars().filter(|c| !c.is_whitespace()) {
+        let (x, y) = match code.to_ascii_uppercase() {
+            'R' => (DIRECTION_CODE_LENGTH, 0),
+            'D' => (0, DIRECTION_CODE_LENGTH),
+            'L' => (-DIRECTION_CODE_LENGTH, 0),
+            'U' => (0, -DIRECTION_CODE_LENGTH),
+            _ => bail!("invalid direction code {code:?} in {codes:?}, expected R, D, L or U"),
+        };
+        path.push_str(&format!(" l{x},{y}"));
+    }
+    Ok(path)
+}
+
+/// Sample direction codes (e.g. `"RDLU"`) into points, like an SVG path.
+pub fn direction_codes_to_points(codes: &str) -> anyhow::Result<PointHistory> {
+    svg_path_to_points(&direction_codes_to_svg_path(codes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::direction_codes::{direction_codes_to_points, direction_codes_to_svg_path};
+    use crate::svg_path::svg_path_to_points;
+
+    #[test]
+    fn test_direction_codes() {
+        assert_eq!(
+            direction_codes_to_svg_path("RD lu").unwrap(),
+            "M0,0 l100,0 l0,100 l-100,0 l0,-100"
+        );
+        assert_eq!(
+            direction_codes_to_points("RRD").unwrap(),
+            svg_path_to_points("M0,0 H200 V100").unwrap()
+        );
+        assert!(direction_codes_to_points("").is_err());
+        assert!(direction_codes_to_points("RX").is_err());
+    }
+}
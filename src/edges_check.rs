@@ -0,0 +1,100 @@
+use anyhow::{anyhow, Context};
+
+use crate::event::{Bounds, Edge, Point, RelativeTo};
+
+/// a point of the cursor path : `x,y` or `x y`
+fn parse_point(line: &str) -> anyhow::Result<Point> {
+    let mut coordinates = line
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty());
+    let mut next = || -> anyhow::Result<i32> {
+        let part = coordinates
+            .next()
+            .ok_or_else(|| anyhow!("missing coordinate"))?;
+        part.parse()
+            .with_context(|| format!("invalid coordinate {part:?}"))
+    };
+    let point = Point {
+        x: next()?,
+        y: next()?,
+    };
+    if coordinates.next().is_some() {
+        return Err(anyhow!("more than 2 coordinates"));
+    }
+    Ok(point)
+}
+
+/// The edges of each point of the path (one point per line, the empty lines and the `#`
+/// comments are skipped) in the bounds, like the grab classifies the events.
+pub fn check_edges(
+    path: &str,
+    bounds: Bounds,
+    corner_size: u32,
+    relative_to: RelativeTo,
+) -> anyhow::Result<Vec<(Point, Vec<Edge>)>> {
+    path.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| {
+            let point = parse_point(line).with_context(|| format!("line {}", index + 1))?;
+            let edges = Edge::edges_in(point.x, point.y, corner_size, bounds, relative_to);
+            Ok((point, edges))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::edges_check::check_edges;
+    use crate::event::{Bounds, Edge, Point, RelativeTo};
+
+    #[test]
+    fn test_check_edges() {
+        let bounds = Bounds::from_str("1920x1080").unwrap();
+        let path = "# the top left corner\n0,0\n\n960 540\n1919,500\n";
+        let edges = check_edges(path, bounds, 20, RelativeTo::Screen).unwrap();
+        assert_eq!(
+            edges,
+            vec![
+                (
+                    Point { x: 0, y: 0 },
+                    vec![Edge::Left, Edge::Top, Edge::TopLeft]
+                ),
+                (Point { x: 960, y: 540 }, vec![]),
+                (Point { x: 1919, y: 500 }, vec![Edge::Right]),
+            ]
+        );
+
+        // the right monitor of a dual screen, the window edges are a few pixels wide
+        let bounds = Bounds::from_str("1920x1080+1920+0").unwrap();
+        let edges = check_edges("1922,500\n100,500", bounds, 0, RelativeTo::Window).unwrap();
+        assert_eq!(edges[0].1, vec![Edge::Left]);
+        assert_eq!(edges[1].1, vec![]);
+
+        let err = check_edges("0,0\n10;20", bounds, 0, RelativeTo::Screen).unwrap_err();
+        assert!(format!("{err:#}").contains("line 2"), "{err:#}");
+        assert!(check_edges("1,2,3", bounds, 0, RelativeTo::Screen).is_err());
+    }
+
+    #[test]
+    fn test_bounds_from_str() {
+        assert_eq!(
+            Bounds::from_str("1920x1080+1920-10").unwrap(),
+            Bounds {
+                x: 1920,
+                y: -10,
+                width: 1920,
+                height: 1080
+            }
+        );
+        assert_eq!(
+            Bounds::from_str("800x600").unwrap(),
+            Bounds::from_size(800, 600)
+        );
+        assert!(Bounds::from_str("800").is_err());
+        assert!(Bounds::from_str("0x600").is_err());
+        assert!(Bounds::from_str("800x600+10").is_err());
+    }
+}
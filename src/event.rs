@@ -1,39 +1,146 @@
 use std::fmt;
 use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use arrayvec::ArrayVec;
-use rdev::{display_size, Button};
-use serde::de::{SeqAccess, Visitor};
+use anyhow::anyhow;
+use log::warn;
+use rdev::{display_size, Button, Key};
+use serde::de::{self, SeqAccess, Visitor};
 use serde::ser::SerializeSeq;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+// the default capacity of a PointHistory
 pub const HISTO_SIZE: usize = 1000;
 
-// pub type PointHistory = ArrayVec<Point, HISTO_SIZE>;
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
-pub struct PointHistory(ArrayVec<Point, HISTO_SIZE>);
+/// The points of a shape, with a bounded capacity.
+///
+/// Invariant : `len() <= capacity()`, so `is_full()` ⇔ `remaining() == 0`.
+/// A push on a full history panics, the callers check `is_full` before.
+#[derive(Debug, Clone)]
+pub struct PointHistory {
+    points: Vec<Point>,
+    capacity: usize,
+}
 
 impl PointHistory {
     pub fn new() -> Self {
-        PointHistory(ArrayVec::<Point, HISTO_SIZE>::new())
+        PointHistory::with_capacity(HISTO_SIZE)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        PointHistory {
+            points: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// the points of a shape, the capacity is at least the default one
+    pub fn from_points(points: Vec<Point>) -> Self {
+        PointHistory {
+            capacity: HISTO_SIZE.max(points.len()),
+            points,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
     }
-    pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+
+    /// the number of points that can still be pushed
+    pub fn remaining(&self) -> usize {
+        self.capacity - self.points.len()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    pub fn push(&mut self, point: Point) {
+        assert!(!self.is_full(), "PointHistory is full");
+        self.points.push(point);
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Reduce the points to target_len points evenly spaced along the path (by arc
+    /// length), the first and the last points are kept. The capacity is unchanged.
+    pub fn downsample(&self, target_len: usize) -> PointHistory {
+        if self.len() <= target_len || self.is_empty() {
+            return self.clone();
+        }
+        let mut out = PointHistory::with_capacity(self.capacity);
+        if target_len == 1 {
+            out.push(self.points[0]);
+            return out;
+        }
+        // the distance along the path of each point
+        let mut distances = Vec::with_capacity(self.len());
+        let mut distance = 0.0;
+        distances.push(distance);
+        for w in self.points.windows(2) {
+            distance +=
+                (((w[1].x - w[0].x) as f64).powi(2) + ((w[1].y - w[0].y) as f64).powi(2)).sqrt();
+            distances.push(distance);
+        }
+        let total = distance;
+        let mut segment = 0;
+        for i in 0..target_len {
+            let ratio = i as f64 / (target_len - 1) as f64;
+            if total == 0.0 {
+                out.push(self.points[(ratio * (self.len() - 1) as f64).round() as usize]);
+                continue;
+            }
+            let target = total * ratio;
+            while segment < self.len() - 2 && distances[segment + 1] < target {
+                segment += 1;
+            }
+            let (from, to) = (self.points[segment], self.points[segment + 1]);
+            let length = distances[segment + 1] - distances[segment];
+            let t = if length > 0.0 {
+                ((target - distances[segment]) / length).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            out.push(Point {
+                x: (from.x as f64 + (to.x - from.x) as f64 * t).round() as i32,
+                y: (from.y as f64 + (to.y - from.y) as f64 * t).round() as i32,
+            });
+        }
+        out
     }
 }
 
+impl Default for PointHistory {
+    fn default() -> Self {
+        PointHistory::new()
+    }
+}
+
+// the capacity is not part of the shape
+impl PartialEq for PointHistory {
+    fn eq(&self, other: &Self) -> bool {
+        self.points == other.points
+    }
+}
+
+impl Eq for PointHistory {}
+
 impl Deref for PointHistory {
-    type Target = ArrayVec<Point, HISTO_SIZE>;
+    type Target = [Point];
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.points
     }
 }
 
 impl DerefMut for PointHistory {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.points
     }
 }
 
@@ -43,7 +150,7 @@ impl Serialize for PointHistory {
         S: Serializer,
     {
         let mut seq = serializer.serialize_seq(Some(self.len() * 2))?;
-        for p in &self.0 {
+        for p in &self.points {
             seq.serialize_element(&p.x)?;
             seq.serialize_element(&p.y)?;
         }
@@ -55,23 +162,30 @@ impl<'de> Visitor<'de> for PointHistory {
     type Value = PointHistory;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        // FIXME
-        formatter.write_str("PointHistory")
+        formatter.write_str("a flat array of x, y coordinates")
     }
 
     fn visit_seq<M>(self, mut access: M) -> Result<Self::Value, M::Error>
     where
         M: SeqAccess<'de>,
     {
-        let mut ph = PointHistory::new();
+        let mut ph = self;
 
         // While there are entries remaining in the input, add them
-        // into our map.
+        // into our map. The shapes of the config are not limited by the capacity.
         while let Some(x) = access.next_element()? {
-            if let Some(y) = access.next_element()? {
-                ph.push(Point { x, y });
+            match access.next_element()? {
+                Some(y) => ph.points.push(Point { x, y }),
+                // an odd length : the last x has no y
+                None => {
+                    return Err(de::Error::invalid_length(
+                        ph.points.len() * 2 + 1,
+                        &"an even number of coordinates",
+                    ))
+                }
             }
         }
+        ph.capacity = ph.capacity.max(ph.points.len());
 
         Ok(ph)
     }
@@ -95,49 +209,436 @@ pub struct KeyboardState {
     pub control_left: bool,
     pub control_right: bool,
     pub meta_left: bool,
+    pub meta_right: bool,
     pub alt: bool,
     pub alt_gr: bool,
 }
 
+impl KeyboardState {
+    /// set the state of a modifier key, return false if the key isn't a modifier
+    pub fn set_key(&mut self, key: Key, pressed: bool) -> bool {
+        let state = match key {
+            Key::ShiftLeft => &mut self.shift_left,
+            Key::ShiftRight => &mut self.shift_right,
+            Key::ControlLeft => &mut self.control_left,
+            Key::ControlRight => &mut self.control_right,
+            Key::MetaLeft => &mut self.meta_left,
+            Key::MetaRight => &mut self.meta_right,
+            Key::Alt => &mut self.alt,
+            Key::AltGr => &mut self.alt_gr,
+            _ => return false,
+        };
+        *state = pressed;
+        true
+    }
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Edge {
     Top,
     Right,
     Bottom,
     Left,
+    // the corner boxes of corner_size, added to the edges
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// the screen area in which the edges are detected
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Bounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u64,
+    pub height: u64,
+}
+
+impl Bounds {
+    pub fn from_size(width: u64, height: u64) -> Self {
+        Bounds {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        let (x, y) = (x as i64 - self.x as i64, y as i64 - self.y as i64);
+        x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64
+    }
+}
+
+/// an X geometry : `WIDTHxHEIGHT`, optionally followed by the position `+X+Y`
+impl FromStr for Bounds {
+    type Err = anyhow::Error;
+
+    fn from_str(geometry: &str) -> Result<Self, Self::Err> {
+        let invalid = || anyhow!("invalid bounds {geometry:?}, expected WIDTHxHEIGHT[+X+Y]");
+        let (size, position) = match geometry.find(['+', '-']) {
+            Some(index) => geometry.split_at(index),
+            None => (geometry, ""),
+        };
+        let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+        let mut bounds = Bounds::from_size(
+            width.parse().map_err(|_| invalid())?,
+            height.parse().map_err(|_| invalid())?,
+        );
+        if !position.is_empty() {
+            // the sign of Y starts after the digits of X
+            let y_index = position[1..].find(['+', '-']).ok_or_else(invalid)? + 1;
+            let (x, y) = position.split_at(y_index);
+            bounds.x = x.parse().map_err(|_| invalid())?;
+            bounds.y = y.parse().map_err(|_| invalid())?;
+        }
+        if bounds.width == 0 || bounds.height == 0 {
+            return Err(invalid());
+        }
+        Ok(bounds)
+    }
 }
 
+/// what the edges of the events are relative to
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RelativeTo {
+    #[default]
+    Screen,
+    Window,
+}
+
+impl RelativeTo {
+    pub fn is_screen(&self) -> bool {
+        *self == RelativeTo::Screen
+    }
+}
+
+/// the events that abort the shape being drawn
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AbortTrigger {
+    // any wheel scroll, vertical or horizontal
+    Wheel,
+    Escape,
+}
+
+/// the quarters of the screen, the bounding box of a gesture overlaps one or several of them
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Quadrant {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Quadrant {
+    /// the quadrants of bounds overlapped by the box from min to max, the points outside
+    /// are in the nearest quadrant
+    pub fn overlapped_in(min: Point, max: Point, bounds: Bounds) -> Vec<Quadrant> {
+        let half = |position: i32, origin: i32, size: u64| {
+            (position as i64 - origin as i64) * 2 >= size as i64
+        };
+        let (left, right) = (
+            !half(min.x, bounds.x, bounds.width),
+            half(max.x, bounds.x, bounds.width),
+        );
+        let (top, bottom) = (
+            !half(min.y, bounds.y, bounds.height),
+            half(max.y, bounds.y, bounds.height),
+        );
+        [
+            (top && left, Quadrant::TopLeft),
+            (top && right, Quadrant::TopRight),
+            (bottom && left, Quadrant::BottomLeft),
+            (bottom && right, Quadrant::BottomRight),
+        ]
+        .into_iter()
+        .filter_map(|(overlapped, quadrant)| overlapped.then_some(quadrant))
+        .collect()
+    }
+
+    /// the quadrants overlapped by the bounding box of the points, in the focused window
+    /// if window is some, in the screen otherwise
+    pub fn overlapped_by(points: &PointHistory, window: Option<Bounds>) -> Vec<Quadrant> {
+        let (Some(min_x), Some(max_x)) = (
+            points.iter().map(|p| p.x).min(),
+            points.iter().map(|p| p.x).max(),
+        ) else {
+            return vec![];
+        };
+        let min_y = points.iter().map(|p| p.y).min().unwrap();
+        let max_y = points.iter().map(|p| p.y).max().unwrap();
+        let (min, max) = (Point { x: min_x, y: min_y }, Point { x: max_x, y: max_y });
+        if let Some(bounds) = window {
+            return Quadrant::overlapped_in(min, max, bounds);
+        }
+        match display_size() {
+            Ok((width, height)) => {
+                Quadrant::overlapped_in(min, max, Bounds::from_size(width, height))
+            }
+            Err(err) => {
+                warn!("couldn't get the screen size, no quadrant detected : {err:?}");
+                vec![]
+            }
+        }
+    }
+}
+
+pub fn quadrants_are_equals(quadrants1: &[Quadrant], quadrants2: &[Quadrant]) -> bool {
+    quadrants1.len() == quadrants2.len()
+        && quadrants1
+            .iter()
+            .all(|quadrant| quadrants2.contains(quadrant))
+}
+
+/// the areas of a 3x3 grid on the screen, in which the shapes are drawn
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Region {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Region {
+    const GRID: [Region; 9] = [
+        Region::TopLeft,
+        Region::Top,
+        Region::TopRight,
+        Region::Left,
+        Region::Center,
+        Region::Right,
+        Region::BottomLeft,
+        Region::Bottom,
+        Region::BottomRight,
+    ];
+
+    /// the region of the point in bounds, the points outside are in the nearest region
+    pub fn from_bounds(x: i32, y: i32, bounds: Bounds) -> Region {
+        let cell = |position: i64, origin: i32, size: u64| {
+            ((position - origin as i64) * 3)
+                .div_euclid(size.max(1) as i64)
+                .clamp(0, 2) as usize
+        };
+        let column = cell(x as i64, bounds.x, bounds.width);
+        let row = cell(y as i64, bounds.y, bounds.height);
+        Region::GRID[row * 3 + column]
+    }
+
+    /// the region of the focused window if window is some, of the screen otherwise
+    pub fn from_pos(x: i32, y: i32, window: Option<Bounds>) -> Option<Region> {
+        if let Some(bounds) = window {
+            return Some(Region::from_bounds(x, y, bounds));
+        }
+        match display_size() {
+            Ok((width, height)) => {
+                Some(Region::from_bounds(x, y, Bounds::from_size(width, height)))
+            }
+            Err(err) => {
+                warn!("couldn't get the screen size, no region detected : {err:?}");
+                None
+            }
+        }
+    }
+}
+
+// the pointer can't go past the border of a window like past a screen edge,
+// the window edges are a few pixels wide
+const WINDOW_EDGE_THRESHOLD: u32 = 4;
+
 impl Edge {
-    pub fn edges_from_pos(x: i32, y: i32) -> Vec<Edge> {
-        let (width, height) = display_size().unwrap();
+    /// The edges of the focused window if window is some, of the screen otherwise.
+    /// The screen size is queried on each event, not cached : the edges follow the
+    /// resolution and layout changes (monitor hotplug, RandR) without a restart.
+    /// The screen is the whole desktop : the X11 root window spans all the monitors
+    /// from (0, 0), whatever the monitor positions of the layout, and the evdev grab
+    /// keeps the cursor in it. The bounds with a negative origin are supported too.
+    pub fn edges_from_pos(x: i32, y: i32, corner_size: u32, window: Option<Bounds>) -> Vec<Edge> {
+        if let Some(bounds) = window {
+            return Edge::edges_in(x, y, corner_size, bounds, RelativeTo::Window);
+        }
+        match display_size() {
+            Ok((width, height)) => Edge::edges_in(
+                x,
+                y,
+                corner_size,
+                Bounds::from_size(width, height),
+                RelativeTo::Screen,
+            ),
+            Err(err) => {
+                // e.g. during a display reconfiguration
+                warn!("couldn't get the screen size, no edge detected : {err:?}");
+                vec![]
+            }
+        }
+    }
+
+    /// The edges and corner of the point in the bounds of the screen or of the window,
+    /// without querying the display. A point outside the window has no edges.
+    pub fn edges_in(
+        x: i32,
+        y: i32,
+        corner_size: u32,
+        bounds: Bounds,
+        relative_to: RelativeTo,
+    ) -> Vec<Edge> {
+        let threshold = match relative_to {
+            RelativeTo::Screen => 0,
+            RelativeTo::Window if !bounds.contains(x, y) => return vec![],
+            RelativeTo::Window => WINDOW_EDGE_THRESHOLD,
+        };
+        let mut edges = Edge::edges_from_bounds(x, y, bounds, threshold);
+        edges.extend(Edge::corner_from_bounds(x, y, bounds, corner_size));
+        edges
+    }
+
+    /// the edges of the point in bounds, within threshold pixels of the border
+    pub fn edges_from_bounds(x: i32, y: i32, bounds: Bounds, threshold: u32) -> Vec<Edge> {
         let mut edges: Vec<Edge> = vec![];
+        let threshold = threshold as i64;
+        let (x, y) = (x as i64 - bounds.x as i64, y as i64 - bounds.y as i64);
 
-        if x == 0 {
+        if x <= threshold {
             edges.push(Edge::Left);
-        } else if (x) == (width as i32 - 1) {
+        } else if x >= bounds.width as i64 - 1 - threshold {
             edges.push(Edge::Right);
         }
-        if y == 0 {
+        if y <= threshold {
             edges.push(Edge::Top);
-        } else if (y) == (height as i32 - 1) {
+        } else if y >= bounds.height as i64 - 1 - threshold {
             edges.push(Edge::Bottom);
         }
         edges
     }
+
+    /// the corner of the point if it's in a corner_size pixels square box of the bounds,
+    /// None if corner_size is 0
+    pub fn corner_from_bounds(x: i32, y: i32, bounds: Bounds, corner_size: u32) -> Option<Edge> {
+        if corner_size == 0 {
+            return None;
+        }
+        let corner_size = corner_size as i64;
+        let (x, y) = (x as i64 - bounds.x as i64, y as i64 - bounds.y as i64);
+        let left = x < corner_size;
+        let right = x >= bounds.width as i64 - corner_size;
+        let top = y < corner_size;
+        let bottom = y >= bounds.height as i64 - corner_size;
+        match (top, bottom, left, right) {
+            (true, _, true, _) => Some(Edge::TopLeft),
+            (true, _, _, true) => Some(Edge::TopRight),
+            (_, true, true, _) => Some(Edge::BottomLeft),
+            (_, true, _, true) => Some(Edge::BottomRight),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Copy, Clone, Serialize, PartialEq)]
 pub enum KeyboardModifier {
     ShiftLeft,
     ShiftRight,
     ControlLeft,
     ControlRight,
     MetaLeft,
+    MetaRight,
     Alt,
     AltGr,
+    // match the left or the right key
+    Shift,
+    Control,
+    Meta,
+}
+
+// the alternative names of the modifiers in the config, compared like the names
+const MODIFIER_ALIASES: [(&str, KeyboardModifier); 11] = [
+    ("ctrl", KeyboardModifier::Control),
+    ("ctrlleft", KeyboardModifier::ControlLeft),
+    ("ctrlright", KeyboardModifier::ControlRight),
+    ("super", KeyboardModifier::Meta),
+    ("superleft", KeyboardModifier::MetaLeft),
+    ("superright", KeyboardModifier::MetaRight),
+    ("win", KeyboardModifier::Meta),
+    ("cmd", KeyboardModifier::Meta),
+    ("altleft", KeyboardModifier::Alt),
+    ("altright", KeyboardModifier::AltGr),
+    ("option", KeyboardModifier::Alt),
+];
+
+/// the name without its case and its separators : "control_left" and "ControlLeft"
+/// are the same
+fn normalized_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// the value named by a string of the config in any case, the serialization keeps
+/// the canonical (PascalCase) names
+fn deserialize_name<'de, D, T>(
+    deserializer: D,
+    kind: &str,
+    all: &[T],
+    aliases: &[(&str, T)],
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Copy + fmt::Debug,
+{
+    let name = String::deserialize(deserializer)?;
+    let normalized = normalized_name(&name);
+    all.iter()
+        .copied()
+        .find(|value| normalized_name(&format!("{value:?}")) == normalized)
+        .or_else(|| {
+            aliases
+                .iter()
+                .find(|(alias, _)| *alias == normalized)
+                .map(|(_, value)| *value)
+        })
+        .ok_or_else(|| {
+            let names: Vec<String> = all.iter().map(|value| format!("{value:?}")).collect();
+            de::Error::custom(format!(
+                "unknown {kind} {name:?}, expected one of {}",
+                names.join(", ")
+            ))
+        })
+}
+
+impl<'de> Deserialize<'de> for KeyboardModifier {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_name(
+            deserializer,
+            "modifier",
+            &KeyboardModifier::ALL,
+            &MODIFIER_ALIASES,
+        )
+    }
 }
 
 impl KeyboardModifier {
+    pub const ALL: [KeyboardModifier; 11] = [
+        KeyboardModifier::ShiftLeft,
+        KeyboardModifier::ShiftRight,
+        KeyboardModifier::ControlLeft,
+        KeyboardModifier::ControlRight,
+        KeyboardModifier::MetaLeft,
+        KeyboardModifier::MetaRight,
+        KeyboardModifier::Alt,
+        KeyboardModifier::AltGr,
+        KeyboardModifier::Shift,
+        KeyboardModifier::Control,
+        KeyboardModifier::Meta,
+    ];
+
     pub fn from_keyboard_state(keyboard_state: KeyboardState) -> Vec<KeyboardModifier> {
         let mut modifiers: Vec<KeyboardModifier> = vec![];
         if keyboard_state.shift_left {
@@ -155,17 +656,59 @@ impl KeyboardModifier {
         if keyboard_state.meta_left {
             modifiers.push(KeyboardModifier::MetaLeft)
         };
+        if keyboard_state.meta_right {
+            modifiers.push(KeyboardModifier::MetaRight)
+        };
         if keyboard_state.alt {
             modifiers.push(KeyboardModifier::Alt)
         };
         if keyboard_state.alt_gr {
             modifiers.push(KeyboardModifier::AltGr)
         };
+        if keyboard_state.shift_left || keyboard_state.shift_right {
+            modifiers.push(KeyboardModifier::Shift)
+        };
+        if keyboard_state.control_left || keyboard_state.control_right {
+            modifiers.push(KeyboardModifier::Control)
+        };
+        if keyboard_state.meta_left || keyboard_state.meta_right {
+            modifiers.push(KeyboardModifier::Meta)
+        };
         modifiers
     }
+
+    /// the modifier matching either the left or the right key, for a side-specific modifier
+    pub fn either_side(self) -> Option<KeyboardModifier> {
+        match self {
+            KeyboardModifier::ShiftLeft | KeyboardModifier::ShiftRight => {
+                Some(KeyboardModifier::Shift)
+            }
+            KeyboardModifier::ControlLeft | KeyboardModifier::ControlRight => {
+                Some(KeyboardModifier::Control)
+            }
+            KeyboardModifier::MetaLeft | KeyboardModifier::MetaRight => {
+                Some(KeyboardModifier::Meta)
+            }
+            _ => None,
+        }
+    }
+
+    /// the modifier is held in the keyboard state, either side for Shift, Control and Meta
+    pub fn is_held(self, keyboard_state: &KeyboardState) -> bool {
+        KeyboardModifier::from_keyboard_state(*keyboard_state)
+            .into_iter()
+            .any(|held| held == self || held.either_side() == Some(self))
+    }
+
+    pub fn is_either_side(self) -> bool {
+        matches!(
+            self,
+            KeyboardModifier::Shift | KeyboardModifier::Control | KeyboardModifier::Meta
+        )
+    }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum MouseButton {
     Left,
     Right,
@@ -178,13 +721,57 @@ pub enum MouseButton {
     Trigger,
     Thumb,
     Thumb2,
+    // the touchpad finger count, see the README
+    TwoFingers,
+    ThreeFingers,
+    FourFingers,
+    FiveFingers,
     WheelUp,
     WheelDown,
     Unknown,
     None,
 }
 
+impl<'de> Deserialize<'de> for MouseButton {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_name(deserializer, "button", &MouseButton::ALL, &[])
+    }
+}
+
 impl MouseButton {
+    pub const ALL: [MouseButton; 19] = [
+        MouseButton::Left,
+        MouseButton::Right,
+        MouseButton::Middle,
+        MouseButton::Side,
+        MouseButton::Extra,
+        MouseButton::Forward,
+        MouseButton::Back,
+        MouseButton::Task,
+        MouseButton::Trigger,
+        MouseButton::Thumb,
+        MouseButton::Thumb2,
+        MouseButton::TwoFingers,
+        MouseButton::ThreeFingers,
+        MouseButton::FourFingers,
+        MouseButton::FiveFingers,
+        MouseButton::WheelUp,
+        MouseButton::WheelDown,
+        MouseButton::Unknown,
+        MouseButton::None,
+    ];
+
+    /// the name of the button in the config
+    pub fn name(self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+
     pub fn from_rdev_event(btn: Button) -> MouseButton {
         match btn {
             Button::Left => MouseButton::Left,
@@ -198,9 +785,19 @@ impl MouseButton {
             Button::Trigger => MouseButton::Trigger,
             Button::Thumb => MouseButton::Thumb,
             Button::Thumb2 => MouseButton::Thumb2,
+            Button::DoubleTap => MouseButton::TwoFingers,
+            Button::TripleTap => MouseButton::ThreeFingers,
+            Button::QuadTap => MouseButton::FourFingers,
+            Button::QuintTap => MouseButton::FiveFingers,
             Button::Unknown(_) => MouseButton::Unknown,
         }
     }
+    /// true if the rdev button is this button, never for None : a None shape_button
+    /// records no shape, for the configs without shape bindings
+    pub fn is_rdev_button(self, button: Button) -> bool {
+        self != MouseButton::None && self.to_rdev_event() == button
+    }
+
     pub fn to_rdev_event(self) -> Button {
         match self {
             MouseButton::Left => Button::Left,
@@ -214,12 +811,18 @@ impl MouseButton {
             MouseButton::Trigger => Button::Trigger,
             MouseButton::Thumb => Button::Thumb,
             MouseButton::Thumb2 => Button::Thumb2,
+            MouseButton::TwoFingers => Button::DoubleTap,
+            MouseButton::ThreeFingers => Button::TripleTap,
+            MouseButton::FourFingers => Button::QuadTap,
+            MouseButton::FiveFingers => Button::QuintTap,
             _ => Button::Unknown(0),
         }
     }
 
-    pub fn from_rdev_wheel(delta_y: i64) -> MouseButton {
-        if delta_y > 0 {
+    /// the vertical wheel button, `invert` swaps WheelUp & WheelDown for the
+    /// inverted hardware or natural scrolling
+    pub fn from_rdev_wheel(delta_y: i64, invert: bool) -> MouseButton {
+        if (delta_y > 0) != invert {
             MouseButton::WheelUp
         } else {
             MouseButton::WheelDown
@@ -227,27 +830,53 @@ impl MouseButton {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub enum EventType {
     Press,
     Release,
+    #[default]
     Click,
     Shape,
 }
 
-impl Default for EventType {
-    fn default() -> Self {
-        EventType::Click
-    }
-}
-
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ClickEvent {
+    // None for the bindings that only have buttons
+    #[serde(default = "default_button", skip_serializing_if = "is_none_button")]
     pub button: MouseButton,
 
+    // the other buttons of the binding : it matches any of them, the button included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buttons: Vec<MouseButton>,
+
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub edges: Vec<Edge>,
 
+    // the binding doesn't match the events in one of these edges, e.g. a shape drawn
+    // anywhere but on the edges
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edges_forbidden: Vec<Edge>,
+
+    // the screen region of the center of the shape, it's only matched by the
+    // location_sensitive bindings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<Region>,
+
+    // the name of the input device, e.g. "Wacom Intuos S Pen". For the bindings : the
+    // events of the other devices don't match, the events of an unknown device match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+
+    // the other mouse buttons held when the shape_button was pressed, only for the
+    // shape_button events. For the bindings : exactly these buttons are held
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub held_buttons: Vec<MouseButton>,
+
+    // the other keys than the modifiers held during the event, e.g. ["KeyG"].
+    // For the bindings : exactly these keys are held
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<Key>,
+
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub modifiers: Vec<KeyboardModifier>,
 
@@ -260,22 +889,98 @@ pub struct ClickEvent {
     // shape points X1, Y1, X2, Y2, X3, Y3, ...
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub shapes_xy: Vec<PointHistory>,
+
+    // shapes as SVG path data, e.g. "M0,0 L0,100 L100,100", sampled on load
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shapes_svg: Vec<String>,
+
+    // shapes as direction codes, e.g. "RDLU" for right, down, left, up, sampled on load
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shapes_directions: Vec<String>,
+
+    // the name of a shape template of the config, its shapes are added on load
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape_ref: Option<String>,
+
+    // the screen quadrants overlapped by the shape, a binding matches exactly these ones.
+    // For the bindings : empty matches any quadrant
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub quadrants: Vec<Quadrant>,
+
+    // the shape ends near its start point, e.g. an O and not a C.
+    // For the bindings : None matches the open and the closed shapes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed: Option<bool>,
+
+    // a chord : the event happens while the shape_button is held
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shape_button_held: bool,
+
+    // the instant of the grabbed event, given by the grab clock, None for the bindings
+    #[serde(skip)]
+    pub time: Option<Instant>,
+
+    // the time without input before the gesture, None for the bindings
+    #[serde(skip)]
+    pub idle: Option<Duration>,
+
+    // the path length in pixels of the shape_button drag on its release, None otherwise
+    #[serde(skip)]
+    pub length: Option<u32>,
+}
+
+fn default_button() -> MouseButton {
+    MouseButton::None
+}
+
+fn is_none_button(button: &MouseButton) -> bool {
+    *button == MouseButton::None
+}
+
+impl ClickEvent {
+    /// the button is the button of this binding event or one of its buttons
+    pub fn matches_button(&self, button: MouseButton) -> bool {
+        self.button == button || self.buttons.contains(&button)
+    }
+}
+
+pub fn buttons_are_equals(buttons1: &[MouseButton], buttons2: &[MouseButton]) -> bool {
+    buttons1.len() == buttons2.len() && buttons1.iter().all(|button| buttons2.contains(button))
+}
+
+pub fn keys_are_equals(keys1: &[Key], keys2: &[Key]) -> bool {
+    keys1.len() == keys2.len() && keys1.iter().all(|key| keys2.contains(key))
 }
 
 pub fn edges_are_equals(edges1: &[Edge], edges2: &[Edge]) -> bool {
     edges1.len() == edges2.len() && edges1.iter().all(|edge| edges2.contains(edge))
 }
 
+/// the binding modifiers are all held, and every held key is required by the binding,
+/// by its side-specific modifier or by its either-side modifier
 pub fn modifiers_are_equals(
-    modifiers1: &[KeyboardModifier],
-    modifiers2: &[KeyboardModifier],
+    binding_modifiers: &[KeyboardModifier],
+    event_modifiers: &[KeyboardModifier],
 ) -> bool {
-    modifiers1.len() == modifiers2.len() && modifiers1.iter().all(|edge| modifiers2.contains(edge))
+    binding_modifiers
+        .iter()
+        .all(|modifier| event_modifiers.contains(modifier))
+        && event_modifiers
+            .iter()
+            .filter(|modifier| !modifier.is_either_side())
+            .all(|modifier| {
+                binding_modifiers.contains(modifier)
+                    || modifier
+                        .either_side()
+                        .is_some_and(|either| binding_modifiers.contains(&either))
+            })
 }
 
 #[derive(Debug, Copy, Clone)]
 pub enum ButtonState {
     Pressed(Button),
+    // the shape drawn with the held button was aborted : its points are no longer recorded
+    Aborted(Button),
     None,
 }
 
@@ -292,9 +997,43 @@ impl Point {
     }
 }
 
+/// A Point that can be shared between threads without lock : x and y are packed
+/// in a single AtomicU64, so a `get` never see a half updated point.
+#[derive(Debug, Default)]
+pub struct AtomicPoint(AtomicU64);
+
+impl AtomicPoint {
+    pub fn new(point: Point) -> Self {
+        AtomicPoint(AtomicU64::new(AtomicPoint::pack(point.x, point.y)))
+    }
+
+    pub fn set(&self, x: i32, y: i32) {
+        self.0.store(AtomicPoint::pack(x, y), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> Point {
+        let packed = self.0.load(Ordering::Relaxed);
+        Point {
+            x: (packed >> 32) as u32 as i32,
+            y: packed as u32 as i32,
+        }
+    }
+
+    fn pack(x: i32, y: i32) -> u64 {
+        ((x as u32 as u64) << 32) | (y as u32 as u64)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::event::{Point, PointHistory};
+    use rdev::Button;
+
+    use crate::compare_angles::compare_angles_with_offset;
+    use crate::event::{
+        modifiers_are_equals, AtomicPoint, Bounds, Edge, KeyboardModifier, KeyboardState,
+        MouseButton, Point, PointHistory, Quadrant, Region, RelativeTo, WINDOW_EDGE_THRESHOLD,
+    };
+    use crate::points_to_angles::points_to_angles;
 
     #[test]
     fn test_point_history_serialize() {
@@ -313,4 +1052,379 @@ mod tests {
         let v: Vec<i32> = ph.to_vec().iter().flat_map(|p| vec![p.x, p.y]).collect();
         assert_eq!(v, vec![0, 0, 1, 1, 2, 2, 3, 3]);
     }
+
+    #[test]
+    fn test_point_history_from_points() {
+        let points = vec![Point { x: 0, y: 1 }, Point { x: 2, y: 3 }];
+        let ph = PointHistory::from_points(points.clone());
+        assert_eq!(ph.to_vec(), points);
+
+        let serialized = serde_json::to_string(&ph).unwrap();
+        assert_eq!(serialized, "[0,1,2,3]");
+        let deserialized: PointHistory = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, ph);
+    }
+
+    #[test]
+    fn test_point_history_deserialize_odd_length() {
+        let err = serde_json::from_str::<PointHistory>("[0,1,2]").unwrap_err();
+        assert!(err.to_string().contains("an even number of coordinates"));
+        assert!(serde_json::from_str::<PointHistory>("[]")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_atomic_point() {
+        let point = AtomicPoint::new(Point { x: 10, y: 20 });
+        assert_eq!(point.get(), Point { x: 10, y: 20 });
+        point.set(-1920, 1079);
+        assert_eq!(point.get(), Point { x: -1920, y: 1079 });
+        point.set(i32::MAX, i32::MIN);
+        assert_eq!(
+            point.get(),
+            Point {
+                x: i32::MAX,
+                y: i32::MIN
+            }
+        );
+    }
+
+    #[test]
+    fn test_point_history_capacity() {
+        let mut points = PointHistory::with_capacity(2);
+        assert_eq!(points.capacity(), 2);
+        assert_eq!(points.remaining(), 2);
+        points.push(Point { x: 0, y: 0 });
+        assert_eq!(points.remaining(), 1);
+        assert!(!points.is_full());
+        points.push(Point { x: 1, y: 1 });
+        assert_eq!(points.remaining(), 0);
+        assert!(points.is_full());
+        points.clear();
+        assert!(points.is_empty());
+        assert_eq!(points.capacity(), 2);
+
+        let points: PointHistory =
+            serde_json::from_str(&format!("[{}]", vec!["1,2"; 1500].join(","))).unwrap();
+        assert_eq!(points.len(), 1500);
+        assert!(points.is_full());
+    }
+
+    #[test]
+    fn test_from_rdev_wheel() {
+        assert_eq!(MouseButton::from_rdev_wheel(1, false), MouseButton::WheelUp);
+        assert_eq!(
+            MouseButton::from_rdev_wheel(-1, false),
+            MouseButton::WheelDown
+        );
+        assert_eq!(
+            MouseButton::from_rdev_wheel(1, true),
+            MouseButton::WheelDown
+        );
+        assert_eq!(MouseButton::from_rdev_wheel(-1, true), MouseButton::WheelUp);
+    }
+
+    #[test]
+    fn test_touchpad_fingers() {
+        for (button, mouse_button) in [
+            (Button::DoubleTap, MouseButton::TwoFingers),
+            (Button::TripleTap, MouseButton::ThreeFingers),
+            (Button::QuadTap, MouseButton::FourFingers),
+            (Button::QuintTap, MouseButton::FiveFingers),
+        ] {
+            assert_eq!(MouseButton::from_rdev_event(button), mouse_button);
+            assert_eq!(mouse_button.to_rdev_event(), button);
+        }
+    }
+
+    #[test]
+    fn test_either_side_modifiers() {
+        let keyboard_state = KeyboardState {
+            control_right: true,
+            ..KeyboardState::default()
+        };
+        let modifiers = KeyboardModifier::from_keyboard_state(keyboard_state);
+        assert_eq!(
+            modifiers,
+            vec![KeyboardModifier::ControlRight, KeyboardModifier::Control]
+        );
+
+        assert!(modifiers_are_equals(
+            &[KeyboardModifier::Control],
+            &modifiers
+        ));
+        assert!(modifiers_are_equals(
+            &[KeyboardModifier::ControlRight],
+            &modifiers
+        ));
+        assert!(!modifiers_are_equals(
+            &[KeyboardModifier::ControlLeft],
+            &modifiers
+        ));
+        assert!(!modifiers_are_equals(&[], &modifiers));
+        assert!(!modifiers_are_equals(
+            &[KeyboardModifier::Control, KeyboardModifier::Shift],
+            &modifiers
+        ));
+    }
+
+    #[test]
+    fn test_button_names() {
+        assert_eq!(MouseButton::Thumb2.name(), "Thumb2");
+        for button in MouseButton::ALL {
+            let json = format!("\"{}\"", button.name());
+            assert_eq!(serde_json::from_str::<MouseButton>(&json).unwrap(), button);
+        }
+    }
+
+    #[test]
+    fn test_names_in_any_case() {
+        let button = |json: &str| serde_json::from_str::<MouseButton>(json);
+        assert_eq!(button("\"left\"").unwrap(), MouseButton::Left);
+        assert_eq!(button("\"WHEELUP\"").unwrap(), MouseButton::WheelUp);
+        assert_eq!(button("\"wheel_down\"").unwrap(), MouseButton::WheelDown);
+        assert_eq!(button("\"thumb2\"").unwrap(), MouseButton::Thumb2);
+        let error = button("\"lft\"").unwrap_err().to_string();
+        assert!(error.contains("unknown button \"lft\""), "{error}");
+        assert!(error.contains("Left, Right, Middle"), "{error}");
+
+        let modifier = |json: &str| serde_json::from_str::<KeyboardModifier>(json);
+        assert_eq!(modifier("\"shift\"").unwrap(), KeyboardModifier::Shift);
+        assert_eq!(modifier("\"ctrl\"").unwrap(), KeyboardModifier::Control);
+        assert_eq!(
+            modifier("\"control_left\"").unwrap(),
+            KeyboardModifier::ControlLeft
+        );
+        assert_eq!(modifier("\"Super\"").unwrap(), KeyboardModifier::Meta);
+        assert_eq!(modifier("\"altgr\"").unwrap(), KeyboardModifier::AltGr);
+        assert!(modifier("\"hyper\"").is_err());
+        for modifier in KeyboardModifier::ALL {
+            let json = serde_json::to_string(&modifier).unwrap();
+            assert_eq!(json, format!("\"{modifier:?}\""));
+            assert_eq!(
+                serde_json::from_str::<KeyboardModifier>(&json).unwrap(),
+                modifier
+            );
+        }
+        // the serialization stays canonical
+        assert_eq!(
+            serde_json::to_string(&button("\"middle\"").unwrap()).unwrap(),
+            "\"Middle\""
+        );
+    }
+
+    #[test]
+    fn test_edges_after_screen_change() {
+        assert_eq!(
+            Edge::edges_from_bounds(1919, 0, Bounds::from_size(1920, 1080), 0),
+            vec![Edge::Right, Edge::Top]
+        );
+        // a second monitor plugged on the right : the same position is not an edge anymore
+        assert_eq!(
+            Edge::edges_from_bounds(1919, 0, Bounds::from_size(3840, 1080), 0),
+            vec![Edge::Top]
+        );
+        assert_eq!(
+            Edge::edges_from_bounds(3839, 1079, Bounds::from_size(3840, 1080), 0),
+            vec![Edge::Right, Edge::Bottom]
+        );
+    }
+
+    #[test]
+    fn test_edges_from_bounds() {
+        let bounds = Bounds::from_size(1920, 1080);
+        assert_eq!(Edge::edges_from_bounds(0, 500, bounds, 0), vec![Edge::Left]);
+        assert!(Edge::edges_from_bounds(1, 500, bounds, 0).is_empty());
+        assert!(Edge::edges_from_bounds(960, 540, bounds, 0).is_empty());
+        assert_eq!(
+            Edge::edges_from_bounds(1917, 1078, bounds, 2),
+            vec![Edge::Right, Edge::Bottom]
+        );
+        assert!(Edge::edges_from_bounds(1916, 1076, bounds, 2).is_empty());
+
+        // a screen on the right of a 1920 wide screen
+        let bounds = Bounds {
+            x: 1920,
+            y: 0,
+            width: 1280,
+            height: 1024,
+        };
+        assert_eq!(
+            Edge::edges_from_bounds(1920, 0, bounds, 0),
+            vec![Edge::Left, Edge::Top]
+        );
+        assert_eq!(
+            Edge::edges_from_bounds(3199, 1023, bounds, 0),
+            vec![Edge::Right, Edge::Bottom]
+        );
+    }
+
+    #[test]
+    fn test_corner_from_bounds() {
+        let bounds = Bounds::from_size(1920, 1080);
+        assert_eq!(Edge::corner_from_bounds(0, 0, bounds, 0), None);
+        assert_eq!(
+            Edge::corner_from_bounds(0, 0, bounds, 20),
+            Some(Edge::TopLeft)
+        );
+        assert_eq!(
+            Edge::corner_from_bounds(19, 19, bounds, 20),
+            Some(Edge::TopLeft)
+        );
+        assert_eq!(Edge::corner_from_bounds(20, 0, bounds, 20), None);
+        assert_eq!(
+            Edge::corner_from_bounds(1900, 5, bounds, 20),
+            Some(Edge::TopRight)
+        );
+        assert_eq!(
+            Edge::corner_from_bounds(3, 1079, bounds, 20),
+            Some(Edge::BottomLeft)
+        );
+        assert_eq!(
+            Edge::corner_from_bounds(1919, 1060, bounds, 20),
+            Some(Edge::BottomRight)
+        );
+        assert_eq!(Edge::corner_from_bounds(960, 0, bounds, 20), None);
+    }
+
+    #[test]
+    fn test_edges_relative_to_window() {
+        let window = Bounds {
+            x: 960,
+            y: 0,
+            width: 960,
+            height: 1080,
+        };
+        assert_eq!(
+            Edge::edges_from_pos(960 + WINDOW_EDGE_THRESHOLD as i32, 500, 0, Some(window)),
+            vec![Edge::Left]
+        );
+        assert!(Edge::edges_from_pos(1200, 500, 0, Some(window)).is_empty());
+        assert_eq!(
+            Edge::edges_from_pos(1919, 1079, 20, Some(window)),
+            vec![Edge::Right, Edge::Bottom, Edge::BottomRight]
+        );
+        // the pointer over an other window
+        assert!(Edge::edges_from_pos(959, 500, 0, Some(window)).is_empty());
+        assert!(window.contains(960, 0));
+        assert!(!window.contains(1920, 0));
+    }
+
+    #[test]
+    fn test_region_from_bounds() {
+        let bounds = Bounds::from_size(1920, 1080);
+        assert_eq!(Region::from_bounds(0, 0, bounds), Region::TopLeft);
+        assert_eq!(Region::from_bounds(960, 540, bounds), Region::Center);
+        assert_eq!(Region::from_bounds(1919, 1079, bounds), Region::BottomRight);
+        assert_eq!(Region::from_bounds(1800, 300, bounds), Region::TopRight);
+        assert_eq!(Region::from_bounds(639, 360, bounds), Region::Left);
+        assert_eq!(Region::from_bounds(640, 360, bounds), Region::Center);
+        // the points outside the bounds
+        assert_eq!(Region::from_bounds(-10, 2000, bounds), Region::BottomLeft);
+
+        let window = Bounds {
+            x: 960,
+            y: 0,
+            width: 960,
+            height: 1080,
+        };
+        assert_eq!(
+            Region::from_pos(1000, 1000, Some(window)),
+            Some(Region::BottomLeft)
+        );
+    }
+
+    #[test]
+    fn test_downsample() {
+        let mut points = PointHistory::new();
+        (0..100).for_each(|i| points.push(Point { x: i, y: 0 }));
+        (0..100).for_each(|i| points.push(Point { x: 100, y: i }));
+        let downsampled = points.downsample(21);
+        assert_eq!(downsampled.len(), 21);
+        assert_eq!(downsampled.capacity(), points.capacity());
+        assert_eq!(downsampled.first(), points.first());
+        assert_eq!(downsampled.last(), points.last());
+        assert_eq!(downsampled[10], Point { x: 100, y: 0 });
+        assert_eq!(points.downsample(500), points);
+        assert_eq!(points.downsample(1).len(), 1);
+
+        // the shape is preserved : the angles stay close
+        let angles = points_to_angles(&points);
+        let downsampled_angles = points_to_angles(&points.downsample(50));
+        let diff = compare_angles_with_offset(&angles, &downsampled_angles);
+        assert!(diff < 0.1, "diff = {diff}");
+
+        let still = PointHistory::from_points(vec![Point { x: 5, y: 5 }; 10]);
+        assert_eq!(still.downsample(3).len(), 3);
+    }
+
+    #[test]
+    fn test_quadrants() {
+        let bounds = Bounds::from_size(1920, 1080);
+        let quadrants = |min: (i32, i32), max: (i32, i32)| {
+            Quadrant::overlapped_in(
+                Point { x: min.0, y: min.1 },
+                Point { x: max.0, y: max.1 },
+                bounds,
+            )
+        };
+        assert_eq!(quadrants((10, 10), (900, 500)), vec![Quadrant::TopLeft]);
+        assert_eq!(
+            quadrants((10, 10), (1000, 500)),
+            vec![Quadrant::TopLeft, Quadrant::TopRight]
+        );
+        assert_eq!(
+            quadrants((1000, 600), (2500, 2000)),
+            vec![Quadrant::BottomRight]
+        );
+        assert_eq!(quadrants((0, 0), (1919, 1079)).len(), 4);
+        assert_eq!(
+            Quadrant::overlapped_by(
+                &PointHistory::from_points(vec![Point { x: 105, y: 600 }]),
+                Some(Bounds {
+                    x: 100,
+                    y: 0,
+                    width: 20,
+                    height: 1000
+                })
+            ),
+            vec![Quadrant::BottomLeft]
+        );
+        assert!(Quadrant::overlapped_by(&PointHistory::new(), Some(bounds)).is_empty());
+    }
+
+    #[test]
+    fn test_edges_in_negative_origin() {
+        // a desktop whose left monitor is at negative coordinates
+        let desktop = Bounds {
+            x: -1920,
+            y: -200,
+            width: 3840,
+            height: 1280,
+        };
+        assert_eq!(
+            Edge::edges_in(-1920, 300, 0, desktop, RelativeTo::Screen),
+            vec![Edge::Left]
+        );
+        assert_eq!(
+            Edge::edges_in(1919, -200, 0, desktop, RelativeTo::Screen),
+            vec![Edge::Right, Edge::Top]
+        );
+        assert!(Edge::edges_in(0, 300, 0, desktop, RelativeTo::Screen).is_empty());
+        assert_eq!(
+            Edge::edges_in(-1900, 1070, 30, desktop, RelativeTo::Screen),
+            vec![Edge::BottomLeft]
+        );
+    }
+
+    #[test]
+    fn test_is_rdev_button() {
+        assert!(MouseButton::Right.is_rdev_button(Button::Right));
+        assert!(!MouseButton::Right.is_rdev_button(Button::Left));
+        // None and Unknown are both Unknown(0) for rdev, only Unknown is a button
+        assert!(MouseButton::Unknown.is_rdev_button(Button::Unknown(0)));
+        assert!(!MouseButton::None.is_rdev_button(Button::Unknown(0)));
+        assert!(!MouseButton::None.is_rdev_button(Button::Right));
+    }
 }
@@ -0,0 +1,278 @@
+use std::process::Command;
+use std::sync::{Mutex, Once};
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use log::{debug, trace, warn};
+
+use crate::event::Bounds;
+
+// the focused window class and geometry are queried at most once per FOCUS_CACHE_DURATION
+const FOCUS_CACHE_DURATION: Duration = Duration::from_millis(500);
+
+lazy_static! {
+    static ref FOCUS_CACHE: Mutex<FocusCache<String>> = Mutex::new(FocusCache::default());
+    static ref FOCUS_GEOMETRY_CACHE: Mutex<FocusCache<Bounds>> = Mutex::new(FocusCache::default());
+    static ref WORKSPACE_CACHE: Mutex<FocusCache<u32>> = Mutex::new(FocusCache::default());
+}
+
+// the unknown workspace is warned once, e.g. with a window manager without EWMH
+static WORKSPACE_UNKNOWN_WARNING: Once = Once::new();
+
+/// the last known value of a property of the focused window
+#[derive(Debug)]
+pub struct FocusCache<T> {
+    value: Option<T>,
+    checked_at: Option<Instant>,
+}
+
+impl<T> Default for FocusCache<T> {
+    fn default() -> Self {
+        FocusCache {
+            value: None,
+            checked_at: None,
+        }
+    }
+}
+
+impl<T: Clone + std::fmt::Debug> FocusCache<T> {
+    /// the cached value, or the result of query if the cache is older than FOCUS_CACHE_DURATION
+    pub fn get(&mut self, now: Instant, query: impl FnOnce() -> Option<T>) -> Option<T> {
+        let expired = self.checked_at.is_none_or(|checked_at| {
+            now.saturating_duration_since(checked_at) >= FOCUS_CACHE_DURATION
+        });
+        if expired {
+            self.value = query();
+            self.checked_at = Some(now);
+            trace!("focused window : {:?}", self.value);
+        }
+        self.value.clone()
+    }
+
+    /// the last value, even expired : never blocks on a query
+    pub fn cached(&self) -> Option<T> {
+        self.value.clone()
+    }
+}
+
+/// the class of the focused window, with xdotool (X11 only)
+fn query_focused_window_class() -> Option<String> {
+    let output = Command::new("xdotool")
+        .args(["getactivewindow", "getwindowclassname"])
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        }
+        Ok(output) => {
+            debug!(
+                "couldn't get the focused window class : {:?}",
+                output.status
+            );
+            None
+        }
+        Err(err) => {
+            debug!("couldn't run xdotool to get the focused window class : {err:?}");
+            None
+        }
+    }
+}
+
+/// the bounds of the `xdotool getwindowgeometry --shell` output
+pub fn parse_window_geometry(output: &str) -> Option<Bounds> {
+    let mut bounds = Bounds::from_size(0, 0);
+    let (mut x, mut y, mut width, mut height) = (false, false, false, false);
+    for line in output.lines() {
+        match line.trim().split_once('=') {
+            Some(("X", value)) => {
+                bounds.x = value.parse().ok()?;
+                x = true;
+            }
+            Some(("Y", value)) => {
+                bounds.y = value.parse().ok()?;
+                y = true;
+            }
+            Some(("WIDTH", value)) => {
+                bounds.width = value.parse().ok()?;
+                width = true;
+            }
+            Some(("HEIGHT", value)) => {
+                bounds.height = value.parse().ok()?;
+                height = true;
+            }
+            _ => {}
+        }
+    }
+    (x && y && width && height && bounds.width > 0 && bounds.height > 0).then_some(bounds)
+}
+
+/// the geometry of the focused window, with xdotool (X11 only)
+fn query_focused_window_geometry() -> Option<Bounds> {
+    let output = Command::new("xdotool")
+        .args(["getactivewindow", "getwindowgeometry", "--shell"])
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_window_geometry(&String::from_utf8_lossy(&output.stdout))
+        }
+        Ok(output) => {
+            debug!(
+                "couldn't get the focused window geometry : {:?}",
+                output.status
+            );
+            None
+        }
+        Err(err) => {
+            debug!("couldn't run xdotool to get the focused window geometry : {err:?}");
+            None
+        }
+    }
+}
+
+/// the bounds of the focused window, None if they're unavailable (e.g. no focused window)
+pub fn focused_window_bounds(now: Instant) -> Option<Bounds> {
+    FOCUS_GEOMETRY_CACHE
+        .lock()
+        .unwrap()
+        .get(now, query_focused_window_geometry)
+}
+
+/// the workspace number of the `xdotool get_desktop` output
+pub fn parse_workspace(output: &str) -> Option<u32> {
+    output.trim().parse().ok()
+}
+
+/// the current workspace, with xdotool from the EWMH _NET_CURRENT_DESKTOP (X11 only)
+fn query_current_workspace() -> Option<u32> {
+    let output = Command::new("xdotool").arg("get_desktop").output();
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_workspace(&String::from_utf8_lossy(&output.stdout))
+        }
+        Ok(output) => {
+            debug!("couldn't get the current workspace : {:?}", output.status);
+            None
+        }
+        Err(err) => {
+            debug!("couldn't run xdotool to get the current workspace : {err:?}");
+            None
+        }
+    }
+}
+
+/// true if the current workspace is the workspace, or if the current workspace is unknown :
+/// the condition is ignored, with a warning the first time
+pub fn is_on_workspace(workspace: u32, now: Instant) -> bool {
+    let current = WORKSPACE_CACHE
+        .lock()
+        .unwrap()
+        .get(now, query_current_workspace);
+    match current {
+        Some(current) => current == workspace,
+        None => {
+            WORKSPACE_UNKNOWN_WARNING.call_once(|| {
+                warn!(
+                    "the current workspace is unknown (xdotool and a window manager supporting \
+                     EWMH are needed) : the workspace conditions of the bindings are ignored"
+                )
+            });
+            true
+        }
+    }
+}
+
+pub fn is_excluded_class(exclude_window_classes: &[String], class: Option<&str>) -> bool {
+    class.is_some_and(|class| {
+        exclude_window_classes
+            .iter()
+            .any(|excluded| excluded == class)
+    })
+}
+
+/// true if the focused window is one of exclude_window_classes, the class is only queried
+/// again on refresh (the presses and the wheel), the other events use the last class
+pub fn is_focused_window_excluded(
+    exclude_window_classes: &[String],
+    now: Instant,
+    refresh: bool,
+) -> bool {
+    if exclude_window_classes.is_empty() {
+        return false;
+    }
+    let mut cache = FOCUS_CACHE.lock().unwrap();
+    let class = if refresh {
+        cache.get(now, query_focused_window_class)
+    } else {
+        cache.cached()
+    };
+    is_excluded_class(exclude_window_classes, class.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::clock::{Clock, MockClock};
+    use crate::event::Bounds;
+    use crate::focus::{is_excluded_class, parse_window_geometry, parse_workspace, FocusCache};
+
+    #[test]
+    fn test_is_excluded_class() {
+        let excluded = vec!["Remmina".to_string(), "VirtualBox Machine".to_string()];
+        assert!(is_excluded_class(&excluded, Some("Remmina")));
+        assert!(is_excluded_class(&excluded, Some("VirtualBox Machine")));
+        assert!(!is_excluded_class(&excluded, Some("firefox")));
+        assert!(!is_excluded_class(&excluded, None));
+        assert!(!is_excluded_class(&[], Some("Remmina")));
+    }
+
+    #[test]
+    fn test_focus_cache() {
+        let clock = MockClock::new();
+        let mut cache = FocusCache::default();
+        assert_eq!(
+            cache.get(clock.now(), || Some("a".to_string())),
+            Some("a".to_string())
+        );
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(
+            cache.get(clock.now(), || Some("b".to_string())),
+            Some("a".to_string())
+        );
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(cache.cached(), Some("a".to_string()));
+        assert_eq!(
+            cache.get(clock.now(), || Some("b".to_string())),
+            Some("b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_window_geometry() {
+        let output = "WINDOW=6291467\nX=1920\nY=24\nWIDTH=960\nHEIGHT=1056\nSCREEN=0\n";
+        assert_eq!(
+            parse_window_geometry(output),
+            Some(Bounds {
+                x: 1920,
+                y: 24,
+                width: 960,
+                height: 1056
+            })
+        );
+        assert_eq!(parse_window_geometry(""), None);
+        assert_eq!(parse_window_geometry("X=0\nY=0\nWIDTH=960\n"), None);
+        assert_eq!(parse_window_geometry("X=0\nY=0\nWIDTH=0\nHEIGHT=0\n"), None);
+        assert_eq!(
+            parse_window_geometry("X=a\nY=0\nWIDTH=960\nHEIGHT=1056\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_workspace() {
+        assert_eq!(parse_workspace("2\n"), Some(2));
+        assert_eq!(parse_workspace("0"), Some(0));
+        assert_eq!(parse_workspace(""), None);
+        assert_eq!(parse_workspace("-1\n"), None);
+    }
+}
@@ -1,25 +1,260 @@
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use std::{thread, time};
 
 use log::Level::Trace;
-use log::{debug, log_enabled, trace};
-use rdev::{grab, Event, EventType, GrabError, Key};
+use log::{debug, error, info, log_enabled, trace, warn};
+use rdev::{grab, Button, Event, EventType, GrabError, Key};
 
 use crate::args::Args;
+use crate::clock::{Clock, SystemClock};
 use crate::config::Config;
 use crate::event::{
-    ButtonState, ClickEvent, Edge, KeyboardModifier, KeyboardState, MouseButton, Point,
-    PointHistory, PointHistoryArcMutex,
+    AbortTrigger, AtomicPoint, Bounds, ButtonState, ClickEvent, Edge, KeyboardModifier,
+    KeyboardState, MouseButton, Point, PointHistory, PointHistoryArcMutex, Quadrant, Region,
+    RelativeTo,
 };
-use crate::{event, listen, points_to_angles, trace_svg};
+use crate::repeat::stop_all_repeats;
+use crate::{event, focus, listen, points_to_angles, reset, trace_svg};
 
+// the maximum distance between the ends of a closed shape, relatively to the shape size
+const CLOSED_MAX_DISTANCE: f64 = 0.2;
+
+#[derive(Clone)]
 pub struct GrabContext {
     pub point_history: PointHistoryArcMutex,
     pub button_state: Arc<Mutex<ButtonState>>,
     pub keyboard_state: Arc<Mutex<KeyboardState>>,
     pub config: Arc<Mutex<Config>>,
-    pub last_point: Arc<Mutex<Point>>,
+    pub last_point: Arc<AtomicPoint>,
     pub args: Arc<Args>,
+    pub clock: Arc<dyn Clock>,
+    pub idle_state: Arc<Mutex<IdleState>>,
+    pub chord_state: Arc<Mutex<ChordState>>,
+    pub sampler: Arc<Mutex<Sampler>>,
+    pub shape_press_time: Arc<Mutex<Option<Instant>>>,
+    pub modifier_resync: Arc<Mutex<ModifierResync>>,
+    pub held_buttons: Arc<Mutex<HeldButtons>>,
+    pub held_keys: Arc<Mutex<HeldKeys>>,
+}
+
+impl GrabContext {
+    /// the state of a new grab : no button or modifier is held, the keys pressed before
+    /// it are not tracked
+    pub fn new(config: Arc<Mutex<Config>>, args: Arc<Args>, clock: Arc<dyn Clock>) -> Self {
+        GrabContext {
+            point_history: Arc::new(Mutex::new(PointHistory::new())),
+            button_state: Arc::new(Mutex::new(ButtonState::None)),
+            keyboard_state: Arc::new(Mutex::new(KeyboardState::default())),
+            config,
+            last_point: Arc::new(AtomicPoint::new(Point { x: 10, y: 10 })),
+            args,
+            idle_state: Arc::new(Mutex::new(IdleState::new(clock.now()))),
+            clock,
+            chord_state: Arc::new(Mutex::new(ChordState::default())),
+            sampler: Arc::new(Mutex::new(Sampler::default())),
+            shape_press_time: Arc::new(Mutex::new(None)),
+            modifier_resync: Arc::new(Mutex::new(ModifierResync::default())),
+            held_buttons: Arc::new(Mutex::new(HeldButtons::default())),
+            held_keys: Arc::new(Mutex::new(HeldKeys::default())),
+        }
+    }
+
+    /// back to the state of a new grab after a desync (a stuck button or modifier), the
+    /// config, the cursor position and the idle time are kept. The repeats are stopped.
+    pub fn reset_state(&self) {
+        self.point_history.lock().unwrap().clear();
+        *self.button_state.lock().unwrap() = ButtonState::None;
+        *self.keyboard_state.lock().unwrap() = KeyboardState::default();
+        *self.chord_state.lock().unwrap() = ChordState::default();
+        *self.sampler.lock().unwrap() = Sampler::default();
+        *self.shape_press_time.lock().unwrap() = None;
+        *self.modifier_resync.lock().unwrap() = ModifierResync::default();
+        *self.held_buttons.lock().unwrap() = HeldButtons::default();
+        *self.held_keys.lock().unwrap() = HeldKeys::default();
+        stop_all_repeats();
+        info!("the grab state has been reset");
+    }
+
+    /// a panic while a lock is held poisons it : clear them to keep processing the next events
+    fn clear_poison(&self) {
+        self.point_history.clear_poison();
+        self.button_state.clear_poison();
+        self.keyboard_state.clear_poison();
+        self.config.clear_poison();
+        self.idle_state.clear_poison();
+        self.chord_state.clear_poison();
+        self.sampler.clear_poison();
+        self.shape_press_time.clear_poison();
+        self.modifier_resync.clear_poison();
+        self.held_buttons.clear_poison();
+        self.held_keys.clear_poison();
+    }
+}
+
+// a held modifier without press (auto-repeat) during this time has been released
+const MODIFIER_STALE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A held key repeats its press (the evdev auto-repeat) : a modifier without press
+/// during MODIFIER_STALE_TIMEOUT has been released without its release event, e.g.
+/// during a focus change, and the keyboard state is corrected.
+#[derive(Debug, Default)]
+pub struct ModifierResync {
+    last_press: Vec<(Key, Instant)>,
+}
+
+impl ModifierResync {
+    pub fn press(&mut self, key: Key, now: Instant) {
+        self.release(key);
+        self.last_press.push((key, now));
+    }
+
+    pub fn release(&mut self, key: Key) {
+        self.last_press.retain(|(pressed, _)| *pressed != key);
+    }
+
+    /// release the stale modifiers in the keyboard state, return them
+    pub fn resync(&mut self, keyboard_state: &mut KeyboardState, now: Instant) -> Vec<Key> {
+        let (stale, held): (Vec<_>, Vec<_>) = self
+            .last_press
+            .iter()
+            .partition(|(_, time)| now.saturating_duration_since(*time) > MODIFIER_STALE_TIMEOUT);
+        self.last_press = held;
+        stale
+            .into_iter()
+            .map(|(key, _)| {
+                keyboard_state.set_key(key, false);
+                warn!("keyboard state desync corrected : {key:?} is released");
+                key
+            })
+            .collect()
+    }
+}
+
+/// limit the points pushed to the point history to one per sample_interval_ms
+#[derive(Debug, Default)]
+pub struct Sampler {
+    last_sample: Option<Instant>,
+}
+
+impl Sampler {
+    /// return true if the point at now must be recorded, and record its time
+    pub fn sample(&mut self, now: Instant, sample_interval: Duration) -> bool {
+        let due = self
+            .last_sample
+            .is_none_or(|last| now.saturating_duration_since(last) >= sample_interval);
+        if due {
+            self.last_sample = Some(now);
+        }
+        due
+    }
+}
+
+/// the shape_button used as a modifier : the other buttons and the wheel events
+/// that happen while it's held are chords
+#[derive(Debug, Default)]
+pub struct ChordState {
+    pub held: bool,
+    // a chord has been done during the current hold : its release is consumed
+    pub used: bool,
+}
+
+impl ChordState {
+    pub fn press_shape_button(&mut self) {
+        self.held = true;
+        self.used = false;
+    }
+
+    /// an event of another button, return true if it's a chord
+    pub fn other_event(&mut self) -> bool {
+        self.used |= self.held;
+        self.held
+    }
+
+    /// return true if a chord has been done during the hold
+    pub fn release_shape_button(&mut self) -> bool {
+        let used = self.held && self.used;
+        self.held = false;
+        self.used = false;
+        used
+    }
+}
+
+/// the held mouse buttons, and the other ones held when the shape_button was pressed
+#[derive(Debug, Default)]
+pub struct HeldButtons {
+    held: Vec<Button>,
+    at_shape_press: Vec<MouseButton>,
+}
+
+impl HeldButtons {
+    /// record the press of the button, return the other held buttons
+    pub fn press(&mut self, button: Button) -> Vec<MouseButton> {
+        let others = self.others(button);
+        if !self.held.contains(&button) {
+            self.held.push(button);
+        }
+        others
+    }
+
+    pub fn release(&mut self, button: Button) {
+        self.held.retain(|held| *held != button);
+    }
+
+    fn others(&self, button: Button) -> Vec<MouseButton> {
+        self.held
+            .iter()
+            .filter(|held| **held != button)
+            .map(|held| MouseButton::from_rdev_event(*held))
+            .collect()
+    }
+}
+
+/// the held keys that aren't modifiers, in the press order
+#[derive(Debug, Default)]
+pub struct HeldKeys(Vec<Key>);
+
+impl HeldKeys {
+    pub fn press(&mut self, key: Key) {
+        // the auto-repeat presses the held key again
+        if !self.0.contains(&key) {
+            self.0.push(key);
+        }
+    }
+
+    pub fn release(&mut self, key: Key) {
+        self.0.retain(|held| *held != key);
+    }
+
+    pub fn keys(&self) -> Vec<Key> {
+        self.0.clone()
+    }
+}
+
+/// the time without input before the gestures, for the idle_min_ms bindings
+#[derive(Debug)]
+pub struct IdleState {
+    last_input: Instant,
+    // the idle time before the press of the current gesture, reported on its release
+    press_idle: Option<Duration>,
+}
+
+impl IdleState {
+    pub fn new(now: Instant) -> Self {
+        IdleState {
+            last_input: now,
+            press_idle: None,
+        }
+    }
+
+    /// record an input event at now, return the idle time before it
+    pub fn input(&mut self, now: Instant) -> Duration {
+        let idle = now.saturating_duration_since(self.last_input);
+        self.last_input = now;
+        idle
+    }
 }
 
 pub fn start_grab_binding(
@@ -29,25 +264,74 @@ pub fn start_grab_binding(
 ) -> Result<(), GrabError> {
     // FIXME : to avoid "Release Enter key event" to be lost (if run the script by Enter press in a terminal)
     thread::sleep(time::Duration::from_millis(300));
-    let point_history: PointHistoryArcMutex = Arc::new(Mutex::new(PointHistory::new()));
-    let button_state: Arc<Mutex<ButtonState>> = Arc::new(Mutex::new(ButtonState::None));
-    let keyboard_state: Arc<Mutex<KeyboardState>> = Arc::new(Mutex::new(KeyboardState::default()));
-    let last_point: Arc<Mutex<Point>> = Arc::new(Mutex::new(Point { x: 10, y: 10 }));
+    let grab_nice = config.lock().unwrap().grab_nice;
+    let context = GrabContext::new(config, args.clone(), Arc::new(SystemClock));
     if !args.no_listen {
-        listen::start_listen(last_point.clone());
+        listen::start_listen(context.last_point.clone());
+    }
+    if let Some(nice) = grab_nice {
+        set_thread_nice(nice);
     }
+    reset::listen_reset_signal();
 
     debug!("Start grab");
     grab(move |event: Event| {
-        let context = GrabContext {
-            point_history: point_history.clone(),
-            button_state: button_state.clone(),
-            keyboard_state: keyboard_state.clone(),
-            config: config.clone(),
-            last_point: last_point.clone(),
-            args: args.clone(),
-        };
-        grab_event_fn(event, context, process_event_fn)
+        if reset::take_reset_request() {
+            context.reset_state();
+        }
+        catch_event_panic(event.time, event.event_type, || {
+            grab_event_fn(event, context.clone(), process_event_fn)
+        })
+        .unwrap_or_else(|fallback| {
+            context.clear_poison();
+            fallback
+        })
+    })
+}
+
+/// Set the nice value of the current thread (the grab one, the threads it spawns inherit it),
+/// a failure, e.g. a negative value without the privileges, keeps the default priority.
+fn set_thread_nice(nice: i32) {
+    // on Linux, the priority of the pid 0 is the one of the calling thread
+    match rustix::process::setpriority_process(None, nice) {
+        Ok(()) => info!("grab thread nice value set to {nice}"),
+        Err(err) => warn!(
+            "can't set the grab thread nice value to {nice} ({err}), a negative value \
+             needs the CAP_SYS_NICE capability : continue at the default priority"
+        ),
+    }
+}
+
+/// the panic payload message, when it's a string (the panic! and unwrap ones)
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Run the processing of an event, a panic is logged and the event is propagated
+/// in the Err, so that a single bad event or binding doesn't stop the grab.
+/// The event isn't cloned for this fallback : it is rebuilt from its time and type,
+/// without its name and device, the grab only checks that an event is returned.
+fn catch_event_panic(
+    time: time::SystemTime,
+    event_type: EventType,
+    process: impl FnOnce() -> Option<Event>,
+) -> Result<Option<Event>, Option<Event>> {
+    catch_unwind(AssertUnwindSafe(process)).map_err(|payload| {
+        error!(
+            "Panic while processing the event {:?} : {}, the grab continues",
+            event_type,
+            panic_message(payload.as_ref())
+        );
+        Some(Event {
+            time,
+            name: None,
+            event_type,
+            device: None,
+        })
     })
 }
 
@@ -60,46 +344,154 @@ pub fn grab_event_fn(
         config,
         last_point,
         args,
+        clock,
+        idle_state,
+        chord_state,
+        sampler,
+        shape_press_time,
+        modifier_resync,
+        held_buttons,
+        held_keys,
     }: GrabContext,
     process_event_fn: fn(Arc<Mutex<Config>>, ClickEvent, Arc<Args>) -> bool,
 ) -> Option<Event> {
+    let now = clock.now();
+    let idle = idle_state.lock().unwrap().input(now);
+    let is_mouse_event = !matches!(
+        event.event_type,
+        EventType::KeyPress(_) | EventType::KeyRelease(_)
+    );
+    // the held buttons are still tracked in the excluded windows, like the keyboard state
+    let other_held_buttons = match event.event_type {
+        EventType::ButtonPress(btn) => held_buttons.lock().unwrap().press(btn),
+        EventType::ButtonRelease(btn) => {
+            held_buttons.lock().unwrap().release(btn);
+            vec![]
+        }
+        _ => vec![],
+    };
+    // the keyboard state is still tracked, to stay right after leaving the excluded window
+    if is_mouse_event {
+        // xdotool is only run on the presses and the wheel, without the config lock :
+        // the moves are re-emitted by this callback, they must not wait for it.
+        // The other events use the cached class, the classes aren't cloned for them.
+        let refresh = matches!(
+            event.event_type,
+            EventType::ButtonPress(_) | EventType::Wheel { .. }
+        );
+        let excluded = {
+            let config = config.lock().unwrap();
+            if config.exclude_window_classes.is_empty() {
+                false
+            } else if refresh {
+                let exclude_window_classes = config.exclude_window_classes.clone();
+                drop(config);
+                focus::is_focused_window_excluded(&exclude_window_classes, now, true)
+            } else {
+                focus::is_focused_window_excluded(&config.exclude_window_classes, now, false)
+            }
+        };
+        if excluded {
+            return Some(event);
+        }
+    }
+    if matches!(
+        event.event_type,
+        EventType::ButtonPress(_) | EventType::Wheel { .. }
+    ) {
+        modifier_resync
+            .lock()
+            .unwrap()
+            .resync(&mut keyboard_state.lock().unwrap(), now);
+    }
+    if let Some(trigger) = abort_trigger(&event.event_type) {
+        if abort_shape(&config, &button_state, &point_history, trigger) {
+            return None;
+        }
+    }
     match event.event_type {
         EventType::MouseMove { x, y } => {
             if args.no_listen {
-                last_point.lock().unwrap().set(x as i32, y as i32);
+                last_point.set(x as i32, y as i32);
+            }
+            let (shape_button, sample_interval) = {
+                let config = config.lock().unwrap();
+                (
+                    config.shape_button,
+                    Duration::from_millis(config.sample_interval_ms),
+                )
+            };
+            if shape_button == MouseButton::None {
+                // no shape recording : skip the button state and the history
+                return Some(event);
             }
             if let ButtonState::Pressed(pressed_btn) = *button_state.lock().unwrap() {
-                if config.lock().unwrap().shape_button.to_rdev_event() == pressed_btn {
-                    let mut histo = point_history.lock().unwrap();
-                    if !histo.is_full() {
-                        histo.push(*last_point.lock().unwrap());
-                    } else {
-                        trace!("point_history is full !")
-                    }
+                if shape_button.is_rdev_button(pressed_btn)
+                    && sampler.lock().unwrap().sample(now, sample_interval)
+                {
+                    record_point(&mut point_history.lock().unwrap(), last_point.get());
                 }
             }
             Some(event)
         }
         EventType::ButtonPress(pressed_btn) => {
             *button_state.lock().unwrap() = ButtonState::Pressed(pressed_btn);
-            let last_point_clone = *last_point.lock().unwrap();
+            idle_state.lock().unwrap().press_idle = Some(idle);
+            let last_point_clone = last_point.get();
+            let is_shape_button = config
+                .lock()
+                .unwrap()
+                .shape_button
+                .is_rdev_button(pressed_btn);
+            let shape_button_held = if is_shape_button {
+                chord_state.lock().unwrap().press_shape_button();
+                *shape_press_time.lock().unwrap() = Some(now);
+                held_buttons.lock().unwrap().at_shape_press = other_held_buttons.clone();
+                false
+            } else {
+                chord_state.lock().unwrap().other_event()
+            };
 
             let click_event = ClickEvent {
                 button: MouseButton::from_rdev_event(pressed_btn),
-                edges: Edge::edges_from_pos(last_point_clone.x, last_point_clone.y),
+                edges: get_edges(&config, last_point_clone, now),
                 modifiers: KeyboardModifier::from_keyboard_state(*keyboard_state.lock().unwrap()),
                 event_type: event::EventType::Press,
                 shapes_angles: vec![],
                 shapes_xy: vec![],
+                shapes_svg: vec![],
+                shapes_directions: vec![],
+                shape_ref: None,
+                shape_button_held,
+                buttons: vec![],
+                edges_forbidden: vec![],
+                region: None,
+                quadrants: vec![],
+                device: event.device.clone(),
+                held_buttons: if is_shape_button {
+                    other_held_buttons
+                } else {
+                    vec![]
+                },
+                keys: held_keys.lock().unwrap().keys(),
+                closed: None,
+                time: Some(now),
+                idle: Some(idle),
+                length: None,
             };
-            if config.lock().unwrap().shape_button.to_rdev_event() == pressed_btn {
+            if is_shape_button {
+                sampler.lock().unwrap().sample(now, Duration::ZERO);
                 let mut histo = point_history.lock().unwrap();
-                if !histo.is_full() {
-                    histo.push(last_point_clone);
-                } else {
-                    trace!("point_history is full !");
+                let capacity = config
+                    .lock()
+                    .unwrap()
+                    .shape_capacity(&keyboard_state.lock().unwrap());
+                if histo.is_empty() && histo.capacity() != capacity {
+                    debug!("record up to {capacity} points for this gesture");
+                    *histo = PointHistory::with_capacity(capacity);
                 }
-                if histo.len() < 10 {
+                record_point(&mut histo, last_point_clone);
+                if !is_shape(&histo, config.lock().unwrap().shape_min_points) {
                     process_event_fn(config, click_event, args);
                 }
                 return None;
@@ -111,21 +503,92 @@ pub fn grab_event_fn(
             }
         }
         EventType::ButtonRelease(btn) => {
-            let angles = points_to_angles::points_to_angles(&point_history.lock().unwrap());
+            let is_shape_button = config.lock().unwrap().shape_button.is_rdev_button(btn);
+            let held_at_shape_press = if is_shape_button {
+                std::mem::take(&mut held_buttons.lock().unwrap().at_shape_press)
+            } else {
+                vec![]
+            };
+            let shape_button_held = if is_shape_button {
+                if chord_state.lock().unwrap().release_shape_button() {
+                    debug!("chord done during the shape_button hold → consume its release");
+                    point_history.lock().unwrap().clear();
+                    *button_state.lock().unwrap() = ButtonState::None;
+                    return None;
+                }
+                let press_time = shape_press_time.lock().unwrap().take();
+                let max_gesture_ms = config.lock().unwrap().max_gesture_ms;
+                if is_gesture_too_long(press_time, now, max_gesture_ms) {
+                    debug!("gesture longer than max_gesture_ms={max_gesture_ms:?} → drop it");
+                    point_history.lock().unwrap().clear();
+                    *button_state.lock().unwrap() = ButtonState::None;
+                    return None;
+                }
+                false
+            } else {
+                chord_state.lock().unwrap().other_event()
+            };
+            let (resample_spacing, trim_start, trim_end, click_max_move) = {
+                let config = config.lock().unwrap();
+                (
+                    config.resample_spacing,
+                    config.trim_start_points,
+                    config.trim_end_points,
+                    config.click_max_move,
+                )
+            };
+            // the press and release jitters are dropped from the shape
+            let points = trim_points(&point_history.lock().unwrap(), trim_start, trim_end);
+            // the unresampled points are kept in shapes_xy, for the trace and the record
+            let angles = match resample_spacing {
+                0 => points_to_angles::points_to_angles(&points),
+                spacing => points_to_angles::points_to_angles(&points_to_angles::resample_points(
+                    &points,
+                    spacing as f64,
+                )),
+            };
+            // a release without a significant move is a click : it has no shape
+            let (shapes_angles, shapes_xy) = if is_click_move(&points, click_max_move) {
+                debug!("no move larger than click_max_move={click_max_move}px → a click");
+                (vec![], vec![])
+            } else {
+                (vec![angles.clone()], vec![points.clone()])
+            };
 
             if log_enabled!(Trace) {
-                let normalized_points = normalize_points(&point_history.lock().unwrap(), false);
+                let normalized_points = normalize_points(&points, false);
                 trace!("normalized_points = {normalized_points:?}");
-                trace_svg::trace_svg(&point_history.lock().unwrap(), &angles);
+                trace!(
+                    "directions = {}",
+                    points_to_angles::directions_to_string(
+                        &points_to_angles::angles_to_directions(&angles)
+                    )
+                );
+                trace_svg::trace_svg(&points, &angles);
             }
-            let last_point_clone = *last_point.lock().unwrap();
+            let last_point_clone = last_point.get();
             let click_event = ClickEvent {
                 button: MouseButton::from_rdev_event(btn),
-                edges: Edge::edges_from_pos(last_point_clone.x, last_point_clone.y),
+                edges: get_edges(&config, last_point_clone, now),
                 modifiers: KeyboardModifier::from_keyboard_state(*keyboard_state.lock().unwrap()),
                 event_type: event::EventType::Release,
-                shapes_angles: vec![angles],
-                shapes_xy: vec![point_history.lock().unwrap().clone()],
+                shapes_angles,
+                shapes_xy,
+                shapes_svg: vec![],
+                shapes_directions: vec![],
+                shape_ref: None,
+                shape_button_held,
+                buttons: vec![],
+                edges_forbidden: vec![],
+                region: get_region(&config, &points, now),
+                quadrants: get_quadrants(&config, &points, now),
+                device: event.device.clone(),
+                held_buttons: held_at_shape_press,
+                keys: held_keys.lock().unwrap().keys(),
+                closed: is_closed(&points),
+                time: Some(now),
+                idle: idle_state.lock().unwrap().press_idle.take(),
+                length: is_shape_button.then(|| path_length(&points)),
             };
             point_history.lock().unwrap().clear();
             *button_state.lock().unwrap() = ButtonState::None;
@@ -136,16 +599,36 @@ pub fn grab_event_fn(
                 None
             }
         }
+        // the horizontal wheel is not bound
+        EventType::Wheel { delta_y: 0, .. } => Some(event),
         EventType::Wheel { delta_y, .. } => {
-            let last_point_clone = *last_point.lock().unwrap();
+            let last_point_clone = last_point.get();
+            let shape_button_held = chord_state.lock().unwrap().other_event();
             let click_event = ClickEvent {
-                button: MouseButton::from_rdev_wheel(delta_y),
-                edges: Edge::edges_from_pos(last_point_clone.x, last_point_clone.y),
+                button: MouseButton::from_rdev_wheel(delta_y, config.lock().unwrap().invert_wheel),
+                edges: get_edges(&config, last_point_clone, now),
                 modifiers: KeyboardModifier::from_keyboard_state(*keyboard_state.lock().unwrap()),
                 event_type: event::EventType::Release,
                 shapes_angles: vec![],
                 shapes_xy: vec![],
+                shapes_svg: vec![],
+                shapes_directions: vec![],
+                shape_ref: None,
+                shape_button_held,
+                buttons: vec![],
+                edges_forbidden: vec![],
+                region: None,
+                quadrants: vec![],
+                device: event.device.clone(),
+                held_buttons: vec![],
+                keys: held_keys.lock().unwrap().keys(),
+                closed: None,
+                time: Some(now),
+                idle: Some(idle),
+                length: None,
             };
+            // the scroll reaches the application if nothing matched or the binding is
+            // passthrough
             if process_event_fn(config, click_event, args) {
                 Some(event)
             } else {
@@ -153,36 +636,202 @@ pub fn grab_event_fn(
             }
         }
         EventType::KeyPress(key) => {
-            match key {
-                Key::ShiftLeft => keyboard_state.lock().unwrap().shift_left = true,
-                Key::ShiftRight => keyboard_state.lock().unwrap().shift_right = true,
-                Key::ControlLeft => keyboard_state.lock().unwrap().control_left = true,
-                Key::ControlRight => keyboard_state.lock().unwrap().control_right = true,
-                Key::MetaLeft => keyboard_state.lock().unwrap().meta_left = true,
-                Key::Alt => keyboard_state.lock().unwrap().alt = true,
-                Key::AltGr => keyboard_state.lock().unwrap().alt_gr = true,
-                _ => {}
+            if keyboard_state.lock().unwrap().set_key(key, true) {
+                modifier_resync.lock().unwrap().press(key, now);
+            } else {
+                held_keys.lock().unwrap().press(key);
             }
             Some(event)
         }
         EventType::KeyRelease(key) => {
-            match key {
-                Key::ShiftLeft => keyboard_state.lock().unwrap().shift_left = false,
-                Key::ShiftRight => keyboard_state.lock().unwrap().shift_right = false,
-                Key::ControlLeft => keyboard_state.lock().unwrap().control_left = false,
-                Key::ControlRight => keyboard_state.lock().unwrap().control_right = false,
-                Key::MetaLeft => keyboard_state.lock().unwrap().meta_left = false,
-                Key::Alt => keyboard_state.lock().unwrap().alt = false,
-                Key::AltGr => keyboard_state.lock().unwrap().alt_gr = false,
-                _ => {}
+            if keyboard_state.lock().unwrap().set_key(key, false) {
+                modifier_resync.lock().unwrap().release(key);
+            } else {
+                held_keys.lock().unwrap().release(key);
             }
             Some(event)
         }
     }
 }
 
+/// the bounds of the focused window if the edges are relative to it, None for the screen
+fn relative_window_bounds(config: &Arc<Mutex<Config>>, now: Instant) -> Option<Bounds> {
+    let relative_to = config.lock().unwrap().relative_to;
+    match relative_to {
+        RelativeTo::Screen => None,
+        RelativeTo::Window => focus::focused_window_bounds(now),
+    }
+}
+
+/// the center of the points : the mean of their coordinates
+pub fn centroid(points: &PointHistory) -> Option<Point> {
+    if points.is_empty() {
+        return None;
+    }
+    let len = points.len() as i64;
+    let (sum_x, sum_y) = points.iter().fold((0i64, 0i64), |(x, y), point| {
+        (x + point.x as i64, y + point.y as i64)
+    });
+    Some(Point {
+        x: (sum_x / len) as i32,
+        y: (sum_y / len) as i32,
+    })
+}
+
+/// the abort trigger of the event, if it's one
+fn abort_trigger(event_type: &EventType) -> Option<AbortTrigger> {
+    match event_type {
+        EventType::Wheel { .. } => Some(AbortTrigger::Wheel),
+        EventType::KeyPress(Key::Escape) => Some(AbortTrigger::Escape),
+        _ => None,
+    }
+}
+
+/// Abort the shape being drawn with the shape_button if the trigger is configured:
+/// its points are dropped and no more recorded until the release, processed like a click.
+fn abort_shape(
+    config: &Arc<Mutex<Config>>,
+    button_state: &Arc<Mutex<ButtonState>>,
+    point_history: &PointHistoryArcMutex,
+    trigger: AbortTrigger,
+) -> bool {
+    let (shape_button, abort) = {
+        let config = config.lock().unwrap();
+        (
+            config.shape_button,
+            config.abort_shape_on.contains(&trigger),
+        )
+    };
+    let mut button_state = button_state.lock().unwrap();
+    match *button_state {
+        ButtonState::Pressed(btn) if abort && shape_button.is_rdev_button(btn) => {
+            debug!("{trigger:?} during the shape → abort it");
+            *button_state = ButtonState::Aborted(btn);
+            point_history.lock().unwrap().clear();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// the region of the shape, for the location_sensitive bindings
+fn get_region(config: &Arc<Mutex<Config>>, points: &PointHistory, now: Instant) -> Option<Region> {
+    let center = centroid(points)?;
+    Region::from_pos(center.x, center.y, relative_window_bounds(config, now))
+}
+
+fn get_quadrants(
+    config: &Arc<Mutex<Config>>,
+    points: &PointHistory,
+    now: Instant,
+) -> Vec<Quadrant> {
+    if points.is_empty() {
+        return vec![];
+    }
+    Quadrant::overlapped_by(points, relative_window_bounds(config, now))
+}
+
+fn get_edges(config: &Arc<Mutex<Config>>, point: Point, now: Instant) -> Vec<Edge> {
+    if config.lock().unwrap().detect_edges {
+        let corner_size = config.lock().unwrap().corner_size;
+        let window = relative_window_bounds(config, now);
+        Edge::edges_from_pos(point.x, point.y, corner_size, window)
+    } else {
+        vec![]
+    }
+}
+
+/// a shape_button gesture held longer than max_gesture_ms is an accidental slow drag
+pub fn is_gesture_too_long(
+    press_time: Option<Instant>,
+    release_time: Instant,
+    max_gesture_ms: Option<u64>,
+) -> bool {
+    match (press_time, max_gesture_ms) {
+        (Some(press_time), Some(max_gesture_ms)) => {
+            release_time.saturating_duration_since(press_time)
+                > Duration::from_millis(max_gesture_ms)
+        }
+        _ => false,
+    }
+}
+
+/// Some(true) if the shape ends near its start point, relatively to the shape size,
+/// None if there is no shape
+pub fn is_closed(points: &PointHistory) -> Option<bool> {
+    let (first, last) = (points.first()?, points.last()?);
+    let width = points.iter().map(|p| p.x).max()? - points.iter().map(|p| p.x).min()?;
+    let height = points.iter().map(|p| p.y).max()? - points.iter().map(|p| p.y).min()?;
+    let size = width.max(height);
+    if size == 0 {
+        return None;
+    }
+    let distance =
+        (((last.x - first.x) as f64).powi(2) + ((last.y - first.y) as f64).powi(2)).sqrt();
+    Some(distance <= CLOSED_MAX_DISTANCE * size as f64)
+}
+
+/// the length in pixels of the path along the points
+pub fn path_length(points: &PointHistory) -> u32 {
+    points
+        .iter()
+        .zip(points.iter().skip(1))
+        .map(|(from, to)| {
+            (((to.x - from.x) as f64).powi(2) + ((to.y - from.y) as f64).powi(2)).sqrt()
+        })
+        .sum::<f64>()
+        .round() as u32
+}
+
+/// add a point to the shape : a full history is downsampled to half its capacity first,
+/// the elaborate shapes are kept whole with less points
+pub fn record_point(histo: &mut PointHistory, point: Point) {
+    if histo.is_full() {
+        if histo.capacity() < 4 {
+            trace!("point_history is full !");
+            return;
+        }
+        trace!("point_history is full → downsample it");
+        *histo = histo.downsample(histo.capacity() / 2);
+    }
+    histo.push(point);
+}
+
+/// the points without the first start and the last end ones, kept whole if they don't
+/// have more points than that
+pub fn trim_points(points: &PointHistory, start: usize, end: usize) -> PointHistory {
+    if start + end >= points.len() {
+        return points.clone();
+    }
+    PointHistory::from_points(points[start..points.len() - end].to_vec())
+}
+
+/// the points stay within max_move pixels of each other on both axes : the button was
+/// clicked, not dragged. Always false with a max_move of 0.
+pub fn is_click_move(points: &PointHistory, max_move: u32) -> bool {
+    if max_move == 0 {
+        return false;
+    }
+    let extent = |coordinates: Vec<i32>| {
+        let min = coordinates.iter().min().copied().unwrap_or_default();
+        let max = coordinates.iter().max().copied().unwrap_or_default();
+        (max as i64 - min as i64) as u64
+    };
+    extent(points.iter().map(|p| p.x).collect()) <= max_move as u64
+        && extent(points.iter().map(|p| p.y).collect()) <= max_move as u64
+}
+
+/// a shape_button gesture is a shape if enough points were recorded, otherwise it's a click
+pub fn is_shape(points: &PointHistory, shape_min_points: usize) -> bool {
+    points.len() >= shape_min_points
+}
+
+/// Scale the points to 0..1000 on their larger dimension (or around their average with use_avg),
+/// both axes by the same factor : the aspect ratio of the shape is preserved.
+/// The two centerings only translate the points : the angles, compared by the matching,
+/// are the same for both, the centering only changes the recorded shapes_xy.
 pub fn normalize_points(input_points: &PointHistory, use_avg: bool) -> PointHistory {
-    let mut out = PointHistory::new();
+    let mut out = PointHistory::with_capacity(input_points.capacity());
     if !input_points.is_empty() {
         let min_x = input_points.iter().map(|p| p.x).min().unwrap();
         let max_x = input_points.iter().map(|p| p.x).max().unwrap();
@@ -193,25 +842,29 @@ pub fn normalize_points(input_points: &PointHistory, use_avg: bool) -> PointHist
         let height = max_y - min_y;
         let size = width.max(height);
 
-        if width > 0 && height > 0 {
-            if use_avg {
-                let avg_x: i32 =
-                    input_points.iter().map(|p| p.x).sum::<i32>() / (input_points.len() as i32);
-                let avg_y: i32 =
-                    input_points.iter().map(|p| p.y).sum::<i32>() / (input_points.len() as i32);
-                for p in input_points.iter() {
-                    out.push(Point {
-                        x: 1000 * (p.x - avg_x) / size,
-                        y: 1000 * (p.y - avg_y) / size,
-                    });
-                }
-            } else {
-                for p in input_points.iter() {
-                    out.push(Point {
-                        x: 1000 * (p.x - min_x) / size,
-                        y: 1000 * (p.y - min_y) / size,
-                    });
-                }
+        // a straight line has a degenerate axis : only the other axis is scaled,
+        // the degenerate one is constant. A single point is kept at the origin.
+        if size == 0 {
+            input_points
+                .iter()
+                .for_each(|_| out.push(Point { x: 0, y: 0 }));
+        } else if use_avg {
+            let avg_x: i32 =
+                input_points.iter().map(|p| p.x).sum::<i32>() / (input_points.len() as i32);
+            let avg_y: i32 =
+                input_points.iter().map(|p| p.y).sum::<i32>() / (input_points.len() as i32);
+            for p in input_points.iter() {
+                out.push(Point {
+                    x: 1000 * (p.x - avg_x) / size,
+                    y: 1000 * (p.y - avg_y) / size,
+                });
+            }
+        } else {
+            for p in input_points.iter() {
+                out.push(Point {
+                    x: 1000 * (p.x - min_x) / size,
+                    y: 1000 * (p.y - min_y) / size,
+                });
             }
         }
     }
@@ -220,8 +873,42 @@ pub fn normalize_points(input_points: &PointHistory, use_avg: bool) -> PointHist
 
 #[cfg(test)]
 mod tests {
-    use crate::event::{Point, PointHistory};
-    use crate::grab::normalize_points;
+    use crate::event::{Point, PointHistory, HISTO_SIZE};
+    use std::f64::consts::PI;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
+
+    use clap::Parser;
+    use rdev::{Button, Event, EventType, Key};
+
+    use crate::args::Args;
+    use crate::builder::{BindingBuilder, ConfigBuilder};
+    use crate::clock::{Clock, MockClock};
+    use crate::config::Config;
+    use crate::event::{AbortTrigger, ButtonState, ClickEvent, KeyboardState, MouseButton};
+    use crate::grab::{
+        abort_shape, abort_trigger, catch_event_panic, centroid, grab_event_fn, is_click_move,
+        is_closed, is_gesture_too_long, is_shape, normalize_points, panic_message, path_length,
+        record_point, trim_points, ChordState, GrabContext, HeldButtons, HeldKeys, IdleState,
+        ModifierResync, Sampler,
+    };
+    use crate::points_to_angles;
+    use crate::process_event::process_event_with_runner;
+    use crate::runner::{CommandRunner, RecordingRunner};
+    use crate::test_shapes::{shape_points, Shape};
+
+    thread_local! {
+        static GRAB_RUNNER: Arc<RecordingRunner> = Arc::new(RecordingRunner::default());
+    }
+
+    fn recorded_process_event(
+        config: Arc<Mutex<Config>>,
+        event: ClickEvent,
+        args: Arc<Args>,
+    ) -> bool {
+        let runner: Arc<dyn CommandRunner> = GRAB_RUNNER.with(|runner| runner.clone());
+        process_event_with_runner(config, event, args, &runner)
+    }
 
     #[test]
     fn test_normalize_points() {
@@ -231,9 +918,496 @@ mod tests {
         points.push(Point { x: 5, y: 4 });
         points.push(Point { x: 5, y: 2 });
         let norm = normalize_points(&points, false);
-        assert_eq!(norm.get(0).unwrap(), &Point { x: 0, y: 0 });
+        assert_eq!(norm.first().unwrap(), &Point { x: 0, y: 0 });
         assert_eq!(norm.get(1).unwrap(), &Point { x: 1000, y: 0 });
         assert_eq!(norm.get(2).unwrap(), &Point { x: 500, y: 400 });
         assert_eq!(norm.get(3).unwrap(), &Point { x: 500, y: 200 });
     }
+
+    #[test]
+    fn test_normalize_points_degenerate() {
+        let mut vertical = PointHistory::new();
+        (0..5).for_each(|i| vertical.push(Point { x: 7, y: i * 10 }));
+        let norm = normalize_points(&vertical, false);
+        assert_eq!(norm.len(), 5);
+        assert_eq!(norm.first().unwrap(), &Point { x: 0, y: 0 });
+        assert_eq!(norm.last().unwrap(), &Point { x: 0, y: 1000 });
+
+        let mut horizontal = PointHistory::new();
+        (0..5).for_each(|i| {
+            horizontal.push(Point {
+                x: 30 - i * 5,
+                y: 4,
+            })
+        });
+        let norm = normalize_points(&horizontal, false);
+        assert_eq!(norm.first().unwrap(), &Point { x: 1000, y: 0 });
+        assert_eq!(norm.get(2).unwrap(), &Point { x: 500, y: 0 });
+        let norm = normalize_points(&horizontal, true);
+        assert_eq!(norm.first().unwrap(), &Point { x: 500, y: 0 });
+
+        let mut single = PointHistory::new();
+        single.push(Point { x: 3, y: 3 });
+        assert_eq!(normalize_points(&single, false).len(), 1);
+    }
+
+    #[test]
+    fn test_is_shape() {
+        let mut points = PointHistory::new();
+        for i in 0..3 {
+            points.push(Point { x: i, y: i });
+        }
+        assert!(!is_shape(&points, 10));
+
+        for i in 3..20 {
+            points.push(Point { x: i, y: i });
+        }
+        assert!(is_shape(&points, 10));
+        assert!(!is_shape(&points, 30));
+    }
+
+    #[test]
+    fn test_idle_state() {
+        let clock = MockClock::new();
+        let mut idle_state = IdleState::new(clock.now());
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(idle_state.input(clock.now()), Duration::from_secs(60));
+        clock.advance(Duration::from_millis(10));
+        assert_eq!(idle_state.input(clock.now()), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_chord_state() {
+        let mut chord_state = ChordState::default();
+        assert!(!chord_state.other_event());
+
+        chord_state.press_shape_button();
+        assert!(!chord_state.release_shape_button());
+
+        chord_state.press_shape_button();
+        assert!(chord_state.other_event());
+        assert!(chord_state.other_event());
+        assert!(chord_state.release_shape_button());
+        assert!(!chord_state.other_event());
+    }
+
+    #[test]
+    fn test_sampler() {
+        let clock = MockClock::new();
+        let mut sampler = Sampler::default();
+        let interval = Duration::from_millis(10);
+        assert!(sampler.sample(clock.now(), interval));
+        clock.advance(Duration::from_millis(1));
+        assert!(!sampler.sample(clock.now(), interval));
+        clock.advance(Duration::from_millis(9));
+        assert!(sampler.sample(clock.now(), interval));
+        assert!(sampler.sample(clock.now(), Duration::ZERO));
+    }
+
+    #[test]
+    fn test_is_gesture_too_long() {
+        let clock = MockClock::new();
+        let press = clock.now();
+        clock.advance(Duration::from_millis(3000));
+        assert!(!is_gesture_too_long(Some(press), clock.now(), Some(3000)));
+        assert!(!is_gesture_too_long(Some(press), clock.now(), None));
+        assert!(!is_gesture_too_long(None, clock.now(), Some(3000)));
+        clock.advance(Duration::from_millis(1));
+        assert!(is_gesture_too_long(Some(press), clock.now(), Some(3000)));
+    }
+
+    #[test]
+    fn test_centroid() {
+        let mut points = PointHistory::new();
+        assert_eq!(centroid(&points), None);
+        points.push(Point { x: 0, y: 0 });
+        points.push(Point { x: 100, y: 0 });
+        points.push(Point { x: 100, y: 50 });
+        points.push(Point { x: 0, y: 50 });
+        assert_eq!(centroid(&points), Some(Point { x: 50, y: 25 }));
+    }
+
+    #[test]
+    fn test_is_closed() {
+        let circle = |end_angle: f64| {
+            let mut points = PointHistory::new();
+            (0..=40).for_each(|i| {
+                let angle = end_angle * i as f64 / 40.0;
+                points.push(Point {
+                    x: (100.0 * angle.cos()) as i32,
+                    y: (100.0 * angle.sin()) as i32,
+                })
+            });
+            points
+        };
+        assert_eq!(is_closed(&circle(2.0 * PI)), Some(true));
+        assert_eq!(is_closed(&circle(1.9 * PI)), Some(true));
+        // a C
+        assert_eq!(is_closed(&circle(1.5 * PI)), Some(false));
+        assert_eq!(is_closed(&PointHistory::new()), None);
+    }
+
+    #[test]
+    fn test_modifier_resync() {
+        let clock = MockClock::new();
+        let mut keyboard_state = KeyboardState::default();
+        let mut resync = ModifierResync::default();
+        keyboard_state.set_key(Key::ControlLeft, true);
+        resync.press(Key::ControlLeft, clock.now());
+        keyboard_state.set_key(Key::ShiftLeft, true);
+        resync.press(Key::ShiftLeft, clock.now());
+
+        clock.advance(Duration::from_secs(4));
+        // the auto-repeat of the held Shift
+        resync.press(Key::ShiftLeft, clock.now());
+        assert!(resync.resync(&mut keyboard_state, clock.now()).is_empty());
+
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(
+            resync.resync(&mut keyboard_state, clock.now()),
+            vec![Key::ControlLeft]
+        );
+        assert!(!keyboard_state.control_left);
+        assert!(keyboard_state.shift_left);
+    }
+
+    #[test]
+    fn test_catch_event_panic() {
+        let event = rdev::Event {
+            time: std::time::SystemTime::UNIX_EPOCH,
+            name: None,
+            event_type: rdev::EventType::ButtonPress(rdev::Button::Left),
+            device: None,
+        };
+        assert_eq!(
+            catch_event_panic(event.time, event.event_type, || None),
+            Ok(None)
+        );
+
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| {}));
+        let state = std::sync::Mutex::new(0);
+        let result = catch_event_panic(event.time, event.event_type, || {
+            let _guard = state.lock().unwrap();
+            panic!("bad binding");
+        });
+        std::panic::set_hook(hook);
+        assert_eq!(result, Err(Some(event)));
+        assert!(state.is_poisoned());
+        state.clear_poison();
+        assert_eq!(*state.lock().unwrap(), 0);
+
+        let payload: Box<dyn std::any::Any + Send> = Box::new(String::from("owned"));
+        assert_eq!(panic_message(payload.as_ref()), "owned");
+        let payload: Box<dyn std::any::Any + Send> = Box::new(1);
+        assert_eq!(panic_message(payload.as_ref()), "unknown panic");
+    }
+
+    #[test]
+    fn test_abort_shape() {
+        assert_eq!(
+            abort_trigger(&EventType::Wheel {
+                delta_x: 1,
+                delta_y: 0
+            }),
+            Some(AbortTrigger::Wheel)
+        );
+        assert_eq!(
+            abort_trigger(&EventType::KeyPress(Key::Escape)),
+            Some(AbortTrigger::Escape)
+        );
+        assert_eq!(abort_trigger(&EventType::KeyRelease(Key::Escape)), None);
+
+        let config = Arc::new(Mutex::new(
+            ConfigBuilder::new(MouseButton::Right)
+                .abort_shape_on(vec![AbortTrigger::Wheel])
+                .build(),
+        ));
+        let point_history = Arc::new(Mutex::new(PointHistory::new()));
+        point_history.lock().unwrap().push(Point { x: 1, y: 1 });
+        let button_state = Arc::new(Mutex::new(ButtonState::Pressed(Button::Left)));
+        // not the shape_button
+        assert!(!abort_shape(
+            &config,
+            &button_state,
+            &point_history,
+            AbortTrigger::Wheel
+        ));
+
+        *button_state.lock().unwrap() = ButtonState::Pressed(Button::Right);
+        // not configured
+        assert!(!abort_shape(
+            &config,
+            &button_state,
+            &point_history,
+            AbortTrigger::Escape
+        ));
+        assert_eq!(point_history.lock().unwrap().len(), 1);
+
+        assert!(abort_shape(
+            &config,
+            &button_state,
+            &point_history,
+            AbortTrigger::Wheel
+        ));
+        assert!(point_history.lock().unwrap().is_empty());
+        assert!(matches!(
+            *button_state.lock().unwrap(),
+            ButtonState::Aborted(Button::Right)
+        ));
+        // already aborted
+        assert!(!abort_shape(
+            &config,
+            &button_state,
+            &point_history,
+            AbortTrigger::Wheel
+        ));
+    }
+
+    #[test]
+    fn test_normalize_points_preserves_aspect() {
+        let rectangle = PointHistory::from_points(vec![
+            Point { x: 0, y: 0 },
+            Point { x: 200, y: 0 },
+            Point { x: 200, y: 50 },
+            Point { x: 0, y: 50 },
+        ]);
+        let norm = normalize_points(&rectangle, false);
+        assert_eq!(norm.get(2).unwrap(), &Point { x: 1000, y: 250 });
+
+        let square = PointHistory::from_points(vec![
+            Point { x: 0, y: 0 },
+            Point { x: 50, y: 0 },
+            Point { x: 50, y: 50 },
+            Point { x: 0, y: 50 },
+        ]);
+        let norm_square = normalize_points(&square, false);
+        assert_eq!(norm_square.get(2).unwrap(), &Point { x: 1000, y: 1000 });
+        assert_ne!(norm_square, norm);
+
+        // a scaled rectangle keeps the normalized coordinates of the rectangle
+        let scaled = PointHistory::from_points(
+            rectangle
+                .iter()
+                .map(|p| Point {
+                    x: 10 + 3 * p.x,
+                    y: 20 + 3 * p.y,
+                })
+                .collect(),
+        );
+        assert_eq!(normalize_points(&scaled, false), norm);
+
+        // around the average point too, both axes are divided by the larger side
+        let norm_avg = normalize_points(&rectangle, true);
+        assert_eq!(norm_avg.first().unwrap(), &Point { x: -500, y: -125 });
+        assert_eq!(norm_avg.get(2).unwrap(), &Point { x: 500, y: 125 });
+    }
+
+    #[test]
+    fn test_held_buttons() {
+        let mut held = HeldButtons::default();
+        assert_eq!(held.press(Button::Back), vec![]);
+        assert_eq!(held.press(Button::Back), vec![]);
+        assert_eq!(held.press(Button::Right), vec![MouseButton::Back]);
+        held.release(Button::Back);
+        held.release(Button::Right);
+        assert_eq!(held.press(Button::Right), vec![]);
+    }
+
+    #[test]
+    fn test_path_length() {
+        assert_eq!(path_length(&PointHistory::new()), 0);
+        let mut points = PointHistory::new();
+        points.push(Point { x: 0, y: 0 });
+        assert_eq!(path_length(&points), 0);
+        points.push(Point { x: 30, y: 40 });
+        points.push(Point { x: 30, y: 0 });
+        assert_eq!(path_length(&points), 90);
+    }
+
+    #[test]
+    fn test_held_keys() {
+        let mut held = HeldKeys::default();
+        held.press(Key::KeyG);
+        held.press(Key::KeyG);
+        held.press(Key::KeyH);
+        assert_eq!(held.keys(), vec![Key::KeyG, Key::KeyH]);
+        held.release(Key::KeyG);
+        assert_eq!(held.keys(), vec![Key::KeyH]);
+        held.release(Key::KeyG);
+        assert_eq!(held.keys(), vec![Key::KeyH]);
+    }
+
+    #[test]
+    fn test_normalize_centering_keeps_the_angles() {
+        // an outlier at the start of the drag moves the centroid but not the angles
+        let mut points = PointHistory::new();
+        points.push(Point { x: -300, y: 40 });
+        (0..20).for_each(|i| points.push(Point { x: i * 10, y: 0 }));
+        (0..20).for_each(|i| points.push(Point { x: 200, y: i * 10 }));
+        let bbox = normalize_points(&points, false);
+        let centroid = normalize_points(&points, true);
+        assert_ne!(bbox, centroid);
+        assert_eq!(
+            points_to_angles::points_to_angles(&bbox),
+            points_to_angles::points_to_angles(&centroid)
+        );
+    }
+
+    #[test]
+    fn test_trim_points() {
+        let points = PointHistory::from_points((0..10).map(|x| Point { x, y: 0 }).collect());
+        let trimmed = trim_points(&points, 1, 2);
+        assert_eq!(trimmed.len(), 7);
+        assert_eq!(trimmed.first().unwrap(), &Point { x: 1, y: 0 });
+        assert_eq!(trimmed.last().unwrap(), &Point { x: 7, y: 0 });
+        assert_eq!(trim_points(&points, 0, 0), points);
+        assert_eq!(trim_points(&points, 5, 5), points);
+
+        // the release jitter adds an angle, trimmed away
+        let mut jittered = points.clone();
+        jittered.push(Point { x: 9, y: 5 });
+        assert_ne!(
+            points_to_angles::points_to_angles(&jittered),
+            points_to_angles::points_to_angles(&points)
+        );
+        assert_eq!(
+            points_to_angles::points_to_angles(&trim_points(&jittered, 0, 1)),
+            points_to_angles::points_to_angles(&points)
+        );
+    }
+
+    #[test]
+    fn test_record_point_overflow() {
+        let mut histo = PointHistory::with_capacity(100);
+        (0..150).for_each(|x| record_point(&mut histo, Point { x, y: 0 }));
+        // downsampled to 50 points at the 101st point, then the 50 next ones
+        assert_eq!(histo.len(), 100);
+        assert_eq!(histo.first().unwrap(), &Point { x: 0, y: 0 });
+        assert_eq!(histo.last().unwrap(), &Point { x: 149, y: 0 });
+
+        let mut tiny = PointHistory::with_capacity(2);
+        (0..5).for_each(|x| record_point(&mut tiny, Point { x, y: 0 }));
+        assert_eq!(tiny.len(), 2);
+    }
+
+    #[test]
+    fn test_is_click_move() {
+        let points = |coordinates: &[(i32, i32)]| {
+            PointHistory::from_points(coordinates.iter().map(|&(x, y)| Point { x, y }).collect())
+        };
+        let jitter = points(&[(100, 100), (101, 102), (99, 101), (100, 103)]);
+        assert!(is_click_move(&jitter, 3));
+        assert!(!is_click_move(&jitter, 2));
+        assert!(!is_click_move(&jitter, 0));
+        assert!(!is_click_move(&points(&[(100, 100), (100, 150)]), 3));
+        assert!(is_click_move(&points(&[(100, 100)]), 3));
+        assert!(is_click_move(&PointHistory::new(), 3));
+    }
+
+    #[test]
+    fn test_reset_state() {
+        let _repeats = crate::repeat::REPEATS_TEST_LOCK.lock().unwrap();
+        let config = Arc::new(Mutex::new(Config::default()));
+        let args = Arc::new(Args::parse_from(["mouse-actions"]));
+        let context = GrabContext::new(config, args, Arc::new(MockClock::new()));
+        context
+            .point_history
+            .lock()
+            .unwrap()
+            .push(Point { x: 1, y: 2 });
+        *context.button_state.lock().unwrap() = ButtonState::Pressed(Button::Right);
+        context
+            .keyboard_state
+            .lock()
+            .unwrap()
+            .set_key(Key::ShiftLeft, true);
+        context.chord_state.lock().unwrap().press_shape_button();
+        context.held_buttons.lock().unwrap().press(Button::Left);
+        context.held_keys.lock().unwrap().press(Key::KeyG);
+
+        context.reset_state();
+        assert!(context.point_history.lock().unwrap().is_empty());
+        assert!(matches!(
+            *context.button_state.lock().unwrap(),
+            ButtonState::None
+        ));
+        assert!(!context.keyboard_state.lock().unwrap().shift_left);
+        assert!(!context.chord_state.lock().unwrap().held);
+        assert!(context
+            .held_buttons
+            .lock()
+            .unwrap()
+            .press(Button::Right)
+            .is_empty());
+        assert!(context.held_keys.lock().unwrap().keys().is_empty());
+    }
+
+    #[test]
+    fn test_borderline_shape_click() {
+        let binding = BindingBuilder::new(MouseButton::Right)
+            .comment("L")
+            .shape(shape_points(Shape::L, 200, 40))
+            .diff_max(2.0)
+            .cmd_str("true")
+            .build();
+        let config = Arc::new(Mutex::new(
+            ConfigBuilder::new(MouseButton::Right)
+                .consume_threshold(90.0)
+                .binding(binding)
+                .build(),
+        ));
+        let args = Arc::new(Args::parse_from(["mouse-actions", "--no-listen"]));
+        let clock = MockClock::new();
+        let context = GrabContext::new(config, args, Arc::new(clock.clone()));
+        let grab = |event_type: EventType| {
+            clock.advance(Duration::from_millis(20));
+            let event = Event {
+                time: SystemTime::now(),
+                name: None,
+                event_type,
+                device: None,
+            };
+            grab_event_fn(event, context.clone(), recorded_process_event)
+        };
+        // the press and the release of a shape drawn with the shape button
+        let draw = |shape: Shape| {
+            let points = shape_points(shape, 200, 40);
+            grab(EventType::MouseMove {
+                x: points[0].x as f64,
+                y: points[0].y as f64,
+            });
+            let press = grab(EventType::ButtonPress(Button::Right));
+            for point in points.iter() {
+                grab(EventType::MouseMove {
+                    x: point.x as f64,
+                    y: point.y as f64,
+                });
+            }
+            (press, grab(EventType::ButtonRelease(Button::Right)))
+        };
+        let runs = || GRAB_RUNNER.with(|runner| (runner.runs().len(), runner.replays().len()));
+
+        let (press, release) = draw(Shape::L);
+        assert!(press.is_none() && release.is_none());
+        assert_eq!(runs(), (1, 0));
+        // a borderline match doesn't run the binding and doesn't pass the release alone :
+        // the whole click is replayed
+        let (press, release) = draw(Shape::C);
+        assert!(press.is_none() && release.is_none());
+        assert_eq!(runs(), (1, 1));
+    }
+
+    #[test]
+    fn test_normalize_long_gesture() {
+        let points = PointHistory::from_points(
+            (0..3 * HISTO_SIZE as i32)
+                .map(|x| Point { x, y: x % 7 })
+                .collect(),
+        );
+        let norm = normalize_points(&points, false);
+        assert_eq!(norm.len(), 3 * HISTO_SIZE);
+        assert_eq!(norm.capacity(), points.capacity());
+        assert_eq!(norm.last().unwrap(), &Point { x: 1000, y: 1 });
+    }
 }
@@ -0,0 +1,112 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+
+use crate::args::Args;
+use crate::binding::Binding;
+use crate::config::Config;
+use crate::event::ClickEvent;
+use crate::process_event::process_event_with_runner;
+use crate::runner::{CommandRunner, PROCESS_RUNNER};
+
+lazy_static! {
+    static ref LATENCY_STATS: Mutex<LatencyStats> = Mutex::new(LatencyStats::default());
+    // the grab instant of the event being processed
+    static ref EVENT_TIME: Mutex<Option<Instant>> = Mutex::new(None);
+    static ref LATENCY_RUNNER: Arc<dyn CommandRunner> = Arc::new(LatencyRunner);
+}
+
+/// the latencies measured during the session
+#[derive(Debug, Default)]
+pub struct LatencyStats {
+    samples: Vec<Duration>,
+}
+
+impl LatencyStats {
+    pub fn record(&mut self, latency: Duration) {
+        self.samples.push(latency);
+    }
+
+    /// the nearest-rank percentile of the latencies, None without latency
+    pub fn percentile(&self, percent: f64) -> Option<Duration> {
+        let mut samples = self.samples.clone();
+        samples.sort();
+        let rank = ((percent / 100.0) * samples.len() as f64).ceil() as usize;
+        samples
+            .get(rank.clamp(1, samples.len().max(1)) - 1)
+            .copied()
+    }
+
+    pub fn summary(&self) -> String {
+        let format = |percent: f64| {
+            self.percentile(percent)
+                .map(|latency| format!("{latency:.2?}"))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        format!(
+            "n={} p50={} p90={} p99={} max={}",
+            self.samples.len(),
+            format(50.0),
+            format(90.0),
+            format(99.0),
+            format(100.0)
+        )
+    }
+}
+
+/// Spawn the commands like the daemon, and measure the time from the grab of
+/// the event to the command handed to the spawning thread.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LatencyRunner;
+
+impl CommandRunner for LatencyRunner {
+    fn run(&self, binding: &Binding, cmd: Vec<String>) {
+        if let Some(event_time) = EVENT_TIME.lock().unwrap().take() {
+            let latency = event_time.elapsed();
+            let mut stats = LATENCY_STATS.lock().unwrap();
+            stats.record(latency);
+            println!(
+                "{} : {latency:.2?}    {}",
+                binding.log_fields(),
+                stats.summary()
+            );
+        }
+        PROCESS_RUNNER.run(binding, cmd);
+    }
+}
+
+/// process the event like the daemon, the command latencies are printed
+pub fn latency_event(config: Arc<Mutex<Config>>, event: ClickEvent, args: Arc<Args>) -> bool {
+    *EVENT_TIME.lock().unwrap() = event.time;
+    let propagate = process_event_with_runner(config, event, args, &LATENCY_RUNNER);
+    // the events without command are not measured
+    EVENT_TIME.lock().unwrap().take();
+    propagate
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::latency::LatencyStats;
+
+    #[test]
+    fn test_latency_stats() {
+        let mut stats = LatencyStats::default();
+        assert_eq!(stats.percentile(50.0), None);
+        assert_eq!(stats.summary(), "n=0 p50=- p90=- p99=- max=-");
+
+        (1..=10)
+            .rev()
+            .for_each(|ms| stats.record(Duration::from_millis(ms)));
+        assert_eq!(stats.percentile(50.0), Some(Duration::from_millis(5)));
+        assert_eq!(stats.percentile(90.0), Some(Duration::from_millis(9)));
+        assert_eq!(stats.percentile(99.0), Some(Duration::from_millis(10)));
+        assert_eq!(stats.percentile(0.0), Some(Duration::from_millis(1)));
+        assert_eq!(
+            stats.summary(),
+            "n=10 p50=5.00ms p90=9.00ms p99=10.00ms max=10.00ms"
+        );
+    }
+}
@@ -1,14 +1,34 @@
 pub mod args;
 pub mod binding;
+pub mod builder;
+pub mod clock;
 pub mod cmd_str_spliter;
 pub mod compare_angles;
 pub mod config;
+pub mod direction_codes;
+pub mod edges_check;
 pub mod event;
+pub mod focus;
 pub mod grab;
+pub mod latency;
+pub mod limits;
 pub mod listen;
+pub mod log_file;
+pub mod monitor;
+pub mod notify;
 pub mod points_to_angles;
 pub mod process_args;
 pub mod process_event;
 pub mod record;
+pub mod repeat;
+pub mod reset;
+pub mod runner;
+pub mod schedule;
+pub mod schema;
 pub mod single_instance;
+pub mod sound;
+pub mod spawned;
+pub mod svg_path;
+#[cfg(test)]
+pub mod test_shapes;
 pub mod trace_svg;
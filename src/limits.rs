@@ -0,0 +1,157 @@
+use std::process::Command;
+
+use anyhow::{anyhow, bail};
+use serde::{Deserialize, Serialize};
+
+/// The umask and the resource limits of the command of a binding, set in the
+/// child process before the exec.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandLimits {
+    // the file mode creation mask, in octal, e.g. "077"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub umask: Option<String>,
+
+    // the maximum size of the virtual memory (RLIMIT_AS)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u64>,
+
+    // the maximum number of open file descriptors (RLIMIT_NOFILE)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_open_files: Option<u64>,
+
+    // disable the core dumps (RLIMIT_CORE = 0)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_core_dump: bool,
+}
+
+/// parse an octal umask, e.g. "077" or "0o022"
+pub fn parse_umask(umask: &str) -> anyhow::Result<u32> {
+    let digits = umask.trim().trim_start_matches("0o");
+    let mask = u32::from_str_radix(digits, 8)
+        .map_err(|_| anyhow!("invalid umask {umask:?}, expected an octal number like \"077\""))?;
+    if mask > 0o777 {
+        bail!("invalid umask {umask:?}, it must be at most 777");
+    }
+    Ok(mask)
+}
+
+impl CommandLimits {
+    /// check the limits when the config is loaded, the command can't fail on them later
+    pub fn check(&self) -> anyhow::Result<()> {
+        if let Some(umask) = &self.umask {
+            parse_umask(umask)?;
+        }
+        Ok(())
+    }
+
+    /// the soft and hard limits to set, the values are checked by `check`
+    fn rlimits(&self) -> Vec<(rustix::process::Resource, u64)> {
+        use rustix::process::Resource;
+        let mut rlimits = vec![];
+        if let Some(max_memory_mb) = self.max_memory_mb {
+            rlimits.push((Resource::As, max_memory_mb.saturating_mul(1024 * 1024)));
+        }
+        if let Some(max_open_files) = self.max_open_files {
+            rlimits.push((Resource::Nofile, max_open_files));
+        }
+        if self.no_core_dump {
+            rlimits.push((Resource::Core, 0));
+        }
+        rlimits
+    }
+
+    /// set the limits in the child process, between the fork and the exec
+    #[cfg(target_os = "linux")]
+    pub fn apply(&self, command: &mut Command) {
+        use std::io;
+        use std::os::unix::process::CommandExt;
+
+        use rustix::fs::Mode;
+        use rustix::process::{setrlimit, umask, Rlimit};
+
+        let mask = self
+            .umask
+            .as_deref()
+            .and_then(|mask| parse_umask(mask).ok());
+        // computed before the fork : the closure must not allocate
+        let rlimits = self.rlimits();
+        // SAFETY: umask and setrlimit are async-signal-safe syscalls
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(mask) = mask {
+                    umask(Mode::from_bits_truncate(mask));
+                }
+                for (resource, limit) in rlimits.iter() {
+                    setrlimit(
+                        *resource,
+                        Rlimit {
+                            current: Some(*limit),
+                            maximum: Some(*limit),
+                        },
+                    )
+                    .map_err(io::Error::from)?;
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn apply(&self, _command: &mut Command) {
+        log::warn!("the command limits are only supported on Linux, ignored");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use rustix::process::Resource;
+
+    use crate::limits::{parse_umask, CommandLimits};
+
+    #[test]
+    fn test_parse_umask() {
+        assert_eq!(parse_umask("077").unwrap(), 0o077);
+        assert_eq!(parse_umask("0o022").unwrap(), 0o022);
+        assert_eq!(parse_umask("7").unwrap(), 0o007);
+        assert!(parse_umask("088").is_err());
+        assert!(parse_umask("1777").is_err());
+        assert!(parse_umask("").is_err());
+    }
+
+    #[test]
+    fn test_rlimits() {
+        let limits = CommandLimits {
+            umask: Some("077".to_string()),
+            max_memory_mb: Some(512),
+            max_open_files: None,
+            no_core_dump: true,
+        };
+        assert!(limits.check().is_ok());
+        assert_eq!(
+            limits.rlimits(),
+            vec![(Resource::As, 512 * 1024 * 1024), (Resource::Core, 0)]
+        );
+        assert!(CommandLimits::default().rlimits().is_empty());
+        let invalid = CommandLimits {
+            umask: Some("abc".to_string()),
+            ..CommandLimits::default()
+        };
+        assert!(invalid.check().is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let limits = CommandLimits {
+            umask: Some("027".to_string()),
+            no_core_dump: true,
+            ..CommandLimits::default()
+        };
+        let mut command = Command::new("sh");
+        command.args(["-c", "umask; ulimit -c"]);
+        limits.apply(&mut command);
+        let output = command.output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "0027\n0\n");
+    }
+}
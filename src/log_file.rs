@@ -0,0 +1,108 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// the log file is rotated when it reaches LOG_FILE_MAX_SIZE bytes
+pub const LOG_FILE_MAX_SIZE: u64 = 10 * 1024 * 1024;
+// the number of rotated log files kept : <log file>.1 (the newest) … <log file>.<keep>
+pub const LOG_FILE_KEEP: usize = 3;
+
+/// A log file that is rotated by size : the full file is renamed to `<log file>.1`,
+/// the previous rotated files are shifted and the oldest is deleted.
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    /// open the log file in append mode, it's created if it doesn't exist
+    pub fn open(path: &Path, max_size: u64, keep: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_size,
+            keep,
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        PathBuf::from(path)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated_path(self.keep));
+            for index in (1..self.keep).rev() {
+                if self.rotated_path(index).exists() {
+                    fs::rename(self.rotated_path(index), self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Write;
+
+    use crate::log_file::RotatingFile;
+
+    #[test]
+    fn test_rotating_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mouse-actions.log");
+        let rotated = |index: usize| dir.path().join(format!("mouse-actions.log.{index}"));
+
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        file.write_all(b"line 1\n").unwrap();
+        file.write_all(b"line 2\n").unwrap();
+        file.write_all(b"line 3\n").unwrap();
+        file.write_all(b"line 4\n").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line 4\n");
+        assert_eq!(fs::read_to_string(rotated(1)).unwrap(), "line 3\n");
+        assert_eq!(fs::read_to_string(rotated(2)).unwrap(), "line 2\n");
+        assert!(!rotated(3).exists());
+
+        // the size of the existing file is counted on open
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        file.write_all(b"line 5\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line 5\n");
+        assert_eq!(fs::read_to_string(rotated(1)).unwrap(), "line 4\n");
+    }
+}
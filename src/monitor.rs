@@ -0,0 +1,136 @@
+use std::sync::Arc;
+use std::{thread, time};
+
+use log::debug;
+use rdev::{grab, Event, EventType, GrabError};
+
+use crate::args::Args;
+use crate::event::{AtomicPoint, KeyboardState, MouseButton, Point};
+use crate::listen;
+
+/// the line of a raw event, with the config names of its button or key, e.g.
+/// "button press Thumb2 (rdev Unknown(9)) device=\"Logitech M720\""
+pub fn event_line(event: &Event, listen_point: Option<Point>) -> String {
+    let mut line = match event.event_type {
+        EventType::KeyPress(key) => format!("key press {key:?}{}", modifier_suffix(key)),
+        EventType::KeyRelease(key) => format!("key release {key:?}{}", modifier_suffix(key)),
+        EventType::ButtonPress(button) => format!(
+            "button press {} (rdev {button:?})",
+            MouseButton::from_rdev_event(button).name()
+        ),
+        EventType::ButtonRelease(button) => format!(
+            "button release {} (rdev {button:?})",
+            MouseButton::from_rdev_event(button).name()
+        ),
+        EventType::MouseMove { x, y } => match listen_point {
+            // the grab position can differ from the listen one, see listen::start_listen
+            Some(point) => format!("move {x},{y} (listen {},{})", point.x, point.y),
+            None => format!("move {x},{y}"),
+        },
+        EventType::Wheel { delta_x, delta_y } if delta_y != 0 => format!(
+            "wheel delta_x={delta_x} delta_y={delta_y} {}",
+            // the invert_wheel of the config isn't applied, no config is loaded
+            MouseButton::from_rdev_wheel(delta_y, false).name()
+        ),
+        EventType::Wheel { delta_x, delta_y } => {
+            format!("wheel delta_x={delta_x} delta_y={delta_y} (horizontal, not bound)")
+        }
+    };
+    if let Some(device) = &event.device {
+        line.push_str(&format!(" device={device:?}"));
+    }
+    line
+}
+
+fn modifier_suffix(key: rdev::Key) -> &'static str {
+    if KeyboardState::default().set_key(key, true) {
+        " (modifier)"
+    } else {
+        ""
+    }
+}
+
+/// Grab the events and print them decoded, without config and without matching : all the
+/// events are propagated, to check that the hardware events reach mouse-actions.
+pub fn start_monitor(args: Arc<Args>) -> Result<(), GrabError> {
+    // FIXME : to avoid "Release Enter key event" to be lost (if run the script by Enter press in a terminal)
+    thread::sleep(time::Duration::from_millis(300));
+    let listen_point = (!args.no_listen).then(|| {
+        let point = Arc::new(AtomicPoint::new(Point { x: 0, y: 0 }));
+        listen::start_listen(point.clone());
+        point
+    });
+    debug!("Start monitor grab");
+    grab(move |event: Event| {
+        println!(
+            "{}",
+            event_line(&event, listen_point.as_ref().map(|point| point.get()))
+        );
+        Some(event)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use clap::Parser;
+    use rdev::{Button, Event, EventType, Key};
+
+    use crate::args::Args;
+    use crate::event::Point;
+    use crate::monitor::event_line;
+
+    fn event(event_type: EventType, device: Option<&str>) -> Event {
+        Event {
+            time: SystemTime::now(),
+            name: None,
+            event_type,
+            device: device.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_event_line() {
+        assert_eq!(
+            event_line(&event(EventType::ButtonPress(Button::Side), None), None),
+            "button press Side (rdev Side)"
+        );
+        assert_eq!(
+            event_line(
+                &event(EventType::ButtonRelease(Button::Unknown(9)), Some("M720")),
+                None
+            ),
+            "button release Unknown (rdev Unknown(9)) device=\"M720\""
+        );
+        assert_eq!(
+            event_line(&event(EventType::KeyPress(Key::ControlLeft), None), None),
+            "key press ControlLeft (modifier)"
+        );
+        assert_eq!(
+            event_line(&event(EventType::KeyRelease(Key::KeyG), None), None),
+            "key release KeyG"
+        );
+        assert_eq!(
+            event_line(
+                &event(EventType::MouseMove { x: 10.0, y: 20.5 }, None),
+                Some(Point { x: 11, y: 20 })
+            ),
+            "move 10,20.5 (listen 11,20)"
+        );
+        assert_eq!(
+            event_line(
+                &event(
+                    EventType::Wheel {
+                        delta_x: 0,
+                        delta_y: 1
+                    },
+                    None
+                ),
+                None
+            ),
+            "wheel delta_x=0 delta_y=1 WheelUp"
+        );
+        assert!(Args::parse_from(["mouse-actions", "--monitor-events"]).monitor_events);
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(feature = "dbus-notifications")]
+use std::thread;
+
+use log::debug;
+
+use crate::process_event::process_cmd;
+
+// without D-Bus, the notifications are shown by notify-send, through the notification daemon
+const NOTIFY_CMD: &str = "notify-send";
+const NOTIFY_APP_NAME: &str = "mouse-actions";
+
+/// the command that shows the notification of the message
+pub fn notify_cmd(message: &str) -> Vec<String> {
+    vec![
+        NOTIFY_CMD.to_string(),
+        NOTIFY_APP_NAME.to_string(),
+        message.to_string(),
+    ]
+}
+
+/// show a desktop notification, all the notifications of mouse-actions go through it
+#[cfg(feature = "dbus-notifications")]
+pub fn notify(message: &str) {
+    let message = message.to_string();
+    // the D-Bus call waits for the notification daemon : not on the grab thread
+    thread::Builder::new()
+        .name("notify".to_string())
+        .spawn(move || {
+            let res = notify_rust::Notification::new()
+                .appname(NOTIFY_APP_NAME)
+                .summary(NOTIFY_APP_NAME)
+                .body(&message)
+                .show();
+            if let Err(err) = res {
+                debug!("couldn't send the notification over D-Bus ({err}) : spawn {NOTIFY_CMD}");
+                process_cmd(notify_cmd(&message));
+            }
+        })
+        .unwrap();
+}
+
+/// show a desktop notification, all the notifications of mouse-actions go through it
+#[cfg(not(feature = "dbus-notifications"))]
+pub fn notify(message: &str) {
+    debug!("notification : {message:?}");
+    process_cmd(notify_cmd(message));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::notify::notify_cmd;
+
+    #[test]
+    fn test_notify_cmd() {
+        assert_eq!(
+            notify_cmd("L shape"),
+            vec!["notify-send", "mouse-actions", "L shape"]
+        );
+    }
+}
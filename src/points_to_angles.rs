@@ -1,7 +1,12 @@
-use crate::event::PointHistory;
+use std::f64::consts::{FRAC_PI_4, PI};
+use std::fmt;
+
+use crate::event::{Point, PointHistory};
 
 const MOV_AVG_COEFFICIENT: f64 = 0.8;
 const MOV_AVG_COEFFICIENT_COMP: f64 = 1. - MOV_AVG_COEFFICIENT;
+// the angle difference from which the direction has changed
+const DIRECTION_CHANGE_MIN_ANGLE: f64 = FRAC_PI_4;
 
 // get average angles from points
 pub fn points_to_angles(point_history: &PointHistory) -> Vec<f64> {
@@ -39,12 +44,159 @@ pub fn points_to_angles(point_history: &PointHistory) -> Vec<f64> {
     }
 }
 
+// the points every spacing pixels along the path of point_history, whatever the cursor
+// speed was : a fast move records sparse points, a slow move dense ones
+pub fn resample_points(point_history: &PointHistory, spacing: f64) -> PointHistory {
+    let Some(first) = point_history.first() else {
+        return PointHistory::new();
+    };
+    let mut points = vec![*first];
+    let (mut x, mut y) = (first.x as f64, first.y as f64);
+    // the distance walked along the path since the last resampled point
+    let mut walked = 0.0;
+    for point in point_history[1..].iter() {
+        let (to_x, to_y) = (point.x as f64, point.y as f64);
+        let mut length = ((to_x - x).powi(2) + (to_y - y).powi(2)).sqrt();
+        while walked + length >= spacing && length > 0.0 {
+            let ratio = (spacing - walked) / length;
+            x += (to_x - x) * ratio;
+            y += (to_y - y) * ratio;
+            points.push(Point {
+                x: x.round() as i32,
+                y: y.round() as i32,
+            });
+            length = ((to_x - x).powi(2) + (to_y - y).powi(2)).sqrt();
+            walked = 0.0;
+        }
+        walked += length;
+        (x, y) = (to_x, to_y);
+    }
+    if walked > 0.0 {
+        if let Some(last) = point_history.last() {
+            points.push(*last);
+        }
+    }
+    PointHistory::from_points(points)
+}
+
+// the angles of the same shape drawn in the other direction :
+// the sequence is reversed and each angle turned by π
+pub fn reverse_angles(angles: &[f64]) -> Vec<f64> {
+    angles
+        .iter()
+        .rev()
+        .map(|angle| if *angle > 0.0 { angle - PI } else { angle + PI })
+        .collect()
+}
+
+// the 8-way compass directions, counterclockwise from the right
+const DIRECTIONS: [Direction; 8] = [
+    Direction::Right,
+    Direction::UpRight,
+    Direction::Up,
+    Direction::UpLeft,
+    Direction::Left,
+    Direction::DownLeft,
+    Direction::Down,
+    Direction::DownRight,
+];
+// the part of the angles under which a direction is a transition, not a stroke
+const DIRECTION_MIN_RUN_RATIO: f64 = 0.1;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    Right,
+    UpRight,
+    Up,
+    UpLeft,
+    Left,
+    DownLeft,
+    Down,
+    DownRight,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Direction::Right => "right",
+            Direction::UpRight => "up-right",
+            Direction::Up => "up",
+            Direction::UpLeft => "up-left",
+            Direction::Left => "left",
+            Direction::DownLeft => "down-left",
+            Direction::Down => "down",
+            Direction::DownRight => "down-right",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Direction {
+    /// the direction of an angle of points_to_angles : 0 is right and π/2 is up,
+    /// an angle on a boundary (e.g. π/8) belongs to the direction farther from right
+    pub fn from_angle(angle: f64) -> Direction {
+        let sector = (angle / FRAC_PI_4).round() as i64;
+        DIRECTIONS[sector.rem_euclid(8) as usize]
+    }
+}
+
+// the successive directions of the strokes of the angles, e.g. right, down, left :
+// the short runs of directions crossed in the curves are ignored
+pub fn angles_to_directions(angles: &[f64]) -> Vec<Direction> {
+    let min_run = ((angles.len() as f64 * DIRECTION_MIN_RUN_RATIO) as usize).max(2);
+    let mut runs: Vec<(Direction, usize)> = vec![];
+    for direction in angles.iter().map(|angle| Direction::from_angle(*angle)) {
+        match runs.last_mut() {
+            Some((last, count)) if *last == direction => *count += 1,
+            _ => runs.push((direction, 1)),
+        }
+    }
+    let mut directions: Vec<Direction> = vec![];
+    for (direction, _) in runs.into_iter().filter(|(_, count)| *count >= min_run) {
+        if directions.last() != Some(&direction) {
+            directions.push(direction);
+        }
+    }
+    directions
+}
+
+pub fn directions_to_string(directions: &[Direction]) -> String {
+    directions
+        .iter()
+        .map(Direction::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// count the significant direction changes of the angles : a straight line has none
+pub fn direction_changes(angles: &[f64]) -> usize {
+    let mut changes = 0;
+    if let Some(first) = angles.first() {
+        let mut direction = *first;
+        for angle in angles[1..].iter() {
+            let mut diff = (angle - direction).abs();
+            if diff > PI {
+                diff = 2.0 * PI - diff;
+            }
+            if diff > DIRECTION_CHANGE_MIN_ANGLE {
+                changes += 1;
+                direction = *angle;
+            }
+        }
+    }
+    changes
+}
+
 #[cfg(test)]
 mod tests {
-    use std::f64::consts::FRAC_PI_4;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, FRAC_PI_8, PI};
 
+    use crate::compare_angles::compare_angles;
     use crate::event::{Point, PointHistory};
-    use crate::points_to_angles::points_to_angles;
+    use crate::points_to_angles::{
+        angles_to_directions, direction_changes, directions_to_string, points_to_angles,
+        resample_points, reverse_angles, Direction,
+    };
 
     #[test]
     fn test_points_to_angles() {
@@ -71,4 +223,99 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_direction_changes() {
+        let mut line = PointHistory::new();
+        (0..40).for_each(|i| {
+            line.push(Point {
+                x: i * 10,
+                y: i * 3,
+            })
+        });
+        assert_eq!(direction_changes(&points_to_angles(&line)), 0);
+
+        let mut l_shape = PointHistory::new();
+        (0..20).for_each(|i| l_shape.push(Point { x: 0, y: i * 10 }));
+        (0..20).for_each(|i| l_shape.push(Point { x: i * 10, y: 200 }));
+        assert_eq!(direction_changes(&points_to_angles(&l_shape)), 1);
+
+        assert_eq!(direction_changes(&[]), 0);
+    }
+
+    #[test]
+    fn test_reverse_angles() {
+        let mut points = PointHistory::new();
+        (0..20).for_each(|i| points.push(Point { x: 0, y: i * 10 }));
+        (0..20).for_each(|i| points.push(Point { x: i * 10, y: 200 }));
+        let mut reversed_points = points.clone();
+        reversed_points.reverse();
+
+        let reversed = reverse_angles(&points_to_angles(&points));
+        let drawn_reversed = points_to_angles(&reversed_points);
+        assert!(compare_angles(&reversed, &drawn_reversed) < 0.5);
+        assert!(compare_angles(&points_to_angles(&points), &drawn_reversed) > 1.0);
+    }
+
+    #[test]
+    fn test_direction_from_angle() {
+        assert_eq!(Direction::from_angle(0.0), Direction::Right);
+        assert_eq!(Direction::from_angle(FRAC_PI_2), Direction::Up);
+        assert_eq!(Direction::from_angle(-FRAC_PI_2), Direction::Down);
+        assert_eq!(Direction::from_angle(PI), Direction::Left);
+        assert_eq!(Direction::from_angle(-PI), Direction::Left);
+        assert_eq!(Direction::from_angle(3.0 * FRAC_PI_4), Direction::UpLeft);
+        assert_eq!(Direction::from_angle(-FRAC_PI_4), Direction::DownRight);
+        // the boundaries
+        assert_eq!(Direction::from_angle(FRAC_PI_8 - 0.001), Direction::Right);
+        assert_eq!(Direction::from_angle(FRAC_PI_8), Direction::UpRight);
+        assert_eq!(Direction::from_angle(-FRAC_PI_8), Direction::DownRight);
+        assert_eq!(Direction::from_angle(7.0 * FRAC_PI_8), Direction::Left);
+        assert_eq!(Direction::from_angle(-7.0 * FRAC_PI_8), Direction::Left);
+    }
+
+    #[test]
+    fn test_angles_to_directions() {
+        let mut points = PointHistory::new();
+        (0..20).for_each(|i| points.push(Point { x: i * 10, y: 0 }));
+        (0..20).for_each(|i| points.push(Point { x: 200, y: i * 10 }));
+        (0..20).for_each(|i| {
+            points.push(Point {
+                x: 200 - i * 10,
+                y: 200,
+            })
+        });
+        let directions = angles_to_directions(&points_to_angles(&points));
+        assert_eq!(
+            directions,
+            vec![Direction::Right, Direction::Down, Direction::Left]
+        );
+        assert_eq!(directions_to_string(&directions), "right, down, left");
+        assert!(angles_to_directions(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_resample_points() {
+        // a fast move then a slow move along the same line
+        let mut points = PointHistory::new();
+        [0, 50, 100, 101, 102, 103, 104, 105, 110, 120]
+            .iter()
+            .for_each(|x| points.push(Point { x: *x, y: 0 }));
+        let resampled = resample_points(&points, 10.0);
+        let xs = resampled.iter().map(|p| p.x).collect::<Vec<_>>();
+        assert_eq!(xs, (0..=12).map(|i| i * 10).collect::<Vec<_>>());
+        assert!(resampled.iter().all(|p| p.y == 0));
+
+        // the last point is kept
+        let mut points = PointHistory::new();
+        points.push(Point { x: 0, y: 0 });
+        points.push(Point { x: 0, y: 25 });
+        let ys = resample_points(&points, 10.0)
+            .iter()
+            .map(|p| p.y)
+            .collect::<Vec<_>>();
+        assert_eq!(ys, vec![0, 10, 20, 25]);
+
+        assert!(resample_points(&PointHistory::new(), 10.0).is_empty());
+    }
 }
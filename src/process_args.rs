@@ -1,18 +1,24 @@
 use std::io;
 use std::io::{ErrorKind, Read};
 use std::ops::Deref;
+use std::path::Path;
 use std::process::exit;
 use std::sync::Arc;
 
 use clap::Parser;
-use env_logger::Env;
+use env_logger::{Env, Target, WriteStyle};
 use log::{debug, error, info, trace};
 use rdev::GrabError;
 
 use crate::args::{Args, MouseActionsCommands};
 use crate::config::{get_config_from_args, IS_WAYLAND};
+use crate::event::{Bounds, MouseButton, RelativeTo};
+use crate::log_file::{RotatingFile, LOG_FILE_KEEP, LOG_FILE_MAX_SIZE};
 use crate::single_instance::get_instance;
-use crate::{config, grab, process_event, record, single_instance};
+use crate::{
+    config, edges_check, grab, latency, monitor, notify, process_event, record, schema,
+    single_instance,
+};
 
 #[cfg(target_os = "linux")]
 static DEV_PATH: &str = "/dev/input";
@@ -36,7 +42,21 @@ pub fn process_args(args: Args) {
     let mut args: Arc<Args> = Arc::new(args);
 
     let log_level = args.deref().log_level.clone().unwrap_or("info".to_string());
-    env_logger::Builder::from_env(Env::default().default_filter_or(&log_level)).init();
+    let mut logger = env_logger::Builder::from_env(Env::default().default_filter_or(&log_level));
+    if let Some(log_file) = &args.log_file {
+        match RotatingFile::open(Path::new(log_file), LOG_FILE_MAX_SIZE, LOG_FILE_KEEP) {
+            Ok(file) => {
+                logger
+                    .target(Target::Pipe(Box::new(file)))
+                    .write_style(WriteStyle::Never);
+            }
+            Err(err) => {
+                eprintln!("couldn't open the log file {log_file:?} : {err}");
+                exit(1);
+            }
+        }
+    }
+    logger.init();
     debug!("log_level={log_level}");
 
     trace!("version : {}", get_version());
@@ -49,18 +69,41 @@ pub fn process_args(args: Args) {
     }
     if args.version {
         println!("{}", get_version());
+    } else if args.emit_schema {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&schema::config_schema()).unwrap()
+        );
+    } else if args.monitor_events {
+        if let Err(error) = monitor_events(&args) {
+            process_error(&error);
+            exit(4);
+        }
     } else {
         // TODO enum resut (nor only GrabError)
         let res: Result<(), GrabError> = match args.command {
             Some(MouseActionsCommands::Start) | None => start(&args),
             Some(MouseActionsCommands::Trace) => trace(&args),
             Some(MouseActionsCommands::Record) => record(&args),
+            Some(MouseActionsCommands::Tune) => tune(&args),
+            Some(MouseActionsCommands::Latency) => latency(&args),
+            Some(MouseActionsCommands::RecordSamples { .. }) => record_samples(&args),
+            Some(MouseActionsCommands::Edges {
+                bounds,
+                corner_size,
+                window,
+            }) => edges(bounds, corner_size, window),
             Some(MouseActionsCommands::ListBindings) => list_bindings(&args),
             Some(MouseActionsCommands::GrabOneEvent) => grab_one_event(&args),
+            Some(MouseActionsCommands::ListButtons) => list_buttons(),
+            Some(MouseActionsCommands::WhichButton) => which_button(&args),
             Some(MouseActionsCommands::Stop) => stop(),
             Some(MouseActionsCommands::Status) => status(),
+            Some(MouseActionsCommands::ResetState) => reset_state(),
             Some(MouseActionsCommands::ShowConfig) => show_config(&args),
             Some(MouseActionsCommands::SetConfig) => set_config(&args),
+            Some(MouseActionsCommands::InitExamples) => init_examples(&args),
+            Some(MouseActionsCommands::Format) => format_config(&args),
             Some(MouseActionsCommands::ShowGui) => {
                 error!("This binary doesn't have the GUI");
                 exit(6);
@@ -79,9 +122,19 @@ pub fn trace(args: &Arc<Args>) -> Result<(), GrabError> {
     grab::start_grab_binding(args.clone(), config, process_event::trace_event)
 }
 
+pub fn monitor_events(args: &Arc<Args>) -> Result<(), GrabError> {
+    let _instance = get_instance().unwrap();
+    println!("Start monitor : every event is printed, no binding is run, Ctrl+C to stop");
+    monitor::start_monitor(args.clone())
+}
+
 pub fn record(args: &Arc<Args>) -> Result<(), GrabError> {
+    if !args.overlay_config_paths().is_empty() {
+        error!("The record writes the first config file, it can't be used with overlays");
+        exit(1);
+    }
     let _instance = get_instance().unwrap();
-    let config = get_config_from_args(&args, true);
+    let config = get_config_from_args(args, true);
     println!(
         "Start record event : draw a shape with the {:?} button :",
         config.lock().unwrap().shape_button
@@ -89,29 +142,85 @@ pub fn record(args: &Arc<Args>) -> Result<(), GrabError> {
     grab::start_grab_binding(args.clone(), config, record::record_event)
 }
 
+pub fn tune(args: &Arc<Args>) -> Result<(), GrabError> {
+    let _instance = get_instance().unwrap();
+    let config = get_config_from_args(args, true);
+    println!(
+        "Start tune : draw the shapes with the {:?} button, no command is run, Ctrl+C to stop",
+        config.lock().unwrap().shape_button
+    );
+    grab::start_grab_binding(args.clone(), config, process_event::tune_event)
+}
+
+pub fn latency(args: &Arc<Args>) -> Result<(), GrabError> {
+    let _instance = get_instance().unwrap();
+    let config = get_config_from_args(args, true);
+    println!("Start latency : the bindings run, their latencies are printed, Ctrl+C to stop");
+    grab::start_grab_binding(args.clone(), config, latency::latency_event)
+}
+
+pub fn record_samples(args: &Arc<Args>) -> Result<(), GrabError> {
+    let _instance = get_instance().unwrap();
+    let config = get_config_from_args(args, false);
+    println!(
+        "Start record samples : draw the shapes with the {:?} button, Ctrl+C to stop",
+        config.lock().unwrap().shape_button
+    );
+    grab::start_grab_binding(args.clone(), config, record::record_sample_event)
+}
+
 pub fn start(args: &Arc<Args>) -> Result<(), GrabError> {
     let _instance = get_instance().unwrap();
     let config = get_config_from_args(args, true);
+    if config.lock().unwrap().bindings.is_empty() {
+        show_empty_config_hint(args);
+    }
     grab::start_grab_binding(args.clone(), config, process_event::process_event)
 }
 
+/// On the first run, the config has no binding and the daemon silently does nothing :
+/// tell the user how to fill the config, in the log and with a desktop notification.
+fn show_empty_config_hint(args: &Arc<Args>) {
+    let config_path = config::get_config_path(&args.main_config_path());
+    let hint = format!(
+        "The config {config_path:?} has no binding : run `mouse-actions init-examples` \
+         to get some examples, `mouse-actions record` to record a shape, or edit the config file"
+    );
+    info!("{hint}");
+    notify::notify(&hint);
+}
+
 pub fn list_bindings(args: &Arc<Args>) -> Result<(), GrabError> {
-    let config = get_config_from_args(&args, false);
+    let config = get_config_from_args(args, false);
     config
         .lock()
         .unwrap()
         .bindings
         .iter()
-        .for_each(|b| println!(" - {}\n    {:?}\n", b.comment, b.cmd));
+        .for_each(|b| println!(" - {} [{}]\n    {:?}\n", b.comment, b.stable_id(), b.cmd));
     Ok(())
 }
 
 pub fn grab_one_event(args: &Arc<Args>) -> Result<(), GrabError> {
     let _instance = get_instance().unwrap();
-    let config = get_config_from_args(&args, false);
+    let config = get_config_from_args(args, false);
     grab::start_grab_binding(args.clone(), config, process_event::grab_one_event)
 }
 
+pub fn list_buttons() -> Result<(), GrabError> {
+    MouseButton::ALL
+        .iter()
+        .for_each(|button| println!("{}", button.name()));
+    Ok(())
+}
+
+pub fn which_button(args: &Arc<Args>) -> Result<(), GrabError> {
+    let _instance = get_instance().unwrap();
+    let config = get_config_from_args(args, false);
+    println!("Press a mouse button :");
+    grab::start_grab_binding(args.clone(), config, process_event::which_button)
+}
+
 pub fn stop() -> Result<(), GrabError> {
     if single_instance::kill().unwrap_or(false) {
         info!("mouse_actions successfully stopped");
@@ -132,6 +241,41 @@ pub fn status() -> Result<(), GrabError> {
     }
 }
 
+pub fn reset_state() -> Result<(), GrabError> {
+    match single_instance::send_reset_signal() {
+        Ok(pid) => {
+            info!("reset the state of mouse_actions (pid {pid}) on its next event");
+            exit(0);
+        }
+        Err(err) => {
+            error!("mouse_actions reset-state error : {err}");
+            exit(1);
+        }
+    }
+}
+
+pub fn edges(bounds: Bounds, corner_size: u32, window: bool) -> Result<(), GrabError> {
+    let relative_to = if window {
+        RelativeTo::Window
+    } else {
+        RelativeTo::Screen
+    };
+    let mut path = String::new();
+    io::stdin().read_to_string(&mut path).unwrap();
+    match edges_check::check_edges(&path, bounds, corner_size, relative_to) {
+        Ok(edges) => {
+            for (point, edges) in edges {
+                println!("{},{} {edges:?}", point.x, point.y);
+            }
+        }
+        Err(err) => {
+            error!("invalid cursor path : {err:#}");
+            exit(1);
+        }
+    }
+    Ok(())
+}
+
 pub fn show_config(args: &Arc<Args>) -> Result<(), GrabError> {
     let serialized = config::get_json_config(args);
     println!("{serialized}");
@@ -139,14 +283,50 @@ pub fn show_config(args: &Arc<Args>) -> Result<(), GrabError> {
 }
 
 pub fn set_config(args: &Arc<Args>) -> Result<(), GrabError> {
+    exit_if_stdin_config(args);
     let mut stdin_str = String::new();
     io::stdin().read_to_string(&mut stdin_str).unwrap();
     // check the deserialization
-    let config = config::load_from_str(&stdin_str);
-    config::save_config(&config, &args.config_path);
+    let config = config::load_from_str(&stdin_str).unwrap_or_else(|err| {
+        error!("The config is invalid, it is not saved : {err:#}");
+        exit(1);
+    });
+    config::save_config(&config, &args.main_config_path());
+    Ok(())
+}
+
+pub fn init_examples(args: &Arc<Args>) -> Result<(), GrabError> {
+    exit_if_stdin_config(args);
+    let config_path = config::get_config_path(&args.main_config_path());
+    if !config::init_config_file_with_examples(&config_path) {
+        error!("The config file {config_path:?} already has bindings, it is not overwritten");
+        exit(1);
+    }
     Ok(())
 }
 
+pub fn format_config(args: &Arc<Args>) -> Result<(), GrabError> {
+    exit_if_stdin_config(args);
+    let config_path = config::get_config_path(&args.main_config_path());
+    match config::format_config_file(&config_path) {
+        Ok(true) => println!("{config_path:?} formatted"),
+        Ok(false) => println!("{config_path:?} is already formatted"),
+        Err(err) => {
+            error!("couldn't format the config {config_path:?} : {err:#}");
+            exit(1);
+        }
+    }
+    Ok(())
+}
+
+/// the commands that write the config file can't use the stdin config
+fn exit_if_stdin_config(args: &Arc<Args>) {
+    if config::is_stdin_config(&args.main_config_path()) {
+        error!("This command writes the config file, it can't be used with the stdin config");
+        exit(1);
+    }
+}
+
 pub fn process_error(error: &GrabError) {
     error!("Grab Error: {:#?}", error);
 
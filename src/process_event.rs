@@ -1,56 +1,261 @@
+use std::io;
+use std::io::Read;
 use std::ops::{Deref, Mul};
 use std::os::unix::process::CommandExt;
-use std::process::{exit, Command};
+use std::process::{exit, Child, Command, ExitStatus, Stdio};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
-use std::{thread, time};
-use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use log::{debug, error, info, trace, warn};
-use rdev::{simulate, EventType, Button};
+use log::Level::Debug;
+use log::{debug, error, info, log_enabled, trace, warn};
+use rdev::Button;
+use serde_json::json;
 
 use crate::args::Args;
-use crate::binding::Binding;
-use crate::compare_angles::compare_angles_with_offset;
+use crate::binding::{Action, Binding};
+use crate::cmd_str_spliter::str_cmd_to_array;
+use crate::compare_angles::{compare_angles_with_offset_weighted, AngleWeighting};
 use crate::config::Config;
 use crate::event;
-use crate::event::{edges_are_equals, modifiers_are_equals, ClickEvent};
-use crate::grab::normalize_points;
+use crate::event::{
+    buttons_are_equals, edges_are_equals, keys_are_equals, modifiers_are_equals,
+    quadrants_are_equals, ClickEvent, MouseButton,
+};
+use crate::grab::{is_shape, normalize_points};
+use crate::points_to_angles::{direction_changes, reverse_angles};
 use crate::record::reduce_shape_precision;
+use crate::repeat::{start_repeat, stop_all_repeats, stop_repeat};
+use crate::runner::{CommandRunner, PROCESS_RUNNER};
+use crate::{focus, notify, schedule, sound, spawned};
 
 const DIFF_MAX: f64 = 0.8;
 const DIFF_MIN_WITH_SECOND: f64 = 0.05;
 const DIFF_MAX_PRINT: f64 = 300.0;
 const SHAPE_MIN_SIZE: usize = 8;
+const WHEN_TIMEOUT: Duration = Duration::from_millis(500);
+// the bytes of the end of the stdout and stderr of a command kept for the failure log
+const CMD_OUTPUT_TAIL: usize = 4096;
+
+lazy_static::lazy_static! {
+    // the arm_required bindings can fire until this instant
+    static ref ARMED_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
+}
 
 // TODO refactor
 
 /// filter the binding[] of config : keep bindings that have the same button, edges and modifiers
 pub fn find_candidates<'a>(config: &'a Config, event: &ClickEvent) -> Vec<&'a Binding> {
-    let shape_button = &config.shape_button;
     config
         .bindings
         .iter()
-        .filter(|binding| {
-            // TODO comment
-            (binding.event.shapes_angles.is_empty()
-                || shape_button != &binding.event.button
-                || event.event_type != event::EventType::Press)
-                && binding.event.button == event.button
-                && (binding.event.event_type == event.event_type
-                    || (binding.event.event_type == event::EventType::Click
-                        || binding.event.event_type == event::EventType::Shape
-                            && event.event_type == event::EventType::Release))
-                && (edges_are_equals(&binding.event.edges, &event.edges)
-                    || binding.event.event_type == event::EventType::Shape)
-                && modifiers_are_equals(&binding.event.modifiers, &event.modifiers)
-        })
+        .filter(|binding| is_candidate(&config.shape_button, binding, event))
         .collect::<Vec<&Binding>>()
 }
 
+/// the binding has the same button, edges and modifiers than the event
+pub fn is_candidate(shape_button: &MouseButton, binding: &Binding, event: &ClickEvent) -> bool {
+    // TODO comment
+    (binding.event.shapes_angles.is_empty()
+        || !binding.event.matches_button(*shape_button)
+        || event.event_type != event::EventType::Press)
+        && binding.event.matches_button(event.button)
+        && (binding.event.event_type == event.event_type
+            || (binding.event.event_type == event::EventType::Click
+                || binding.event.event_type == event::EventType::Shape
+                    && event.event_type == event::EventType::Release))
+        && (edges_are_equals(&binding.event.edges, &event.edges)
+            || binding.event.event_type == event::EventType::Shape)
+        && !event
+            .edges
+            .iter()
+            .any(|edge| binding.event.edges_forbidden.contains(edge))
+        && (!binding.location_sensitive || binding.event.region == event.region)
+        && (binding.event.quadrants.is_empty()
+            || quadrants_are_equals(&binding.event.quadrants, &event.quadrants))
+        && device_matches(binding, event)
+        && buttons_are_equals(&binding.event.held_buttons, &event.held_buttons)
+        && modifiers_are_equals(&binding.event.modifiers, &event.modifiers)
+        && keys_are_equals(&binding.event.keys, &event.keys)
+        && binding.event.shape_button_held == event.shape_button_held
+        && binding
+            .event
+            .closed
+            .is_none_or(|closed| event.closed == Some(closed))
+}
+
+/// the device of the binding, if any, is the device of the event. The device of the
+/// events is only known by the evdev grab : the condition is ignored without it.
+fn device_matches(binding: &Binding, event: &ClickEvent) -> bool {
+    match (&binding.event.device, &event.device) {
+        (Some(binding_device), Some(event_device)) => binding_device == event_device,
+        _ => true,
+    }
+}
+
+/// the command of a click binding is run on the press, not on the release
+fn runs_cmd_on(binding: &Binding, event: &ClickEvent) -> bool {
+    !(event.event_type == event::EventType::Release
+        && binding.event.event_type == event::EventType::Click
+        && binding.event.shapes_angles.is_empty())
+}
+
+/// the event runs the action or a command of the binding
+fn fires_on(binding: &Binding, event: &ClickEvent) -> bool {
+    if binding.action.is_some() {
+        runs_cmd_on(binding, event)
+    } else {
+        cmd_for_event(binding, event).is_some()
+    }
+}
+
+/// The command to run for the event : the press_cmd or the release_cmd of a
+/// press/release binding, or the cmd of the other bindings
+pub fn cmd_for_event(binding: &Binding, event: &ClickEvent) -> Option<Vec<String>> {
+    let cmd = if binding.is_press_release() {
+        match event.event_type {
+            event::EventType::Press => str_cmd_to_array(&binding.press_cmd),
+            event::EventType::Release => str_cmd_to_array(&binding.release_cmd),
+            _ => vec![],
+        }
+    } else if runs_cmd_on(binding, event) {
+        binding.cmd.clone()
+    } else {
+        vec![]
+    };
+    (!cmd.is_empty()).then_some(cmd)
+}
+
+/// the JSON line of a matched binding printed by --emit-matches : the binding id and
+/// comment, and a summary of the event (without the shape points)
+pub fn match_line(binding: &Binding, event: &ClickEvent) -> String {
+    json!({
+        "id": binding.stable_id(),
+        "comment": binding.comment,
+        "event": {
+            "button": event.button,
+            "event_type": event.event_type,
+            "edges": event.edges,
+            "modifiers": event.modifiers,
+        },
+    })
+    .to_string()
+}
+
+/// do the built-in action of the binding
+fn run_action(binding: &Binding, action: Action, runner: &Arc<dyn CommandRunner>) {
+    if binding.log {
+        info!("{} → action {action:?}", binding.log_fields());
+    }
+    match action {
+        Action::KillSpawned => {
+            stop_all_repeats();
+            runner.kill_spawned();
+        }
+    }
+}
+
+/// the command prefixed with the command_prefix of the config, unless the binding opts out
+pub fn prefixed_cmd(config: &Config, binding: &Binding, cmd: Vec<String>) -> Vec<String> {
+    if config.command_prefix.is_empty() || binding.no_command_prefix {
+        cmd
+    } else {
+        config.command_prefix.iter().cloned().chain(cmd).collect()
+    }
+}
+
+/// If the event is the arm gesture of the config : arm the `arm_required` bindings
+/// and run the arm command, return false if the event is not the arm gesture.
+fn process_arm_gesture(
+    config: &Config,
+    event: &ClickEvent,
+    now: Instant,
+    runner: &Arc<dyn CommandRunner>,
+) -> bool {
+    let Some(arm) = &config.arm else {
+        return false;
+    };
+    if !is_candidate(&config.shape_button, &arm.binding, event)
+        || find_the_chosen_one_among_the_candidates(&[&arm.binding], event, config.angle_weighting)
+            .is_none()
+    {
+        return false;
+    }
+    info!(
+        "{} arm the bindings for {}ms",
+        arm.binding.log_fields(),
+        arm.duration_ms
+    );
+    *ARMED_UNTIL.lock().unwrap() = Some(now + Duration::from_millis(arm.duration_ms));
+    if !arm.binding.cmd.is_empty() && runs_cmd_on(&arm.binding, event) {
+        runner.run(
+            &arm.binding,
+            prefixed_cmd(config, &arm.binding, arm.binding.cmd.clone()),
+        );
+    }
+    true
+}
+
+/// the shape of the event has less direction changes than min_direction_changes
+fn is_straight_drag(config: &Config, event: &ClickEvent) -> bool {
+    config.min_direction_changes > 0
+        && event
+            .shapes_angles
+            .first()
+            .is_some_and(|angles| direction_changes(angles) < config.min_direction_changes)
+}
+
+/// the drag length is in the length range of the binding, if any : the events without
+/// length (not a shape_button drag) don't match a binding with a range
+fn is_length_in_range(binding: &Binding, event: &ClickEvent) -> bool {
+    if binding.length_min.is_none() && binding.length_max.is_none() {
+        return true;
+    }
+    let in_range = event.length.is_some_and(|length| {
+        binding.length_min.is_none_or(|min| length >= min)
+            && binding.length_max.is_none_or(|max| length <= max)
+    });
+    if !in_range {
+        debug!(
+            "{} skip : length {:?} out of the length range",
+            binding.log_fields(),
+            event.length
+        );
+    }
+    in_range
+}
+
+fn is_idle_enough(binding: &Binding, event: &ClickEvent) -> bool {
+    let idle_enough = binding.idle_min_ms.is_none_or(|idle_min_ms| {
+        event
+            .idle
+            .is_some_and(|idle| idle >= Duration::from_millis(idle_min_ms))
+    });
+    if !idle_enough {
+        debug!(
+            "{} skip : idle {:?} < idle_min_ms",
+            binding.log_fields(),
+            event.idle
+        );
+    }
+    idle_enough
+}
+
+fn is_armed(binding: &Binding, now: Instant) -> bool {
+    let armed = ARMED_UNTIL
+        .lock()
+        .unwrap()
+        .is_some_and(|armed_until| now <= armed_until);
+    if !armed {
+        debug!("{} skip : the bindings are not armed", binding.log_fields());
+    }
+    armed
+}
+
 pub fn find_candidates_with_shape_with_offset<'a>(
     candidates: &'a [&Binding],
     event: &ClickEvent,
+    weighting: AngleWeighting,
     // FIXME use struct & check lifetime usages
 ) -> Vec<(&'a &'a Binding, f64)> {
     debug!(
@@ -67,7 +272,7 @@ pub fn find_candidates_with_shape_with_offset<'a>(
     let start = Instant::now();
     let mut candidates_with_shape = candidates
         .iter()
-        .filter(|binding| binding.event.shapes_angles.first().is_some())
+        .filter(|binding| !binding.event.shapes_angles.is_empty())
         .map(|binding| {
             trace!("compare_angles_with_offset of {}", binding.comment);
             (
@@ -78,10 +283,17 @@ pub fn find_candidates_with_shape_with_offset<'a>(
                     .iter()
                     .filter(|angles| angles.len() > SHAPE_MIN_SIZE)
                     .map(|angles| {
-                        let res = compare_angles_with_offset(
-                            &event.shapes_angles.first().unwrap(),
-                            &angles,
-                        );
+                        let event_angles = event.shapes_angles.first().unwrap();
+                        let mut res =
+                            compare_angles_with_offset_weighted(event_angles, angles, weighting);
+                        if binding.bidirectional {
+                            let reversed = reverse_angles(angles);
+                            res = res.min(compare_angles_with_offset_weighted(
+                                event_angles,
+                                &reversed,
+                                weighting,
+                            ));
+                        }
                         trace!("  res = {res}");
                         res
                     })
@@ -100,9 +312,43 @@ pub fn find_candidates_with_shape_with_offset<'a>(
     candidates_with_shape
 }
 
+/// the similarity percentage of a shape difference, for the logs
+pub fn diff_to_score(diff: f64) -> f64 {
+    f64::max(0., 100.0 - diff.powi(2).mul(100.))
+}
+
+/// true if the shape match of the binding reaches the consume_threshold of the config:
+/// a borderline match doesn't run the binding, the click is replayed like without match
+pub fn is_confident_match(config: &Config, binding: &Binding, event: &ClickEvent) -> bool {
+    let Some(threshold) = config.consume_threshold else {
+        return true;
+    };
+    if binding.event.shapes_angles.is_empty() || event.shapes_angles.is_empty() {
+        // not a shape match
+        return true;
+    }
+    let Some(diff) =
+        find_candidates_with_shape_with_offset(&[binding], event, config.angle_weighting)
+            .first()
+            .map(|(_, diff)| *diff)
+    else {
+        return true;
+    };
+    let score = diff_to_score(diff);
+    if score < threshold {
+        info!(
+            "{} borderline shape match : {score:05.2} % < consume_threshold {threshold:.2} % → replay the click",
+            binding.log_fields()
+        );
+        return false;
+    }
+    true
+}
+
 pub fn find_the_chosen_one_among_the_candidates_with_shape<'a>(
     candidates: &'a [&Binding],
     event: &ClickEvent,
+    weighting: AngleWeighting,
 ) -> Option<&'a Binding> {
     let shape_size = event
         .shapes_angles
@@ -111,7 +357,8 @@ pub fn find_the_chosen_one_among_the_candidates_with_shape<'a>(
         .unwrap_or_default();
 
     if shape_size > SHAPE_MIN_SIZE {
-        let candidates_with_shape = find_candidates_with_shape_with_offset(candidates, event);
+        let candidates_with_shape =
+            find_candidates_with_shape_with_offset(candidates, event, weighting);
 
         // check is not empty
         if let Some(first) = candidates_with_shape.first() {
@@ -120,15 +367,40 @@ pub fn find_the_chosen_one_among_the_candidates_with_shape<'a>(
                 .iter()
                 .take(5)
                 .for_each(|(binding, diff)| {
-                    let pc = f64::max(0., 100.0 - diff.powi(2).mul(100.));
                     debug!(
                         "   {:05.2} %    {diff:.2} : {}    {:?}",
-                        pc, binding.comment, binding.cmd
+                        diff_to_score(*diff),
+                        binding.comment,
+                        binding.cmd
                     )
                 });
+            debug!(
+                "{} best shape match : {:05.2} % (diff {:.2}), runner-up : {}",
+                first.0.log_fields(),
+                diff_to_score(first.1),
+                first.1,
+                candidates_with_shape
+                    .get(1)
+                    .map(|(binding, diff)| format!(
+                        "{:05.2} % (diff {diff:.2}) \"{}\"",
+                        diff_to_score(*diff),
+                        binding.comment
+                    ))
+                    .unwrap_or_else(|| "none".to_string())
+            );
 
-            if first.1 < DIFF_MAX {
-                if let Some(second) = candidates_with_shape.get(1) {
+            // the bindings under their diff_max, with the highest priority
+            let matched = candidates_with_shape
+                .iter()
+                .filter(|(binding, diff)| *diff < binding.diff_max.unwrap_or(DIFF_MAX))
+                .collect::<Vec<_>>();
+            let top_priority = matched.iter().map(|(binding, _)| binding.priority).max();
+            let matched = matched
+                .into_iter()
+                .filter(|(binding, _)| Some(binding.priority) == top_priority)
+                .collect::<Vec<_>>();
+            if let Some(first) = matched.first() {
+                if let Some(second) = matched.get(1) {
                     if second.1 - first.1 > DIFF_MIN_WITH_SECOND {
                         return Some(first.0);
                     } else {
@@ -140,8 +412,13 @@ pub fn find_the_chosen_one_among_the_candidates_with_shape<'a>(
                     return Some(first.0);
                 }
             } else {
-                debug!("shape difference > {DIFF_MAX} → ignore this event");
+                debug!(
+                    "shape difference > {} → ignore this event",
+                    first.0.diff_max.unwrap_or(DIFF_MAX)
+                );
             }
+        } else {
+            debug!("no shape candidate");
         }
     } else {
         trace!("shape size({shape_size}) <= {SHAPE_MIN_SIZE} → ignore this event");
@@ -149,6 +426,43 @@ pub fn find_the_chosen_one_among_the_candidates_with_shape<'a>(
     None
 }
 
+/// the closest shape binding of a shape that matched nothing, to tell the user whether
+/// to redraw the shape or to increase the diff_max of the binding
+pub fn closest_shape_suggestion(
+    candidates: &[&Binding],
+    event: &ClickEvent,
+    weighting: AngleWeighting,
+) -> Option<String> {
+    let shape_size = event.shapes_angles.first().map_or(0, |angles| angles.len());
+    if shape_size <= SHAPE_MIN_SIZE {
+        return None;
+    }
+    find_candidates_with_shape_with_offset(candidates, event, weighting)
+        .first()
+        .map(|(binding, diff)| {
+            format!(
+                "no shape matched, did you mean \"{}\" ? {:05.2} % (diff {diff:.2}, diff_max {:.2})",
+                binding.comment,
+                diff_to_score(*diff),
+                binding.diff_max.unwrap_or(DIFF_MAX)
+            )
+        })
+}
+
+fn suggest_closest_shape(config: &Config, candidates: &[&Binding], event: &ClickEvent) {
+    if !config.suggest_closest && !log_enabled!(Debug) {
+        return;
+    }
+    if let Some(suggestion) = closest_shape_suggestion(candidates, event, config.angle_weighting) {
+        if config.suggest_closest {
+            info!("{suggestion}");
+            notify::notify(&suggestion);
+        } else {
+            debug!("{suggestion}");
+        }
+    }
+}
+
 pub fn find_the_chosen_one_among_the_candidates_without_shape<'a>(
     candidates: &'a [&Binding],
     event: &ClickEvent,
@@ -158,35 +472,41 @@ pub fn find_the_chosen_one_among_the_candidates_without_shape<'a>(
         .filter(|b| b.event.shapes_angles.is_empty())
         .collect::<Vec<_>>();
 
-    match candidates_without_shape.len() {
-        1 => {
-            let binding = candidates_without_shape.first().unwrap();
-            debug!("Binding without shape found : {:?}", binding);
-            return Some(binding);
-        }
-        0 => {}
-        _ => {
-            warn!(
-                "WARNING, several candidate ! ev = {:?} candidates = {:?}",
-                event, candidates_without_shape
-            );
-        }
+    // the highest priority wins, then the config order
+    let top_priority = candidates_without_shape.iter().map(|b| b.priority).max()?;
+    let mut top_candidates = candidates_without_shape
+        .iter()
+        .filter(|b| b.priority == top_priority);
+    let binding = top_candidates.next()?;
+    if top_candidates.next().is_some() {
+        warn!(
+            "WARNING, several candidates with the priority {top_priority}, the first one in the config order is used ! ev = {:?} candidates = {:?}",
+            event, candidates_without_shape
+        );
     }
-    None
+    if binding.log {
+        debug!(
+            "{} binding without shape found : {:?}",
+            binding.log_fields(),
+            binding
+        );
+    }
+    Some(binding)
 }
 
 pub fn find_the_chosen_one_among_the_candidates<'a>(
     candidates: &'a [&Binding],
     event: &ClickEvent,
+    weighting: AngleWeighting,
 ) -> Option<&'a Binding> {
-    find_the_chosen_one_among_the_candidates_with_shape(candidates, event)
+    find_the_chosen_one_among_the_candidates_with_shape(candidates, event, weighting)
         .or_else(|| find_the_chosen_one_among_the_candidates_without_shape(candidates, event))
 }
 
 pub fn trace_event(_config: Arc<Mutex<Config>>, event: ClickEvent, _args: Arc<Args>) -> bool {
     println!("event={:?}", event);
     if let Some(shapes_xy) = event.shapes_xy.first() {
-        let normalized_points = normalize_points(&shapes_xy, false);
+        let normalized_points = normalize_points(shapes_xy, false);
         trace!("normalized_points = {normalized_points:?}");
         println!(
             "shapes_xy={}",
@@ -214,28 +534,190 @@ pub fn grab_one_event(config: Arc<Mutex<Config>>, event: ClickEvent, _args: Arc<
     true
 }
 
+/// the table of the scores of all the shape bindings for the shape of the event, ranked
+/// by similarity. The binding that the event would run is marked with `>`, the bindings
+/// whose button, modifiers or conditions don't match the event are marked with `x`.
+pub fn shape_scores_table(config: &Config, event: &ClickEvent) -> Option<String> {
+    let shape_size = event.shapes_angles.first()?.len();
+    if shape_size <= SHAPE_MIN_SIZE {
+        return Some(format!(
+            "shape too short ({shape_size} angles <= {SHAPE_MIN_SIZE})"
+        ));
+    }
+    let all_bindings = config.bindings.iter().collect::<Vec<&Binding>>();
+    let scores =
+        find_candidates_with_shape_with_offset(&all_bindings, event, config.angle_weighting);
+    if scores.is_empty() {
+        return Some("no shape binding".to_string());
+    }
+    let candidates = find_candidates(config, event);
+    let chosen = find_the_chosen_one_among_the_candidates_with_shape(
+        &candidates,
+        event,
+        config.angle_weighting,
+    );
+    let mut table = format!(
+        "  {:>7}  {:>5}  {:>8}  comment\n",
+        "score", "diff", "diff_max"
+    );
+    for (binding, diff) in scores {
+        let mark = if chosen.is_some_and(|chosen| std::ptr::eq(chosen, *binding)) {
+            '>'
+        } else if !candidates.iter().any(|c| std::ptr::eq(*c, *binding)) {
+            'x'
+        } else {
+            ' '
+        };
+        table.push_str(&format!(
+            "{mark} {:>6.2} %  {diff:>5.2}  {:>8.2}  {}\n",
+            diff_to_score(diff),
+            binding.diff_max.unwrap_or(DIFF_MAX),
+            binding.comment
+        ));
+    }
+    if chosen.is_none() {
+        table.push_str("no binding would run\n");
+    }
+    Some(table)
+}
 
+/// print the scores of the shape bindings after each shape, without running any command
+pub fn tune_event(config: Arc<Mutex<Config>>, event: ClickEvent, _args: Arc<Args>) -> bool {
+    if let Some(table) = shape_scores_table(&config.lock().unwrap(), &event) {
+        println!("{table}");
+    }
+    true
+}
+
+/// print the config name of the first pressed button (or wheel move) and exit
+pub fn which_button(_config: Arc<Mutex<Config>>, event: ClickEvent, _args: Arc<Args>) -> bool {
+    if event.event_type == event::EventType::Press
+        || matches!(event.button, MouseButton::WheelUp | MouseButton::WheelDown)
+    {
+        println!("{}", event.button.name());
+        exit(0);
+    }
+    true
+}
 
 /// Execute the command of the event if the corresponding binding is found.
 /// return false if the event must not be propagated
-pub fn process_event(config: Arc<Mutex<Config>>, event: ClickEvent, _args: Arc<Args>) -> bool {
+pub fn process_event(config: Arc<Mutex<Config>>, event: ClickEvent, args: Arc<Args>) -> bool {
+    process_event_with_runner(config, event, args, &PROCESS_RUNNER)
+}
+
+/// `process_event` with the commands executed by the runner
+pub fn process_event_with_runner(
+    config: Arc<Mutex<Config>>,
+    event: ClickEvent,
+    args: Arc<Args>,
+    runner: &Arc<dyn CommandRunner>,
+) -> bool {
     let mut propagate = true;
     let start = Instant::now();
-    let config_lock = config.lock().unwrap();
+    let now = event.time.unwrap_or_else(Instant::now);
+    {
+        let config = config.lock().unwrap();
+        if event.event_type == event::EventType::Release && stop_repeat(event.button) {
+            propagate = false;
+        }
+        if process_arm_gesture(&config, &event, now, runner) {
+            return false;
+        }
+    }
+    // only the predicate of the chosen binding runs, without the config lock : the binding
+    // is chosen again among the other candidates if its predicate fails
+    let mut when_results: Vec<(Vec<String>, bool)> = vec![];
+    let (config_lock, candidate_indexes, chosen_index) = loop {
+        let config_lock = config.lock().unwrap();
+        let (candidate_indexes, chosen_index, pending_when) = {
+            let config = config_lock.deref();
+            let mut candidates = filtered_candidates(config, &event, now);
+            candidates.retain(|binding| {
+                binding.when.is_empty() || when_result(&when_results, binding).unwrap_or(true)
+            });
+            let chosen = (!candidates.is_empty())
+                .then(|| {
+                    find_the_chosen_one_among_the_candidates(
+                        &candidates,
+                        &event,
+                        config.angle_weighting,
+                    )
+                })
+                .flatten();
+            let pending_when = chosen
+                .filter(|binding| {
+                    !binding.when.is_empty()
+                        && when_result(&when_results, binding).is_none()
+                        && fires_on(binding, &event)
+                        && is_confident_match(config, binding, &event)
+                })
+                .cloned();
+            let index = |binding: &Binding| {
+                config
+                    .bindings
+                    .iter()
+                    .position(|b| std::ptr::eq(b, binding))
+                    .unwrap()
+            };
+            (
+                candidates
+                    .iter()
+                    .map(|binding| index(binding))
+                    .collect::<Vec<_>>(),
+                chosen.map(index),
+                pending_when,
+            )
+        };
+        let Some(binding) = pending_when else {
+            break (config_lock, candidate_indexes, chosen_index);
+        };
+        drop(config_lock);
+        let holds = when_predicate_holds(&binding);
+        when_results.push((binding.when, holds));
+    };
     let config = config_lock.deref();
-    let candidates = find_candidates(config, &event);
+    let candidates: Vec<&Binding> = candidate_indexes
+        .iter()
+        .map(|index| &config.bindings[*index])
+        .collect();
     trace!("event={:?}", event);
     // trace!("candidates={:?}", candidates);
 
+    let mut matched = false;
     if !candidates.is_empty() {
         debug!("----------------------------------------");
-        if let Some(binding) = find_the_chosen_one_among_the_candidates(&candidates, &event) {
-            propagate = false;
-            if !(event.event_type == event::EventType::Release
-                && binding.event.event_type == event::EventType::Click
-                && binding.event.shapes_angles.is_empty())
-            {
-                process_cmd(binding.cmd.clone());
+        let chosen = chosen_index.map(|index| &config.bindings[index]);
+        if chosen.is_none() {
+            suggest_closest_shape(config, &candidates, &event);
+        }
+        // the press of the shape button is consumed by the grab : a borderline match
+        // falls through like no match, the release alone would reach the application
+        let chosen = chosen.filter(|binding| is_confident_match(config, binding, &event));
+        if let Some(binding) = chosen {
+            matched = true;
+            if args.emit_matches {
+                println!("{}", match_line(binding, &event));
+            }
+            propagate = binding.passthrough;
+            if let Some(action) = binding.action {
+                if runs_cmd_on(binding, &event) {
+                    run_action(binding, action, runner);
+                }
+            } else if let Some(cmd) = cmd_for_event(binding, &event) {
+                let cmd = prefixed_cmd(config, binding, cmd);
+                runner.run(binding, cmd.clone());
+                if let (event::EventType::Press, Some(repeat_ms)) =
+                    (binding.event.event_type, binding.repeat_ms)
+                {
+                    start_repeat(
+                        event.button,
+                        binding.clone(),
+                        cmd,
+                        repeat_ms,
+                        runner.clone(),
+                    );
+                }
             }
         } else if event.event_type == event::EventType::Release
             && event.button == config.shape_button
@@ -244,7 +726,10 @@ pub fn process_event(config: Arc<Mutex<Config>>, event: ClickEvent, _args: Arc<A
             let rdev_btn = config.shape_button.to_rdev_event();
 
             debug!("simulate");
-            debug!("event shape_angles: {}", event.shapes_angles.first().unwrap().len());
+            debug!(
+                "event shape_angles: {}",
+                event.shapes_angles.first().map_or(0, |angles| angles.len())
+            );
             /*simulate(&EventType::ButtonPress(rdev_btn))
                 .map_err(|err| error!("simulate err: {:?}", err))
                 .ok();
@@ -254,17 +739,21 @@ pub fn process_event(config: Arc<Mutex<Config>>, event: ClickEvent, _args: Arc<A
                 .ok(); */
             //if(rdev_btn == Button::Extra){
             let code = match rdev_btn {
-                Button::Left   => 272,
-                Button::Right  => 273,
+                Button::Left => 272,
+                Button::Right => 273,
                 Button::Middle => 274,
-                Button::Side   => 275,
-                Button::Extra  => 276,
-                Button::Forward=> 277,
-                Button::Back   => 278,
-                Button::Task   => 279,
-                _ => 274
+                Button::Side => 275,
+                Button::Extra => 276,
+                Button::Forward => 277,
+                Button::Back => 278,
+                Button::Task => 279,
+                _ => 274,
             };
-            process_cmd(vec!["play-event.py".to_string(),"--code".to_string(), code.to_string()]);
+            runner.replay_click(vec![
+                "play-event.py".to_string(),
+                "--code".to_string(),
+                code.to_string(),
+            ]);
             debug!("button attemped={:?}", rdev_btn);
             //} else if(rdev_btn == Button::Right){
             //    process_cmd(vec!["libinput".to_string(),"replay".to_string(),"--once".to_string(),"--replay-after".to_string(),"0".to_string(),"/home/spaceslug/Projects/mouse-actions/right-button.yaml".to_string()]);
@@ -274,6 +763,13 @@ pub fn process_event(config: Arc<Mutex<Config>>, event: ClickEvent, _args: Arc<A
             //}
         }
     }
+    if event
+        .shapes_xy
+        .first()
+        .is_some_and(|points| is_shape(points, config.shape_min_points))
+    {
+        sound::gesture_feedback(config, matched);
+    }
     trace!("propagate = {propagate}");
     if !propagate {
         debug!("Process event duration : {:?}", start.elapsed());
@@ -281,6 +777,113 @@ pub fn process_event(config: Arc<Mutex<Config>>, event: ClickEvent, _args: Arc<A
     propagate
 }
 
+/// the candidates of the event that pass the conditions of their binding, before the
+/// shape matching and the `when` predicates
+fn filtered_candidates<'a>(
+    config: &'a Config,
+    event: &ClickEvent,
+    now: Instant,
+) -> Vec<&'a Binding> {
+    let mut candidates = find_candidates(config, event);
+    candidates.retain(|binding| !binding.arm_required || is_armed(binding, now));
+    candidates.retain(|binding| is_idle_enough(binding, event));
+    candidates.retain(|binding| is_length_in_range(binding, event));
+    candidates.retain(|binding| {
+        binding
+            .workspace
+            .is_none_or(|workspace| focus::is_on_workspace(workspace, now))
+    });
+    if candidates
+        .iter()
+        .any(|binding| binding.active_hours.is_some())
+    {
+        let minute_of_day = schedule::local_minute_of_day();
+        candidates.retain(|binding| {
+            binding
+                .active_hours
+                .as_ref()
+                .is_none_or(|hours| schedule::is_in_active_hours(hours, minute_of_day))
+        });
+    }
+    if is_straight_drag(config, event) {
+        debug!(
+            "less than {} direction changes : straight drag, skip the shape bindings",
+            config.min_direction_changes
+        );
+        candidates.retain(|binding| binding.event.shapes_angles.is_empty());
+    }
+    candidates
+}
+
+/// the result of the `when` predicate of the binding, if it already ran for this event :
+/// the bindings with the same predicate share its result
+fn when_result(when_results: &[(Vec<String>, bool)], binding: &Binding) -> Option<bool> {
+    when_results
+        .iter()
+        .find(|(when, _)| *when == binding.when)
+        .map(|(_, holds)| *holds)
+}
+
+/// Run the `when` predicate of the binding and wait for it : false if it fails or
+/// doesn't exit before the timeout, true if the binding has no predicate.
+pub fn when_predicate_holds(binding: &Binding) -> bool {
+    if binding.when.is_empty() {
+        return true;
+    }
+    let start = Instant::now();
+    let mut child = match Command::new(&binding.when[0])
+        .env_remove("RUST_LOG")
+        .args(&binding.when[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) => {
+            info!(
+                "{} skip : can't run the when predicate {:?} : {err}",
+                binding.log_fields(),
+                binding.when
+            );
+            return false;
+        }
+    };
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                if !status.success() {
+                    info!(
+                        "{} skip : the when predicate {:?} failed ({status})",
+                        binding.log_fields(),
+                        binding.when
+                    );
+                }
+                return status.success();
+            }
+            Ok(None) if start.elapsed() < WHEN_TIMEOUT => thread::sleep(Duration::from_millis(5)),
+            Ok(None) => {
+                info!(
+                    "{} skip : the when predicate {:?} timed out after {WHEN_TIMEOUT:?}",
+                    binding.log_fields(),
+                    binding.when
+                );
+                let _ = child.kill();
+                let _ = child.wait();
+                return false;
+            }
+            Err(err) => {
+                info!(
+                    "{} skip : the when predicate {:?} failed : {err}",
+                    binding.log_fields(),
+                    binding.when
+                );
+                return false;
+            }
+        }
+    }
+}
+
 #[cfg(unix)]
 pub fn process_cmd(cmd: Vec<String>) {
     thread::Builder::new()
@@ -298,6 +901,150 @@ pub fn process_cmd(cmd: Vec<String>) {
         .unwrap();
 }
 
+/// the message of the notification shown when the command of the binding starts,
+/// `{comment}` is replaced by the binding comment, an empty message is the comment
+pub fn notification_message(binding: &Binding) -> Option<String> {
+    binding.notify.as_ref().map(|message| {
+        if message.is_empty() {
+            binding.comment.clone()
+        } else {
+            message.replace("{comment}", &binding.comment)
+        }
+    })
+}
+
+#[cfg(feature = "notifications")]
+fn notify_spawned(message: Option<String>) {
+    if let Some(message) = message {
+        notify::notify(&message);
+    }
+}
+
+#[cfg(not(feature = "notifications"))]
+fn notify_spawned(message: Option<String>) {
+    if let Some(message) = message {
+        trace!("built without the notifications feature, skip the notification {message:?}");
+    }
+}
+
+/// Execute a command of the binding in a new thread : the command output is
+/// captured and logged if the command fails, or discarded if the binding is quiet.
+#[cfg(unix)]
+pub fn run_binding_cmd(binding: &Binding, cmd: Vec<String>) {
+    let fields = binding.log_fields();
+    let quiet = binding.quiet;
+    let log = binding.log;
+    let notification = notification_message(binding);
+    let detach = binding.detach;
+    let limits = binding.limits.clone();
+    thread::Builder::new()
+        .name("process_cmd".to_string())
+        .spawn(move || {
+            if log {
+                info!("{fields} → cmd {:?}", cmd);
+            }
+            let mut command = Command::new(&cmd[0]);
+            command.env_remove("RUST_LOG").args(&cmd[1..]);
+            if let Some(limits) = &limits {
+                limits.apply(&mut command);
+            }
+            if detach {
+                // a new session : the command survives the daemon and its terminal
+                command
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null());
+                // SAFETY: setsid is async-signal-safe
+                unsafe {
+                    command.pre_exec(|| {
+                        rustix::process::setsid()
+                            .map(|_| ())
+                            .map_err(io::Error::from)
+                    });
+                }
+                let res = command.spawn();
+                if res.is_ok() {
+                    notify_spawned(notification);
+                }
+                let res = res.and_then(|mut child| child.wait());
+                trace!("{fields} detached cmd {cmd:?} result : {res:?}");
+                return;
+            }
+            command.process_group(0);
+            if quiet {
+                command.stdout(Stdio::null()).stderr(Stdio::null());
+            } else {
+                command.stdout(Stdio::piped()).stderr(Stdio::piped());
+            }
+            let child = command.spawn();
+            let pid = child.as_ref().ok().map(Child::id);
+            if let Some(pid) = pid {
+                notify_spawned(notification);
+                spawned::track(pid);
+            }
+            if quiet {
+                let res = child.and_then(|mut child| child.wait());
+                trace!("{fields} cmd {cmd:?} result : {res:?}");
+            } else {
+                match child.and_then(wait_with_output_tail) {
+                    Ok((status, stdout, stderr)) if !status.success() => warn!(
+                        "{fields} cmd {cmd:?} failed ({status}) :\n  stdout: {}\n  stderr: {}",
+                        String::from_utf8_lossy(&stdout).trim_end(),
+                        String::from_utf8_lossy(&stderr).trim_end()
+                    ),
+                    Ok((status, _, _)) => trace!("{fields} cmd {cmd:?} : {status}"),
+                    Err(err) => error!("{fields} cmd {cmd:?} spawn error : {err}"),
+                }
+            }
+            if let Some(pid) = pid {
+                spawned::untrack(pid);
+            }
+        })
+        .unwrap();
+}
+
+/// the last max_len bytes read until the end of the reader : a long running command
+/// (e.g. a GUI application) doesn't fill the memory with its output
+pub fn read_tail(mut reader: impl Read, max_len: usize) -> Vec<u8> {
+    let mut tail = vec![];
+    let mut buffer = [0; 4096];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(len) => {
+                tail.extend_from_slice(&buffer[..len]);
+                if tail.len() > max_len {
+                    tail.drain(..tail.len() - max_len);
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
+    tail
+}
+
+/// wait for the child and keep the end of its piped stdout and stderr
+fn wait_with_output_tail(mut child: Child) -> io::Result<(ExitStatus, Vec<u8>, Vec<u8>)> {
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let stderr_tail = thread::spawn(move || {
+        stderr
+            .map(|stderr| read_tail(stderr, CMD_OUTPUT_TAIL))
+            .unwrap_or_default()
+    });
+    let stdout_tail = stdout
+        .map(|stdout| read_tail(stdout, CMD_OUTPUT_TAIL))
+        .unwrap_or_default();
+    let stderr_tail = stderr_tail.join().unwrap_or_default();
+    Ok((child.wait()?, stdout_tail, stderr_tail))
+}
+
+#[cfg(windows)]
+pub fn run_binding_cmd(_binding: &Binding, cmd: Vec<String>) {
+    process_cmd(cmd);
+}
+
 #[cfg(windows)]
 fn process_cmd(cmd: Vec<String>) {
     thread::spawn(move || {
@@ -319,3 +1066,1107 @@ fn process_cmd(cmd: Vec<String>) {
     })
     .unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    use rdev::Key;
+
+    use crate::builder::BindingBuilder;
+    use crate::event::{
+        Edge, EventType, KeyboardModifier, MouseButton, Point, PointHistory, Quadrant, Region,
+    };
+    use std::sync::{Arc, Mutex};
+
+    use clap::Parser;
+
+    use crate::args::Args;
+    use crate::binding::{Action, ArmGesture, Binding};
+    use crate::builder::ConfigBuilder;
+    use crate::clock::{Clock, MockClock};
+    use crate::compare_angles::AngleWeighting;
+    use crate::event::ClickEvent;
+    use crate::process_event::{
+        closest_shape_suggestion, cmd_for_event,
+        find_the_chosen_one_among_the_candidates_with_shape,
+        find_the_chosen_one_among_the_candidates_without_shape, is_candidate, is_length_in_range,
+        match_line, notification_message, prefixed_cmd, process_event, process_event_with_runner,
+        read_tail, run_binding_cmd, shape_scores_table, wait_with_output_tail,
+        when_predicate_holds, CMD_OUTPUT_TAIL,
+    };
+    use crate::repeat::REPEATS_TEST_LOCK;
+    use crate::runner::{CommandRunner, RecordingRunner};
+    use crate::test_shapes::{shape_points, Shape};
+
+    fn l_shape() -> PointHistory {
+        let mut points = PointHistory::new();
+        (0..20).for_each(|i| points.push(Point { x: 0, y: i * 10 }));
+        (0..20).for_each(|i| points.push(Point { x: i * 10, y: 200 }));
+        points
+    }
+
+    #[test]
+    fn test_priority_without_shape() {
+        let first = BindingBuilder::new(MouseButton::Middle)
+            .comment("first")
+            .build();
+        let second = BindingBuilder::new(MouseButton::Middle)
+            .comment("second")
+            .build();
+        let event = first.event.clone();
+        // the config order
+        let candidates = vec![&first, &second];
+        let chosen = find_the_chosen_one_among_the_candidates_without_shape(&candidates, &event);
+        assert_eq!(chosen.unwrap().comment, "first");
+        let candidates = vec![&second, &first];
+        let chosen = find_the_chosen_one_among_the_candidates_without_shape(&candidates, &event);
+        assert_eq!(chosen.unwrap().comment, "second");
+
+        // the priority overrides the config order
+        let prioritized = BindingBuilder::new(MouseButton::Middle)
+            .comment("prioritized")
+            .priority(1)
+            .build();
+        let candidates = vec![&first, &second, &prioritized];
+        let chosen = find_the_chosen_one_among_the_candidates_without_shape(&candidates, &event);
+        assert_eq!(chosen.unwrap().comment, "prioritized");
+        assert!(find_the_chosen_one_among_the_candidates_without_shape(&[], &event).is_none());
+    }
+
+    #[test]
+    fn test_priority_with_shape() {
+        let mut event = BindingBuilder::new(MouseButton::Right)
+            .shape(l_shape())
+            .build()
+            .event;
+        event.event_type = EventType::Release;
+        let binding = |comment: &str, priority: i32| {
+            BindingBuilder::new(MouseButton::Right)
+                .shape(l_shape())
+                .comment(comment)
+                .priority(priority)
+                .build()
+        };
+
+        // the same shape twice : too close, nothing is chosen
+        let (first, second) = (binding("first", 0), binding("second", 0));
+        let candidates = vec![&first, &second];
+        assert!(find_the_chosen_one_among_the_candidates_with_shape(
+            &candidates,
+            &event,
+            AngleWeighting::Uniform
+        )
+        .is_none());
+
+        // the priority decides
+        let prioritized = binding("prioritized", 1);
+        let candidates = vec![&first, &prioritized, &second];
+        let chosen = find_the_chosen_one_among_the_candidates_with_shape(
+            &candidates,
+            &event,
+            AngleWeighting::Uniform,
+        );
+        assert_eq!(chosen.unwrap().comment, "prioritized");
+
+        // a prioritized binding over its diff_max doesn't win
+        let mut strict = binding("strict", 2);
+        strict.diff_max = Some(0.0);
+        let candidates = vec![&strict, &prioritized];
+        let chosen = find_the_chosen_one_among_the_candidates_with_shape(
+            &candidates,
+            &event,
+            AngleWeighting::Uniform,
+        );
+        assert_eq!(chosen.unwrap().comment, "prioritized");
+    }
+
+    #[test]
+    fn test_diff_max() {
+        let mut event = BindingBuilder::new(MouseButton::Right)
+            .shape(l_shape())
+            .build()
+            .event;
+        event.event_type = EventType::Release;
+
+        let binding = BindingBuilder::new(MouseButton::Right)
+            .shape(l_shape())
+            .build();
+        let candidates = vec![&binding];
+        assert!(find_the_chosen_one_among_the_candidates_with_shape(
+            &candidates,
+            &event,
+            AngleWeighting::Uniform
+        )
+        .is_some());
+
+        let strict_binding = BindingBuilder::new(MouseButton::Right)
+            .shape(l_shape())
+            .diff_max(0.0)
+            .build();
+        let candidates = vec![&strict_binding];
+        assert!(find_the_chosen_one_among_the_candidates_with_shape(
+            &candidates,
+            &event,
+            AngleWeighting::Uniform
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_bidirectional() {
+        let line = |reversed: bool| {
+            let mut points = PointHistory::new();
+            (0..40).for_each(|i| points.push(Point { x: i * 10, y: 0 }));
+            if reversed {
+                points.reverse();
+            }
+            points
+        };
+        let mut event = BindingBuilder::new(MouseButton::Right)
+            .shape(line(true))
+            .build()
+            .event;
+        event.event_type = EventType::Release;
+
+        let binding = BindingBuilder::new(MouseButton::Right)
+            .shape(line(false))
+            .build();
+        let candidates = vec![&binding];
+        assert!(find_the_chosen_one_among_the_candidates_with_shape(
+            &candidates,
+            &event,
+            AngleWeighting::Uniform
+        )
+        .is_none());
+
+        let bidirectional_binding = BindingBuilder::new(MouseButton::Right)
+            .shape(line(false))
+            .bidirectional(true)
+            .build();
+        let candidates = vec![&bidirectional_binding];
+        assert!(find_the_chosen_one_among_the_candidates_with_shape(
+            &candidates,
+            &event,
+            AngleWeighting::Uniform
+        )
+        .is_some());
+        event.shapes_angles = bidirectional_binding.event.shapes_angles.clone();
+        assert!(find_the_chosen_one_among_the_candidates_with_shape(
+            &candidates,
+            &event,
+            AngleWeighting::Uniform
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_closest_shape_suggestion() {
+        let mut event = BindingBuilder::new(MouseButton::Right)
+            .shape(l_shape())
+            .build()
+            .event;
+        event.event_type = EventType::Release;
+
+        let strict_binding = BindingBuilder::new(MouseButton::Right)
+            .comment("strict L")
+            .shape(l_shape())
+            .diff_max(0.0)
+            .build();
+        let candidates = vec![&strict_binding];
+        assert!(find_the_chosen_one_among_the_candidates_with_shape(
+            &candidates,
+            &event,
+            AngleWeighting::Uniform
+        )
+        .is_none());
+        let suggestion =
+            closest_shape_suggestion(&candidates, &event, AngleWeighting::Uniform).unwrap();
+        assert!(
+            suggestion.contains("did you mean \"strict L\" ?"),
+            "{suggestion}"
+        );
+        assert!(suggestion.contains("diff_max 0.00"), "{suggestion}");
+
+        event.shapes_angles = vec![];
+        assert!(closest_shape_suggestion(&candidates, &event, AngleWeighting::Uniform).is_none());
+    }
+
+    #[test]
+    fn test_shape_button_held() {
+        let wheel = BindingBuilder::new(MouseButton::WheelUp).build();
+        let chord = BindingBuilder::new(MouseButton::WheelUp)
+            .shape_button_held(true)
+            .build();
+        let mut event = ClickEvent {
+            event_type: EventType::Release,
+            ..BindingBuilder::new(MouseButton::WheelUp).build().event
+        };
+        assert!(is_candidate(&MouseButton::Right, &wheel, &event));
+        assert!(!is_candidate(&MouseButton::Right, &chord, &event));
+
+        event.shape_button_held = true;
+        assert!(!is_candidate(&MouseButton::Right, &wheel, &event));
+        assert!(is_candidate(&MouseButton::Right, &chord, &event));
+    }
+
+    #[test]
+    fn test_notification_message() {
+        let binding = |notify: Option<&str>| {
+            let mut binding = BindingBuilder::new(MouseButton::Task)
+                .comment("backup")
+                .build();
+            binding.notify = notify.map(str::to_string);
+            binding
+        };
+        assert_eq!(notification_message(&binding(None)), None);
+        assert_eq!(
+            notification_message(&binding(Some(""))),
+            Some("backup".to_string())
+        );
+        assert_eq!(
+            notification_message(&binding(Some("{comment} started"))),
+            Some("backup started".to_string())
+        );
+    }
+
+    #[test]
+    fn test_detach() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("session");
+        let binding = BindingBuilder::new(MouseButton::Task).detach(true).build();
+        // the session id of a detached command is its own pid
+        let script = format!(
+            "read -r pid comm state ppid pgrp sid rest < /proc/$$/stat; echo \"$sid $pid\" > {}",
+            output.display()
+        );
+        run_binding_cmd(&binding, vec!["sh".to_string(), "-c".to_string(), script]);
+
+        let start = Instant::now();
+        while !output.exists() && start.elapsed() < Duration::from_secs(2) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+        let session = std::fs::read_to_string(&output).unwrap();
+        let ids: Vec<&str> = session.split_whitespace().collect();
+        assert_eq!(ids.len(), 2, "{session}");
+        assert_eq!(ids[0], ids[1]);
+    }
+
+    #[test]
+    fn test_prefixed_cmd() {
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .command_prefix(vec!["systemd-run".to_string(), "--user".to_string()])
+            .build();
+        let binding = BindingBuilder::new(MouseButton::Task)
+            .cmd_str("firefox")
+            .build();
+        assert_eq!(
+            prefixed_cmd(&config, &binding, binding.cmd.clone()),
+            vec!["systemd-run", "--user", "firefox"]
+        );
+
+        let opt_out = BindingBuilder::new(MouseButton::Task)
+            .cmd_str("firefox")
+            .no_command_prefix(true)
+            .build();
+        assert_eq!(
+            prefixed_cmd(&config, &opt_out, opt_out.cmd.clone()),
+            vec!["firefox"]
+        );
+    }
+
+    #[test]
+    fn test_closed() {
+        let binding = |closed: Option<bool>| {
+            let mut binding = BindingBuilder::new(MouseButton::Right)
+                .shape(l_shape())
+                .build();
+            binding.event.closed = closed;
+            binding
+        };
+        let mut event = ClickEvent {
+            event_type: EventType::Release,
+            closed: Some(false),
+            ..binding(None).event
+        };
+        assert!(is_candidate(&MouseButton::Right, &binding(None), &event));
+        assert!(is_candidate(
+            &MouseButton::Right,
+            &binding(Some(false)),
+            &event
+        ));
+        assert!(!is_candidate(
+            &MouseButton::Right,
+            &binding(Some(true)),
+            &event
+        ));
+        event.closed = Some(true);
+        assert!(is_candidate(
+            &MouseButton::Right,
+            &binding(Some(true)),
+            &event
+        ));
+    }
+
+    #[test]
+    fn test_location_sensitive() {
+        let binding = BindingBuilder::new(MouseButton::Right)
+            .shape(l_shape())
+            .location_sensitive(Region::TopRight)
+            .build();
+        let mut event = ClickEvent {
+            event_type: EventType::Release,
+            ..binding.event.clone()
+        };
+        assert!(is_candidate(&MouseButton::Right, &binding, &event));
+        event.region = Some(Region::Center);
+        assert!(!is_candidate(&MouseButton::Right, &binding, &event));
+
+        // the other bindings match in any region
+        let binding = BindingBuilder::new(MouseButton::Right)
+            .shape(l_shape())
+            .build();
+        assert!(is_candidate(&MouseButton::Right, &binding, &event));
+    }
+
+    #[test]
+    fn test_length_range() {
+        let short = BindingBuilder::new(MouseButton::Right)
+            .event_type(EventType::Release)
+            .length_range(None, Some(200))
+            .build();
+        let long = BindingBuilder::new(MouseButton::Right)
+            .event_type(EventType::Release)
+            .length_range(Some(200), None)
+            .build();
+        let drag = |length| ClickEvent {
+            length,
+            ..short.event.clone()
+        };
+        assert!(is_length_in_range(&short, &drag(Some(50))));
+        assert!(!is_length_in_range(&long, &drag(Some(50))));
+        assert!(!is_length_in_range(&short, &drag(Some(600))));
+        assert!(is_length_in_range(&long, &drag(Some(600))));
+        // the bounds are included
+        assert!(is_length_in_range(&short, &drag(Some(200))));
+        assert!(is_length_in_range(&long, &drag(Some(200))));
+        assert!(!is_length_in_range(&short, &drag(None)));
+
+        let any = BindingBuilder::new(MouseButton::Right).build();
+        assert!(is_length_in_range(&any, &drag(None)));
+    }
+
+    #[test]
+    fn test_keys() {
+        let binding = BindingBuilder::new(MouseButton::Right)
+            .event_type(EventType::Press)
+            .keys(vec![Key::KeyG])
+            .build();
+        let plain = BindingBuilder::new(MouseButton::Right)
+            .event_type(EventType::Press)
+            .build();
+        let event = |keys| ClickEvent {
+            keys,
+            ..plain.event.clone()
+        };
+        assert!(is_candidate(
+            &MouseButton::Left,
+            &binding,
+            &event(vec![Key::KeyG])
+        ));
+        assert!(!is_candidate(
+            &MouseButton::Left,
+            &plain,
+            &event(vec![Key::KeyG])
+        ));
+        assert!(!is_candidate(&MouseButton::Left, &binding, &event(vec![])));
+        assert!(!is_candidate(
+            &MouseButton::Left,
+            &binding,
+            &event(vec![Key::KeyG, Key::KeyH])
+        ));
+        assert!(is_candidate(&MouseButton::Left, &plain, &event(vec![])));
+
+        let serialized = serde_json::to_string(&binding.event).unwrap();
+        assert!(serialized.contains(r#""keys":["KeyG"]"#), "{serialized}");
+    }
+
+    #[test]
+    fn test_held_buttons() {
+        let binding = BindingBuilder::new(MouseButton::Right)
+            .event_type(EventType::Release)
+            .held_buttons(vec![MouseButton::Back])
+            .build();
+        let mut event = ClickEvent {
+            held_buttons: vec![MouseButton::Back],
+            ..binding.event.clone()
+        };
+        assert!(is_candidate(&MouseButton::Right, &binding, &event));
+        event.held_buttons = vec![];
+        assert!(!is_candidate(&MouseButton::Right, &binding, &event));
+        event.held_buttons = vec![MouseButton::Back, MouseButton::Side];
+        assert!(!is_candidate(&MouseButton::Right, &binding, &event));
+
+        let plain = BindingBuilder::new(MouseButton::Right)
+            .event_type(EventType::Release)
+            .build();
+        assert!(!is_candidate(
+            &MouseButton::Right,
+            &plain,
+            &ClickEvent {
+                held_buttons: vec![MouseButton::Back],
+                ..plain.event.clone()
+            }
+        ));
+    }
+
+    #[test]
+    fn test_device() {
+        let binding = BindingBuilder::new(MouseButton::Middle)
+            .device("Wacom Intuos S Pen")
+            .build();
+        let mut event = ClickEvent {
+            device: Some("Wacom Intuos S Pen".to_string()),
+            ..binding.event.clone()
+        };
+        assert!(is_candidate(&MouseButton::Right, &binding, &event));
+        event.device = Some("Logitech USB Optical Mouse".to_string());
+        assert!(!is_candidate(&MouseButton::Right, &binding, &event));
+        // the device is unknown, e.g. the events of the listen thread
+        event.device = None;
+        assert!(is_candidate(&MouseButton::Right, &binding, &event));
+
+        let any_device = BindingBuilder::new(MouseButton::Middle).build();
+        event.device = Some("Logitech USB Optical Mouse".to_string());
+        assert!(is_candidate(&MouseButton::Right, &any_device, &event));
+    }
+
+    #[test]
+    fn test_edges_forbidden() {
+        let binding = BindingBuilder::new(MouseButton::Right)
+            .shape(l_shape())
+            .edges_forbidden(vec![Edge::Top, Edge::Left])
+            .build();
+        let mut event = ClickEvent {
+            event_type: EventType::Release,
+            edges_forbidden: vec![],
+            ..binding.event.clone()
+        };
+        assert!(is_candidate(&MouseButton::Right, &binding, &event));
+        event.edges = vec![Edge::Bottom];
+        assert!(is_candidate(&MouseButton::Right, &binding, &event));
+        event.edges = vec![Edge::Left, Edge::Bottom];
+        assert!(!is_candidate(&MouseButton::Right, &binding, &event));
+    }
+
+    #[test]
+    fn test_buttons() {
+        let binding: Binding = serde_json::from_str(
+            r#"{ "event": { "buttons": ["Middle", "Side"] }, "cmd_str": "notify-send both" }"#,
+        )
+        .unwrap();
+        assert_eq!(binding.event.button, MouseButton::None);
+        let event = |button: MouseButton| ClickEvent {
+            event_type: EventType::Press,
+            ..BindingBuilder::new(button).build().event
+        };
+        assert!(is_candidate(
+            &MouseButton::Right,
+            &binding,
+            &event(MouseButton::Middle)
+        ));
+        assert!(is_candidate(
+            &MouseButton::Right,
+            &binding,
+            &event(MouseButton::Side)
+        ));
+        assert!(!is_candidate(
+            &MouseButton::Right,
+            &binding,
+            &event(MouseButton::Extra)
+        ));
+
+        let serialized = serde_json::to_string(&binding).unwrap();
+        assert!(
+            serialized.contains(r#""event":{"buttons":["Middle","Side"],"#),
+            "{serialized}"
+        );
+    }
+
+    #[test]
+    fn test_process_event_with_runner() {
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .command_prefix(vec!["systemd-run".to_string()])
+            .binding(
+                BindingBuilder::new(MouseButton::Task)
+                    .comment("task")
+                    .cmd_str("notify-send task")
+                    .build(),
+            )
+            .build();
+        let config = Arc::new(Mutex::new(config));
+        let args = Arc::new(Args::parse_from(["mouse-actions"]));
+        let recording = Arc::new(RecordingRunner::default());
+        let runner: Arc<dyn CommandRunner> = recording.clone();
+        let event = |button: MouseButton| ClickEvent {
+            event_type: EventType::Press,
+            ..BindingBuilder::new(button).build().event
+        };
+
+        assert!(!process_event_with_runner(
+            config.clone(),
+            event(MouseButton::Task),
+            args.clone(),
+            &runner
+        ));
+        assert!(process_event_with_runner(
+            config,
+            event(MouseButton::Extra),
+            args,
+            &runner
+        ));
+        assert_eq!(
+            recording.runs(),
+            vec![(
+                "task".to_string(),
+                vec![
+                    "systemd-run".to_string(),
+                    "notify-send".to_string(),
+                    "task".to_string()
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_action() {
+        // KillSpawned stops all the repeats
+        let _lock = REPEATS_TEST_LOCK.lock().unwrap();
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .binding(
+                BindingBuilder::new(MouseButton::Task)
+                    .comment("panic")
+                    .cmd_str("notify-send task")
+                    .action(Action::KillSpawned)
+                    .build(),
+            )
+            .build();
+        let recording = Arc::new(RecordingRunner::default());
+        let runner: Arc<dyn CommandRunner> = recording.clone();
+        let event = ClickEvent {
+            event_type: EventType::Press,
+            ..BindingBuilder::new(MouseButton::Task).build().event
+        };
+        assert!(!process_event_with_runner(
+            Arc::new(Mutex::new(config)),
+            event,
+            Arc::new(Args::parse_from(["mouse-actions"])),
+            &runner
+        ));
+        // the action is done instead of the command
+        assert!(recording.runs().is_empty());
+        assert_eq!(recording.kills(), 1);
+    }
+
+    #[test]
+    fn test_when_predicate() {
+        let when_binding = |when: &[&str]| {
+            BindingBuilder::new(MouseButton::Middle)
+                .when(when.iter().map(|arg| arg.to_string()).collect())
+                .build()
+        };
+        assert!(when_predicate_holds(&when_binding(&[])));
+        assert!(when_predicate_holds(&when_binding(&["true"])));
+        assert!(!when_predicate_holds(&when_binding(&["false"])));
+        assert!(!when_predicate_holds(&when_binding(&["no-such-predicate"])));
+
+        let start = Instant::now();
+        assert!(!when_predicate_holds(&when_binding(&["sleep", "5"])));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_when_predicate_of_the_chosen_binding() {
+        let dir = tempfile::tempdir().unwrap();
+        let touched = dir.path().join("touched");
+        let binding = |comment: &str, priority: i32, when: Vec<String>| {
+            BindingBuilder::new(MouseButton::Middle)
+                .comment(comment)
+                .priority(priority)
+                .when(when)
+                .cmd_str(&format!("echo {priority}"))
+                .build()
+        };
+        let config = Arc::new(Mutex::new(
+            ConfigBuilder::new(MouseButton::Right)
+                .binding(binding("failing", 2, vec!["false".to_string()]))
+                .binding(binding("plain", 1, vec![]))
+                .binding(binding(
+                    "not chosen",
+                    0,
+                    vec!["touch".to_string(), touched.display().to_string()],
+                ))
+                .build(),
+        ));
+        let event = ClickEvent {
+            event_type: EventType::Press,
+            ..BindingBuilder::new(MouseButton::Middle).build().event
+        };
+        let recording = Arc::new(RecordingRunner::default());
+        let runner: Arc<dyn CommandRunner> = recording.clone();
+        process_event_with_runner(
+            config,
+            event,
+            Arc::new(Args::parse_from(["mouse-actions"])),
+            &runner,
+        );
+        // the failing predicate falls back to the next binding, the last one isn't evaluated
+        assert_eq!(
+            recording.runs(),
+            vec![(
+                "plain".to_string(),
+                vec!["echo".to_string(), "1".to_string()]
+            )]
+        );
+        assert!(!touched.exists());
+    }
+
+    #[test]
+    fn test_when_predicate_of_a_click() {
+        let dir = tempfile::tempdir().unwrap();
+        let evaluations = dir.path().join("evaluations");
+        let when = format!("echo when >> {}", evaluations.display());
+        let config = Arc::new(Mutex::new(
+            ConfigBuilder::new(MouseButton::Right)
+                .binding(
+                    BindingBuilder::new(MouseButton::Middle)
+                        .when(vec!["sh".to_string(), "-c".to_string(), when])
+                        .cmd_str("true")
+                        .build(),
+                )
+                .build(),
+        ));
+        let recording = Arc::new(RecordingRunner::default());
+        let runner: Arc<dyn CommandRunner> = recording.clone();
+        for event_type in [EventType::Press, EventType::Release] {
+            let event = ClickEvent {
+                event_type,
+                ..BindingBuilder::new(MouseButton::Middle).build().event
+            };
+            process_event_with_runner(
+                config.clone(),
+                event,
+                Arc::new(Args::parse_from(["mouse-actions"])),
+                &runner,
+            );
+        }
+        // the release doesn't run the command : the predicate is evaluated once
+        assert_eq!(recording.runs().len(), 1);
+        assert_eq!(fs::read_to_string(&evaluations).unwrap(), "when\n");
+    }
+
+    #[test]
+    fn test_when_predicates_of_the_same_command() {
+        let binding = |comment: &str, priority: i32, when: &str| {
+            BindingBuilder::new(MouseButton::Middle)
+                .comment(comment)
+                .priority(priority)
+                .when(vec![when.to_string()])
+                .cmd_str("true")
+                .build()
+        };
+        // the same event and command : the same derived id, but not the same predicate
+        let config = Arc::new(Mutex::new(
+            ConfigBuilder::new(MouseButton::Right)
+                .binding(binding("failing", 1, "false"))
+                .binding(binding("holding", 0, "true"))
+                .build(),
+        ));
+        let event = ClickEvent {
+            event_type: EventType::Press,
+            ..BindingBuilder::new(MouseButton::Middle).build().event
+        };
+        let recording = Arc::new(RecordingRunner::default());
+        let runner: Arc<dyn CommandRunner> = recording.clone();
+        process_event_with_runner(
+            config,
+            event,
+            Arc::new(Args::parse_from(["mouse-actions"])),
+            &runner,
+        );
+        assert_eq!(
+            recording.runs(),
+            vec![("holding".to_string(), vec!["true".to_string()])]
+        );
+    }
+
+    #[test]
+    fn test_arm_gesture() {
+        let arm = ArmGesture {
+            binding: BindingBuilder::new(MouseButton::Task).build(),
+            duration_ms: 1000,
+        };
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .binding(
+                BindingBuilder::new(MouseButton::Thumb2)
+                    .event_type(EventType::Press)
+                    .arm_required(true)
+                    .cmd_str("true")
+                    .build(),
+            )
+            .arm(arm)
+            .build();
+        let config = Arc::new(Mutex::new(config));
+        let args = Arc::new(Args::parse_from(["mouse-actions"]));
+        let clock = MockClock::new();
+        let press = |button: MouseButton| ClickEvent {
+            event_type: EventType::Press,
+            time: Some(clock.now()),
+            ..BindingBuilder::new(button).build().event
+        };
+
+        // not armed : the event is propagated
+        assert!(process_event(
+            config.clone(),
+            press(MouseButton::Thumb2),
+            args.clone()
+        ));
+
+        assert!(!process_event(
+            config.clone(),
+            press(MouseButton::Task),
+            args.clone()
+        ));
+        clock.advance(Duration::from_millis(500));
+        assert!(!process_event(
+            config.clone(),
+            press(MouseButton::Thumb2),
+            args.clone()
+        ));
+
+        clock.advance(Duration::from_millis(1000));
+        assert!(process_event(
+            config.clone(),
+            press(MouseButton::Thumb2),
+            args.clone()
+        ));
+    }
+
+    #[test]
+    fn test_passthrough() {
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .binding(
+                BindingBuilder::new(MouseButton::Forward)
+                    .cmd_str("true")
+                    .build(),
+            )
+            .binding(
+                BindingBuilder::new(MouseButton::Back)
+                    .cmd_str("true")
+                    .passthrough(true)
+                    .build(),
+            )
+            .build();
+        let config = Arc::new(Mutex::new(config));
+        let args = Arc::new(Args::parse_from(["mouse-actions"]));
+        for event_type in [EventType::Press, EventType::Release] {
+            let event = |button: MouseButton| ClickEvent {
+                event_type,
+                ..BindingBuilder::new(button).build().event
+            };
+            assert!(!process_event(
+                config.clone(),
+                event(MouseButton::Forward),
+                args.clone()
+            ));
+            assert!(process_event(
+                config.clone(),
+                event(MouseButton::Back),
+                args.clone()
+            ));
+        }
+    }
+
+    #[test]
+    fn test_wheel_passthrough() {
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .binding(
+                BindingBuilder::new(MouseButton::WheelUp)
+                    .event_type(EventType::Release)
+                    .cmd_str("true")
+                    .build(),
+            )
+            .binding(
+                BindingBuilder::new(MouseButton::WheelDown)
+                    .event_type(EventType::Release)
+                    .cmd_str("true")
+                    .passthrough(true)
+                    .build(),
+            )
+            .build();
+        let config = Arc::new(Mutex::new(config));
+        let args = Arc::new(Args::parse_from(["mouse-actions"]));
+        // a wheel step is a Release event, like in the grab
+        let wheel = |button: MouseButton| ClickEvent {
+            event_type: EventType::Release,
+            ..BindingBuilder::new(button).build().event
+        };
+        assert!(!process_event(
+            config.clone(),
+            wheel(MouseButton::WheelUp),
+            args.clone()
+        ));
+        assert!(process_event(config, wheel(MouseButton::WheelDown), args));
+    }
+
+    #[test]
+    fn test_quadrants() {
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .binding(
+                BindingBuilder::new(MouseButton::Middle)
+                    .event_type(EventType::Release)
+                    .quadrants(vec![Quadrant::TopLeft])
+                    .cmd_str("true")
+                    .build(),
+            )
+            .build();
+        let config = Arc::new(Mutex::new(config));
+        let args = Arc::new(Args::parse_from(["mouse-actions"]));
+        let release = |quadrants: Vec<Quadrant>| ClickEvent {
+            event_type: EventType::Release,
+            quadrants,
+            ..BindingBuilder::new(MouseButton::Middle).build().event
+        };
+        assert!(!process_event(
+            config.clone(),
+            release(vec![Quadrant::TopLeft]),
+            args.clone()
+        ));
+        assert!(process_event(
+            config.clone(),
+            release(vec![Quadrant::TopLeft, Quadrant::TopRight]),
+            args.clone()
+        ));
+        assert!(process_event(config, release(vec![]), args));
+    }
+
+    #[test]
+    fn test_idle_min_ms() {
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .binding(
+                BindingBuilder::new(MouseButton::Extra)
+                    .event_type(EventType::Press)
+                    .idle_min_ms(60_000)
+                    .cmd_str("true")
+                    .build(),
+            )
+            .build();
+        let config = Arc::new(Mutex::new(config));
+        let args = Arc::new(Args::parse_from(["mouse-actions"]));
+        let press = |idle: Duration| ClickEvent {
+            event_type: EventType::Press,
+            idle: Some(idle),
+            ..BindingBuilder::new(MouseButton::Extra).build().event
+        };
+        assert!(process_event(
+            config.clone(),
+            press(Duration::from_secs(5)),
+            args.clone()
+        ));
+        assert!(!process_event(
+            config.clone(),
+            press(Duration::from_secs(90)),
+            args.clone()
+        ));
+    }
+
+    #[test]
+    fn test_press_release_cmd() {
+        let binding = BindingBuilder::new(MouseButton::Side)
+            .press_cmd("ptt start")
+            .release_cmd("ptt stop")
+            .build();
+        let event = |event_type: EventType| ClickEvent {
+            event_type,
+            ..BindingBuilder::new(MouseButton::Side).build().event
+        };
+        assert_eq!(
+            cmd_for_event(&binding, &event(EventType::Press)),
+            Some(vec!["ptt".to_string(), "start".to_string()])
+        );
+        assert_eq!(
+            cmd_for_event(&binding, &event(EventType::Release)),
+            Some(vec!["ptt".to_string(), "stop".to_string()])
+        );
+
+        // a click binding runs its command on the press only
+        let click = BindingBuilder::new(MouseButton::Side).cmd_str("ls").build();
+        assert!(cmd_for_event(&click, &event(EventType::Press)).is_some());
+        assert!(cmd_for_event(&click, &event(EventType::Release)).is_none());
+    }
+
+    #[test]
+    fn test_shape_scores_table() {
+        let mut z_shape = PointHistory::new();
+        (0..20).for_each(|i| z_shape.push(Point { x: i * 10, y: 0 }));
+        (0..20).for_each(|i| {
+            z_shape.push(Point {
+                x: 200 - i * 10,
+                y: i * 10,
+            })
+        });
+        (0..20).for_each(|i| z_shape.push(Point { x: i * 10, y: 200 }));
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .binding(
+                BindingBuilder::new(MouseButton::Right)
+                    .shape(l_shape())
+                    .comment("L")
+                    .build(),
+            )
+            .binding(
+                BindingBuilder::new(MouseButton::Right)
+                    .shape(z_shape)
+                    .comment("Z")
+                    .build(),
+            )
+            .binding(
+                BindingBuilder::new(MouseButton::Right)
+                    .shape(l_shape())
+                    .modifiers(vec![KeyboardModifier::ControlLeft])
+                    .comment("ctrl L")
+                    .build(),
+            )
+            .build();
+        let mut event = BindingBuilder::new(MouseButton::Right)
+            .shape(l_shape())
+            .build()
+            .event;
+        event.event_type = EventType::Release;
+
+        let table = shape_scores_table(&config, &event).unwrap();
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4, "{table}");
+        assert!(
+            lines[1].starts_with('>') && lines[1].ends_with("  L"),
+            "{table}"
+        );
+        assert!(
+            lines[2].starts_with('x') && lines[2].ends_with("ctrl L"),
+            "{table}"
+        );
+        assert!(lines[3].ends_with("  Z"), "{table}");
+
+        event.shapes_angles = vec![vec![0.0; 3]];
+        assert!(shape_scores_table(&config, &event)
+            .unwrap()
+            .starts_with("shape too short"));
+        event.shapes_angles = vec![];
+        assert_eq!(shape_scores_table(&config, &event), None);
+    }
+
+    #[test]
+    fn test_match_line() {
+        let binding = BindingBuilder::new(MouseButton::Middle)
+            .comment("logo")
+            .edges(vec![Edge::Top])
+            .cmd_str("xlogo")
+            .build();
+        let event = ClickEvent {
+            event_type: EventType::Press,
+            shapes_xy: vec![PointHistory::from_points(vec![Point { x: 1, y: 2 }])],
+            ..binding.event.clone()
+        };
+        let line: serde_json::Value = serde_json::from_str(&match_line(&binding, &event)).unwrap();
+        assert_eq!(
+            line,
+            serde_json::json!({
+                "id": binding.stable_id(),
+                "comment": "logo",
+                "event": {
+                    "button": "Middle",
+                    "event_type": "Press",
+                    "edges": ["Top"],
+                    "modifiers": [],
+                },
+            })
+        );
+        assert!(!match_line(&binding, &event).contains('\n'));
+        assert!(Args::parse_from(["mouse-actions", "--emit-matches"]).emit_matches);
+    }
+
+    #[test]
+    fn test_consume_threshold() {
+        let binding = BindingBuilder::new(MouseButton::Right)
+            .comment("L")
+            .shape(shape_points(Shape::L, 200, 40))
+            .diff_max(2.0)
+            .cmd_str("true")
+            .build();
+        let config = |threshold: f64| {
+            Arc::new(Mutex::new(
+                ConfigBuilder::new(MouseButton::Right)
+                    .consume_threshold(threshold)
+                    .binding(binding.clone())
+                    .build(),
+            ))
+        };
+        let event = |shape: Shape| ClickEvent {
+            event_type: EventType::Release,
+            ..BindingBuilder::new(MouseButton::Right)
+                .shape(shape_points(shape, 200, 40))
+                .build()
+                .event
+        };
+        let args = Arc::new(Args::parse_from(["mouse-actions"]));
+        let recording = Arc::new(RecordingRunner::default());
+        let runner: Arc<dyn CommandRunner> = recording.clone();
+
+        // a strong match runs the binding, a borderline one replays the click instead
+        assert!(!process_event_with_runner(
+            config(90.0),
+            event(Shape::L),
+            args.clone(),
+            &runner
+        ));
+        assert!(!process_event_with_runner(
+            config(90.0),
+            event(Shape::C),
+            args.clone(),
+            &runner
+        ));
+        assert_eq!(recording.runs().len(), 1);
+        assert_eq!(recording.replays().len(), 1);
+        // a 0 threshold consumes every match, like no threshold
+        assert!(!process_event_with_runner(
+            config(0.0),
+            event(Shape::C),
+            args,
+            &runner
+        ));
+        assert_eq!(recording.runs().len(), 2);
+    }
+
+    #[test]
+    fn test_read_tail() {
+        let output: Vec<u8> = (0..10_000).map(|i| (i % 10) as u8 + b'0').collect();
+        let tail = read_tail(output.as_slice(), 100);
+        assert_eq!(tail, output[output.len() - 100..]);
+        assert_eq!(read_tail(&b"short"[..], 100), b"short");
+        assert!(read_tail(&b""[..], 100).is_empty());
+
+        // a verbose failing command : only the end of its output is kept
+        let child = Command::new("sh")
+            .args(["-c", "yes | head -c 100000; echo end >&2; exit 3"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let (status, stdout, stderr) = wait_with_output_tail(child).unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(stdout.len(), CMD_OUTPUT_TAIL);
+        assert_eq!(stderr, b"end\n");
+    }
+}
@@ -1,10 +1,12 @@
-use std::io::stdin;
+use std::fs::OpenOptions;
+use std::io::{stdin, Write};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::thread;
 
-use log::{debug, info};
+use log::{debug, error, info};
 
-use crate::args::Args;
+use crate::args::{Args, MouseActionsCommands};
 use crate::binding::Binding;
 use crate::cmd_str_spliter::str_cmd_to_array;
 use crate::config::{save_config, Config};
@@ -12,6 +14,7 @@ use crate::event;
 use crate::event::EventType::Shape;
 use crate::event::{ClickEvent, MouseButton};
 use crate::grab::normalize_points;
+use crate::points_to_angles::{angles_to_directions, directions_to_string};
 
 lazy_static::lazy_static! {
     static ref RECORD_IN_PROGRESS: Mutex<bool> = Mutex::new(false);
@@ -35,6 +38,12 @@ pub fn record_event(config: Arc<Mutex<Config>>, event: ClickEvent, args: Arc<Arg
                 .name("record_event".to_string())
                 .spawn(move || {
                     debug!("record_event : {:?}", event);
+                    if let Some(angles) = event.shapes_angles.first() {
+                        println!(
+                            "shape : {}",
+                            directions_to_string(&angles_to_directions(angles))
+                        );
+                    }
                     println!("command to bind (empty to stop the record) : ");
                     let mut input_string = String::new();
                     stdin()
@@ -53,19 +62,53 @@ pub fn record_event(config: Arc<Mutex<Config>>, event: ClickEvent, args: Arc<Arg
 
                         let mut event = reduce_shape_precision(event);
                         if let Some(shapes_xy) = event.shapes_xy.first() {
-                            event.shapes_xy = vec![normalize_points(&shapes_xy, false)];
+                            event.shapes_xy = vec![normalize_points(shapes_xy, false)];
                             event.event_type = Shape;
                         }
+                        // the recorded shape matches the open and the closed gestures
+                        event.closed = None;
+                        // the recorded binding matches the events of all the devices
+                        event.device = None;
+                        // and in all the quadrants
+                        event.quadrants = vec![];
+                        // record the keys actually held, not their either-side modifiers
+                        event
+                            .modifiers
+                            .retain(|modifier| !modifier.is_either_side());
 
                         let binding = Binding {
                             comment,
+                            id: None,
                             event,
                             cmd: str_cmd_to_array(cmd_string),
                             cmd_str: String::new(),
+                            press_cmd: String::new(),
+                            release_cmd: String::new(),
+                            action: None,
+                            repeat_ms: None,
+                            diff_max: None,
+                            quiet: false,
+                            log: true,
+                            limits: None,
+                            location_sensitive: false,
+                            priority: 0,
+                            no_command_prefix: false,
+                            detach: false,
+                            notify: None,
+                            bidirectional: false,
+                            when: vec![],
+                            arm_required: false,
+                            passthrough: false,
+                            idle_min_ms: None,
+                            length_min: None,
+                            length_max: None,
+                            workspace: None,
+                            active_hours: None,
+                            included_from: None,
                         };
                         info!("push : {binding:#?}");
                         config.lock().unwrap().bindings.push(binding);
-                        save_config(&config.lock().unwrap(), &args.config_path);
+                        save_config(&config.lock().unwrap(), &args.main_config_path());
                         // FIXME
                         println!(
                             "\nStart record event : draw a shape with the {:?} button :",
@@ -96,3 +139,76 @@ pub fn reduce_shape_precision(event: ClickEvent) -> ClickEvent {
         ..event
     }
 }
+
+/// append the shapes drawn with the shape button to the output file of the record-samples command
+pub fn record_sample_event(config: Arc<Mutex<Config>>, event: ClickEvent, args: Arc<Args>) -> bool {
+    if config.lock().unwrap().shape_button != event.button {
+        return true;
+    }
+    if event.event_type == event::EventType::Release && !event.shapes_angles.is_empty() {
+        if let Some(MouseActionsCommands::RecordSamples { output, label }) = &args.command {
+            match append_sample(Path::new(output), label, &event) {
+                Ok(()) => println!("sample \"{label}\" appended to {output}"),
+                Err(err) => error!("couldn't append the sample to {output} : {err}"),
+            }
+        }
+    }
+    false
+}
+
+/// append a JSON line with the label, the normalized points and the angles of the event shape
+pub fn append_sample(output: &Path, label: &str, event: &ClickEvent) -> std::io::Result<()> {
+    let event = reduce_shape_precision(event.clone());
+    let points = event
+        .shapes_xy
+        .first()
+        .map(|shape_xy| normalize_points(shape_xy, false))
+        .unwrap_or_default();
+    let sample = serde_json::json!({
+        "label": label,
+        "points": points,
+        "angles": event.shapes_angles.first().cloned().unwrap_or_default(),
+    });
+    let mut file = OpenOptions::new().create(true).append(true).open(output)?;
+    writeln!(file, "{sample}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::builder::BindingBuilder;
+    use crate::event::{MouseButton, Point, PointHistory};
+    use crate::record::append_sample;
+
+    #[test]
+    fn test_append_sample() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("samples.jsonl");
+        let mut shape = PointHistory::new();
+        (0..20).for_each(|i| {
+            shape.push(Point {
+                x: 100 + i,
+                y: 100 + i * 2,
+            })
+        });
+        let event = BindingBuilder::new(MouseButton::Right)
+            .shape(shape)
+            .build()
+            .event;
+
+        append_sample(&output, "line", &event).unwrap();
+        append_sample(&output, "line", &event).unwrap();
+
+        let content = fs::read_to_string(&output).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["label"], "line");
+        assert_eq!(lines[0]["points"][0], 0);
+        assert_eq!(lines[0]["points"].as_array().unwrap().len(), 40);
+        assert_eq!(lines[0]["angles"].as_array().unwrap().len(), 19);
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use log::debug;
+
+use crate::binding::Binding;
+use crate::event::MouseButton;
+use crate::runner::CommandRunner;
+
+// the shortest repeat_ms period, a shorter one would spawn the command in a tight loop
+pub const MIN_REPEAT_MS: u64 = 20;
+
+lazy_static::lazy_static! {
+    // the stop flags of the repeat threads and the passthrough of their binding, by pressed button
+    static ref REPEATS: Mutex<HashMap<MouseButton, (Arc<AtomicBool>, bool)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// re-run the command of the binding every `repeat_ms` until `stop_repeat` is
+/// called for the button (on its release)
+pub fn start_repeat(
+    button: MouseButton,
+    binding: Binding,
+    cmd: Vec<String>,
+    repeat_ms: u64,
+    runner: Arc<dyn CommandRunner>,
+) {
+    let stop = Arc::new(AtomicBool::new(false));
+    if let Some((previous, _)) = REPEATS
+        .lock()
+        .unwrap()
+        .insert(button, (stop.clone(), binding.passthrough))
+    {
+        previous.store(true, Ordering::Relaxed);
+    }
+    if binding.log {
+        debug!(
+            "{} start the repeat every {repeat_ms}ms",
+            binding.log_fields()
+        );
+    }
+    thread::Builder::new()
+        .name("repeat_cmd".to_string())
+        .spawn(move || loop {
+            thread::sleep(Duration::from_millis(repeat_ms));
+            if stop.load(Ordering::Relaxed) {
+                if binding.log {
+                    debug!("{} stop the repeat", binding.log_fields());
+                }
+                break;
+            }
+            runner.run(&binding, cmd.clone());
+        })
+        .unwrap();
+}
+
+/// stop the repeat started by the press of the button, return true if the release
+/// must be consumed : there was a repeat and its binding is not passthrough
+pub fn stop_repeat(button: MouseButton) -> bool {
+    if let Some((stop, passthrough)) = REPEATS.lock().unwrap().remove(&button) {
+        stop.store(true, Ordering::Relaxed);
+        !passthrough
+    } else {
+        false
+    }
+}
+
+/// stop all the repeats, their button releases are no longer consumed
+pub fn stop_all_repeats() {
+    for (_, (stop, _)) in REPEATS.lock().unwrap().drain() {
+        stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// the tests starting repeats, or stopping them all, run one at a time
+#[cfg(test)]
+pub(crate) static REPEATS_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::BindingBuilder;
+    use crate::event::{EventType, MouseButton};
+    use crate::repeat::{start_repeat, stop_repeat, REPEATS_TEST_LOCK};
+    use crate::runner::{CommandRunner, RecordingRunner};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_repeat() {
+        let _lock = REPEATS_TEST_LOCK.lock().unwrap();
+        let recording = Arc::new(RecordingRunner::default());
+        let runner: Arc<dyn CommandRunner> = recording.clone();
+        let binding = BindingBuilder::new(MouseButton::Thumb2)
+            .comment("repeated")
+            .event_type(EventType::Press)
+            .cmd_str("true")
+            .build();
+        assert!(!stop_repeat(MouseButton::Thumb2));
+        start_repeat(
+            MouseButton::Thumb2,
+            binding,
+            vec!["true".to_string()],
+            20,
+            runner.clone(),
+        );
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while recording.runs().len() < 3 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let runs = recording.runs();
+        assert!(runs.len() >= 3, "{runs:?}");
+        assert!(runs
+            .iter()
+            .all(|run| *run == ("repeated".to_string(), vec!["true".to_string()])));
+        assert!(stop_repeat(MouseButton::Thumb2));
+        assert!(!stop_repeat(MouseButton::Thumb2));
+        // a run already past the stop check can still complete, then the runs stop
+        thread::sleep(Duration::from_millis(100));
+        let stopped_runs = recording.runs().len();
+        assert!(
+            stopped_runs <= runs.len() + 1,
+            "{stopped_runs} {}",
+            runs.len()
+        );
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(recording.runs().len(), stopped_runs);
+
+        let passthrough_binding = BindingBuilder::new(MouseButton::Thumb2)
+            .event_type(EventType::Press)
+            .cmd_str("true")
+            .passthrough(true)
+            .build();
+        start_repeat(
+            MouseButton::Thumb2,
+            passthrough_binding,
+            vec!["true".to_string()],
+            50,
+            runner.clone(),
+        );
+        assert!(!stop_repeat(MouseButton::Thumb2));
+    }
+}
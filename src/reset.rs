@@ -0,0 +1,42 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::warn;
+
+// set by the SIGUSR1 of the reset-state command, the grab resets its state on the next event
+static RESET_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_reset_signal(_signal: libc::c_int) {
+    // only an atomic store : async-signal-safe
+    RESET_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// reset the grab state on SIGUSR1, sent by the `reset-state` command
+pub fn listen_reset_signal() {
+    // SAFETY: the handler only does an atomic store
+    let handler = on_reset_signal as extern "C" fn(libc::c_int) as *const () as libc::sighandler_t;
+    let previous = unsafe { libc::signal(libc::SIGUSR1, handler) };
+    if previous == libc::SIG_ERR {
+        warn!("can't listen to SIGUSR1, the reset-state command won't work");
+    }
+}
+
+/// true once per reset request
+pub fn take_reset_request() -> bool {
+    RESET_REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reset::{listen_reset_signal, take_reset_request};
+
+    #[test]
+    fn test_reset_signal() {
+        listen_reset_signal();
+        // another test can't take it : only this one requests a reset
+        assert!(!take_reset_request());
+        rustix::process::kill_process(rustix::process::getpid(), rustix::process::Signal::Usr1)
+            .unwrap();
+        assert!(take_reset_request());
+        assert!(!take_reset_request());
+    }
+}
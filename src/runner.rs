@@ -0,0 +1,78 @@
+use std::sync::{Arc, Mutex};
+
+use lazy_static::lazy_static;
+
+use crate::binding::Binding;
+use crate::process_event::{process_cmd, run_binding_cmd};
+use crate::spawned;
+
+lazy_static! {
+    pub static ref PROCESS_RUNNER: Arc<dyn CommandRunner> = Arc::new(ProcessRunner);
+}
+
+/// The execution of the binding commands, after the matching : `process_event` spawns
+/// the processes, the tests can give `process_event_with_runner` a `RecordingRunner`.
+pub trait CommandRunner: Send + Sync {
+    fn run(&self, binding: &Binding, cmd: Vec<String>);
+
+    /// replay the click of the shape button consumed by the grab, when no binding runs
+    fn replay_click(&self, cmd: Vec<String>) {
+        process_cmd(cmd);
+    }
+
+    /// terminate the running binding commands, for the KillSpawned action
+    fn kill_spawned(&self) {
+        spawned::kill_spawned();
+    }
+}
+
+/// spawn the command processes, used by the daemon
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProcessRunner;
+
+impl CommandRunner for ProcessRunner {
+    fn run(&self, binding: &Binding, cmd: Vec<String>) {
+        run_binding_cmd(binding, cmd);
+    }
+}
+
+/// record the commands instead of running them : the comment of the binding and the argv
+#[derive(Debug, Default)]
+pub struct RecordingRunner {
+    runs: Mutex<Vec<(String, Vec<String>)>>,
+    replays: Mutex<Vec<Vec<String>>>,
+    kills: Mutex<usize>,
+}
+
+impl RecordingRunner {
+    pub fn runs(&self) -> Vec<(String, Vec<String>)> {
+        self.runs.lock().unwrap().clone()
+    }
+
+    /// the argv of the replayed clicks
+    pub fn replays(&self) -> Vec<Vec<String>> {
+        self.replays.lock().unwrap().clone()
+    }
+
+    /// the number of KillSpawned actions
+    pub fn kills(&self) -> usize {
+        *self.kills.lock().unwrap()
+    }
+}
+
+impl CommandRunner for RecordingRunner {
+    fn run(&self, binding: &Binding, cmd: Vec<String>) {
+        self.runs
+            .lock()
+            .unwrap()
+            .push((binding.comment.clone(), cmd));
+    }
+
+    fn replay_click(&self, cmd: Vec<String>) {
+        self.replays.lock().unwrap().push(cmd);
+    }
+
+    fn kill_spawned(&self) {
+        *self.kills.lock().unwrap() += 1;
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::{anyhow, bail};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// the minutes since midnight of a "HH:MM" time of day, e.g. "09:30" → 570
+pub fn parse_time_of_day(time: &str) -> anyhow::Result<u32> {
+    let invalid = || anyhow!("invalid time of day {time:?}, expected \"HH:MM\" like \"09:30\"");
+    let (hours, minutes) = time.trim().split_once(':').ok_or_else(invalid)?;
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    Ok(hours * 60 + minutes)
+}
+
+/// check the active hours when the config is loaded, the match can't fail on them later
+pub fn check_active_hours(active_hours: &[String; 2]) -> anyhow::Result<()> {
+    if parse_time_of_day(&active_hours[0])? == parse_time_of_day(&active_hours[1])? {
+        bail!("the active hours {active_hours:?} are empty, the start and the end must differ");
+    }
+    Ok(())
+}
+
+/// true if the minute of the day is in [start, end) of the active hours, the hours
+/// cross midnight if the end is before the start, e.g. ["22:00", "06:00"]
+pub fn is_in_active_hours(active_hours: &[String; 2], minute_of_day: u32) -> bool {
+    let (Ok(start), Ok(end)) = (
+        parse_time_of_day(&active_hours[0]),
+        parse_time_of_day(&active_hours[1]),
+    ) else {
+        return false;
+    };
+    if start <= end {
+        (start..end).contains(&minute_of_day)
+    } else {
+        minute_of_day >= start || minute_of_day < end
+    }
+}
+
+/// the minutes since midnight in the local time zone (TZ or /etc/localtime)
+#[cfg(unix)]
+pub fn local_minute_of_day() -> u32 {
+    // SAFETY: time accepts a null pointer, localtime_r only writes the tm it is given
+    let local = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        (!libc::localtime_r(&now, &mut tm).is_null()).then_some(tm)
+    };
+    let Some(tm) = local else {
+        return utc_minute_of_day();
+    };
+    (tm.tm_hour * 60 + tm.tm_min) as u32
+}
+
+/// the minutes since midnight, in UTC : the local time zone isn't read on this platform
+#[cfg(not(unix))]
+pub fn local_minute_of_day() -> u32 {
+    utc_minute_of_day()
+}
+
+fn utc_minute_of_day() -> u32 {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    ((seconds / 60) % MINUTES_PER_DAY as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schedule::{
+        check_active_hours, is_in_active_hours, local_minute_of_day, parse_time_of_day,
+    };
+
+    #[test]
+    fn test_parse_time_of_day() {
+        assert_eq!(parse_time_of_day("00:00").unwrap(), 0);
+        assert_eq!(parse_time_of_day("09:30").unwrap(), 570);
+        assert_eq!(parse_time_of_day("9:05").unwrap(), 545);
+        assert_eq!(parse_time_of_day("23:59").unwrap(), 1439);
+        assert!(parse_time_of_day("24:00").is_err());
+        assert!(parse_time_of_day("12:60").is_err());
+        assert!(parse_time_of_day("noon").is_err());
+        assert!(parse_time_of_day("12").is_err());
+    }
+
+    #[test]
+    fn test_active_hours() {
+        let hours = |start: &str, end: &str| [start.to_string(), end.to_string()];
+        let work = hours("09:00", "18:00");
+        assert!(check_active_hours(&work).is_ok());
+        assert!(!is_in_active_hours(&work, 8 * 60 + 59));
+        assert!(is_in_active_hours(&work, 9 * 60));
+        assert!(is_in_active_hours(&work, 17 * 60 + 59));
+        assert!(!is_in_active_hours(&work, 18 * 60));
+
+        // crossing midnight
+        let night = hours("22:00", "06:00");
+        assert!(is_in_active_hours(&night, 23 * 60));
+        assert!(is_in_active_hours(&night, 0));
+        assert!(is_in_active_hours(&night, 5 * 60 + 59));
+        assert!(!is_in_active_hours(&night, 6 * 60));
+        assert!(!is_in_active_hours(&night, 12 * 60));
+
+        assert!(check_active_hours(&hours("10:00", "10:00")).is_err());
+        assert!(check_active_hours(&hours("10:00", "25:00")).is_err());
+        assert!(local_minute_of_day() < 24 * 60);
+    }
+}
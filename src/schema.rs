@@ -0,0 +1,445 @@
+use serde_json::{json, Map, Value};
+
+use crate::config::MAX_LONG_GESTURE_POINTS;
+use crate::event::MouseButton;
+use crate::repeat::MIN_REPEAT_MS;
+
+// the JSON Schema draft of the generated schema
+const SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+fn enum_of(values: &[&str]) -> Value {
+    json!({ "type": "string", "enum": values })
+}
+
+fn array_of(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{name}") })
+}
+
+fn described(mut schema: Value, description: &str) -> Value {
+    schema["description"] = json!(description);
+    schema
+}
+
+fn object(properties: Map<String, Value>, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn properties(properties: Vec<(&str, Value)>) -> Map<String, Value> {
+    properties
+        .into_iter()
+        .map(|(name, schema)| (name.to_string(), schema))
+        .collect()
+}
+
+fn click_event_properties() -> Map<String, Value> {
+    properties(vec![
+        ("button", reference("MouseButton")),
+        (
+            "buttons",
+            described(
+                array_of(reference("MouseButton")),
+                "the other buttons of the binding, it matches any of them",
+            ),
+        ),
+        ("edges", array_of(reference("Edge"))),
+        ("edges_forbidden", array_of(reference("Edge"))),
+        ("region", reference("Region")),
+        (
+            "quadrants",
+            described(
+                array_of(enum_of(&[
+                    "TopLeft",
+                    "TopRight",
+                    "BottomLeft",
+                    "BottomRight",
+                ])),
+                "the screen quadrants overlapped by the shape, exactly",
+            ),
+        ),
+        (
+            "device",
+            described(json!({ "type": "string" }), "the name of the input device"),
+        ),
+        ("held_buttons", array_of(reference("MouseButton"))),
+        ("modifiers", array_of(reference("KeyboardModifier"))),
+        (
+            "keys",
+            described(
+                array_of(json!({ "type": ["string", "object"] })),
+                "the held keys other than the modifiers, e.g. \"KeyG\"",
+            ),
+        ),
+        ("event_type", reference("EventType")),
+        (
+            "shapes_xy",
+            described(
+                array_of(array_of(json!({ "type": "integer" }))),
+                "the shape points : X1, Y1, X2, Y2, …",
+            ),
+        ),
+        (
+            "shapes_svg",
+            described(
+                array_of(json!({ "type": "string" })),
+                "the shapes as SVG path data, e.g. \"M0,0 L0,100 L100,100\"",
+            ),
+        ),
+        (
+            "shapes_directions",
+            described(
+                array_of(json!({ "type": "string", "pattern": "^[RDLUrdlu\\s]+$" })),
+                "the shapes as direction codes, e.g. \"RDLU\" for right, down, left, up",
+            ),
+        ),
+        (
+            "shape_ref",
+            described(
+                json!({ "type": "string" }),
+                "the name of a shape template of the config",
+            ),
+        ),
+        ("closed", json!({ "type": "boolean" })),
+        ("shape_button_held", json!({ "type": "boolean" })),
+    ])
+}
+
+fn binding_properties() -> Map<String, Value> {
+    let string = || json!({ "type": "string" });
+    let boolean = || json!({ "type": "boolean" });
+    let unsigned = || json!({ "type": "integer", "minimum": 0 });
+    properties(vec![
+        ("comment", string()),
+        (
+            "id",
+            described(
+                string(),
+                "a stable identifier, derived from the event and the commands if absent",
+            ),
+        ),
+        ("event", reference("ClickEvent")),
+        ("cmd", array_of(string())),
+        (
+            "cmd_str",
+            described(string(), "split like a shell command line"),
+        ),
+        ("cmd_line", described(string(), "alias of cmd_str")),
+        ("press_cmd", string()),
+        ("release_cmd", string()),
+        ("action", enum_of(&["KillSpawned"])),
+        (
+            "repeat_ms",
+            json!({ "type": "integer", "minimum": MIN_REPEAT_MS }),
+        ),
+        ("diff_max", json!({ "type": "number", "minimum": 0 })),
+        ("priority", json!({ "type": "integer" })),
+        ("bidirectional", boolean()),
+        ("location_sensitive", boolean()),
+        ("notify", string()),
+        ("detach", boolean()),
+        ("no_command_prefix", boolean()),
+        ("quiet", boolean()),
+        ("log", boolean()),
+        ("limits", reference("CommandLimits")),
+        ("when", array_of(string())),
+        ("idle_min_ms", unsigned()),
+        ("length_min", unsigned()),
+        ("length_max", unsigned()),
+        ("workspace", unsigned()),
+        (
+            "active_hours",
+            json!({
+                "type": "array",
+                "items": { "type": "string", "pattern": "^\\d{1,2}:\\d{2}$" },
+                "minItems": 2,
+                "maxItems": 2
+            }),
+        ),
+        ("passthrough", boolean()),
+        ("arm_required", boolean()),
+    ])
+}
+
+fn config_properties() -> Map<String, Value> {
+    let string = || json!({ "type": "string" });
+    let boolean = || json!({ "type": "boolean" });
+    let unsigned = || json!({ "type": "integer", "minimum": 0 });
+    properties(vec![
+        (
+            "$schema",
+            described(string(), "the path or URL of this schema, ignored"),
+        ),
+        ("shape_button", reference("MouseButton")),
+        ("bindings", array_of(reference("Binding"))),
+        ("includes", array_of(string())),
+        ("detect_edges", boolean()),
+        ("corner_size", unsigned()),
+        ("relative_to", enum_of(&["Screen", "Window"])),
+        ("invert_wheel", boolean()),
+        ("shape_min_points", unsigned()),
+        ("sample_interval_ms", unsigned()),
+        ("resample_spacing", unsigned()),
+        ("trim_start_points", unsigned()),
+        ("trim_end_points", unsigned()),
+        ("click_max_move", unsigned()),
+        ("max_gesture_ms", unsigned()),
+        (
+            "long_gesture",
+            object(
+                properties(vec![
+                    ("modifier", reference("KeyboardModifier")),
+                    (
+                        "max_points",
+                        json!({
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": MAX_LONG_GESTURE_POINTS,
+                            "default": 10000
+                        }),
+                    ),
+                ]),
+                &["modifier"],
+            ),
+        ),
+        ("abort_shape_on", array_of(enum_of(&["Wheel", "Escape"]))),
+        ("command_prefix", array_of(string())),
+        ("min_direction_changes", unsigned()),
+        ("arm", reference("ArmGesture")),
+        (
+            "grab_nice",
+            json!({ "type": "integer", "minimum": -20, "maximum": 19 }),
+        ),
+        ("exclude_window_classes", array_of(string())),
+        ("angle_weighting", enum_of(&["Uniform", "FrontLoaded"])),
+        (
+            "consume_threshold",
+            json!({ "type": "number", "minimum": 0, "maximum": 100 }),
+        ),
+        ("suggest_closest", boolean()),
+        ("sound_on_match", string()),
+        ("sound_on_fail", string()),
+        ("backup_count", unsigned()),
+        ("editor_command", string()),
+        (
+            "templates",
+            described(
+                json!({
+                    "type": "object",
+                    "additionalProperties": array_of(array_of(json!({ "type": "integer" }))),
+                }),
+                "the named shapes referenced by the shape_ref of the events, like shapes_xy",
+            ),
+        ),
+    ])
+}
+
+/// The JSON Schema of the config file, for the validation and the completion in the editors
+pub fn config_schema() -> Value {
+    let buttons: Vec<String> = MouseButton::ALL
+        .iter()
+        .map(|button| button.name())
+        .collect();
+    let mut arm_properties = binding_properties();
+    arm_properties.insert(
+        "duration_ms".to_string(),
+        described(
+            json!({ "type": "integer", "minimum": 0, "default": 5000 }),
+            "the time during which the arm_required bindings can fire after the arm gesture",
+        ),
+    );
+    let mut schema = object(config_properties(), &["shape_button", "bindings"]);
+    schema["$schema"] = json!(SCHEMA_DRAFT);
+    schema["title"] = json!("mouse-actions config");
+    schema["$defs"] = json!({
+        "Binding": object(binding_properties(), &["event"]),
+        "ArmGesture": object(arm_properties, &["event"]),
+        "ClickEvent": object(click_event_properties(), &[]),
+        "CommandLimits": object(
+            properties(vec![
+                (
+                    "umask",
+                    described(json!({ "type": "string" }), "in octal, e.g. \"077\""),
+                ),
+                ("max_memory_mb", json!({ "type": "integer", "minimum": 0 })),
+                ("max_open_files", json!({ "type": "integer", "minimum": 0 })),
+                ("no_core_dump", json!({ "type": "boolean" })),
+            ]),
+            &[],
+        ),
+        "MouseButton": { "type": "string", "enum": buttons },
+        "Edge": enum_of(&[
+            "Top", "Right", "Bottom", "Left", "TopLeft", "TopRight", "BottomLeft", "BottomRight",
+        ]),
+        "Region": enum_of(&[
+            "TopLeft", "Top", "TopRight", "Left", "Center", "Right", "BottomLeft", "Bottom",
+            "BottomRight",
+        ]),
+        "KeyboardModifier": enum_of(&[
+            "ShiftLeft", "ShiftRight", "ControlLeft", "ControlRight", "MetaLeft", "MetaRight",
+            "Alt", "AltGr", "Shift", "Control", "Meta",
+        ]),
+        "EventType": enum_of(&["Press", "Release", "Click", "Shape"]),
+    });
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::DeserializeOwned;
+    use serde_json::Value;
+
+    use crate::binding::{Action, ArmGesture};
+    use crate::builder::{BindingBuilder, ConfigBuilder};
+    use crate::compare_angles::AngleWeighting;
+    use crate::config::{examples_config, LongGesture, Templates};
+    use crate::event::{
+        AbortTrigger, Edge, EventType, KeyboardModifier, MouseButton, Point, PointHistory,
+        Quadrant, Region, RelativeTo,
+    };
+    use crate::limits::CommandLimits;
+    use crate::schema::config_schema;
+
+    fn enum_values(schema: &Value) -> Vec<Value> {
+        schema["enum"].as_array().unwrap().clone()
+    }
+
+    fn assert_enum<T: DeserializeOwned>(schema: &Value) {
+        for value in enum_values(schema) {
+            assert!(
+                serde_json::from_value::<T>(value.clone()).is_ok(),
+                "{value} isn't a valid value"
+            );
+        }
+    }
+
+    /// the keys of the serialized value are all properties of the schema object
+    fn assert_properties(value: &Value, schema: &Value) {
+        for key in value.as_object().unwrap().keys() {
+            assert!(
+                schema["properties"].get(key).is_some(),
+                "{key} is missing in the schema"
+            );
+        }
+    }
+
+    #[test]
+    fn test_schema_enums() {
+        let schema = config_schema();
+        let defs = &schema["$defs"];
+        assert_enum::<MouseButton>(&defs["MouseButton"]);
+        assert_enum::<Action>(&defs["Binding"]["properties"]["action"]);
+        assert_enum::<Edge>(&defs["Edge"]);
+        assert_enum::<Region>(&defs["Region"]);
+        assert_enum::<Quadrant>(&defs["ClickEvent"]["properties"]["quadrants"]["items"]);
+        assert_enum::<KeyboardModifier>(&defs["KeyboardModifier"]);
+        assert_enum::<EventType>(&defs["EventType"]);
+        assert_enum::<RelativeTo>(&schema["properties"]["relative_to"]);
+        assert_enum::<AbortTrigger>(&schema["properties"]["abort_shape_on"]["items"]);
+        assert_enum::<AngleWeighting>(&schema["properties"]["angle_weighting"]);
+    }
+
+    #[test]
+    fn test_schema_properties() {
+        let schema = config_schema();
+        let mut binding = BindingBuilder::new(MouseButton::Right)
+            .comment("all")
+            .id("all")
+            .edges(vec![Edge::Top])
+            .edges_forbidden(vec![Edge::Left])
+            .location_sensitive(Region::Top)
+            .device("mouse")
+            .quadrants(vec![Quadrant::TopLeft])
+            .held_buttons(vec![MouseButton::Back])
+            .keys(vec![rdev::Key::KeyG])
+            .modifiers(vec![KeyboardModifier::Alt])
+            .event_type(EventType::Shape)
+            .shape_svg("M0,0 L0,100")
+            .shape_directions("RD")
+            .shape_button_held(true)
+            .cmd_str("true")
+            .press_cmd("true")
+            .release_cmd("true")
+            .action(Action::KillSpawned)
+            .repeat_ms(100)
+            .diff_max(0.5)
+            .priority(1)
+            .bidirectional(true)
+            .notify("done")
+            .detach(true)
+            .no_command_prefix(true)
+            .quiet(true)
+            .log(false)
+            .limits(CommandLimits {
+                umask: Some("077".to_string()),
+                max_memory_mb: Some(512),
+                max_open_files: Some(1024),
+                no_core_dump: true,
+            })
+            .when(vec!["true".to_string()])
+            .idle_min_ms(1000)
+            .length_range(Some(10), Some(100))
+            .workspace(1)
+            .active_hours("22:00", "06:00")
+            .passthrough(true)
+            .arm_required(true)
+            .build();
+        binding.event.buttons = vec![MouseButton::Side];
+        binding.event.closed = Some(true);
+        binding.event.shape_ref = Some("circle".to_string());
+        let value = serde_json::to_value(&binding).unwrap();
+        assert_properties(&value, &schema["$defs"]["Binding"]);
+        assert_properties(&value["event"], &schema["$defs"]["ClickEvent"]);
+        assert_properties(&value["limits"], &schema["$defs"]["CommandLimits"]);
+
+        let arm = serde_json::to_value(ArmGesture {
+            binding,
+            duration_ms: 3000,
+        })
+        .unwrap();
+        assert_properties(&arm, &schema["$defs"]["ArmGesture"]);
+
+        let config = ConfigBuilder::new(MouseButton::Right)
+            .includes(vec!["other.json".to_string()])
+            .detect_edges(false)
+            .corner_size(10)
+            .relative_to(RelativeTo::Window)
+            .invert_wheel(true)
+            .shape_min_points(5)
+            .sample_interval_ms(5)
+            .resample_spacing(10)
+            .trim_points(1, 2)
+            .click_max_move(3)
+            .max_gesture_ms(3000)
+            .long_gesture(LongGesture {
+                modifier: KeyboardModifier::Shift,
+                max_points: 5000,
+            })
+            .abort_shape_on(vec![AbortTrigger::Wheel])
+            .command_prefix(vec!["systemd-run".to_string()])
+            .min_direction_changes(1)
+            .grab_nice(-5)
+            .exclude_window_classes(vec!["Remmina".to_string()])
+            .angle_weighting(AngleWeighting::FrontLoaded)
+            .consume_threshold(90.0)
+            .suggest_closest(true)
+            .sound_on_match("bell.oga")
+            .sound_on_fail("error.oga")
+            .backup_count(2)
+            .editor_command("code --wait")
+            .templates(Templates::from([(
+                "circle".to_string(),
+                vec![PointHistory::from_points(vec![Point { x: 0, y: 0 }])],
+            )]))
+            .build();
+        assert_properties(&serde_json::to_value(&config).unwrap(), &schema);
+        assert_properties(&serde_json::to_value(examples_config()).unwrap(), &schema);
+    }
+}
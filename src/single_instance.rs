@@ -1,8 +1,8 @@
-use std::{env, fs, process};
 use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::thread::sleep;
 use std::time::Duration;
+use std::{env, fs, process};
 
 use anyhow::{anyhow, Context};
 use fs2::FileExt;
@@ -40,6 +40,21 @@ pub fn kill_from_pid_file(pid_file: &mut fs::File) -> anyhow::Result<String> {
     Ok(pid_str)
 }
 
+/// send SIGUSR1 to the running instance : it resets its grab state, see reset.rs
+pub fn send_reset_signal() -> anyhow::Result<u32> {
+    if !is_running() {
+        return Err(anyhow!("mouse_actions is not running"));
+    }
+    let pid: u32 = fs::read_to_string(get_pid_file_path())?.trim().parse()?;
+    unsafe {
+        kill_process(
+            Pid::from_raw(pid).context("pid conversion error")?,
+            Signal::Usr1,
+        )?;
+    }
+    Ok(pid)
+}
+
 pub fn write_pid_to_file(pid_file_path: &Path) -> anyhow::Result<()> {
     fs::write(pid_file_path, process::id().to_string()).map_err(anyhow::Error::msg)
 }
@@ -90,6 +105,6 @@ pub fn is_running() -> bool {
     let pid_file_path = get_pid_file_path();
 
     fs::File::open(&pid_file_path)
-        .map(|pid_file| !pid_file.try_lock_exclusive().is_ok())
+        .map(|pid_file| pid_file.try_lock_exclusive().is_err())
         .unwrap_or(false)
 }
@@ -0,0 +1,57 @@
+use std::path::Path;
+
+use crate::cmd_str_spliter::str_cmd_to_array;
+use crate::config::Config;
+use crate::process_event::process_cmd;
+
+// the sound files are played by the PulseAudio (or PipeWire) client
+const PLAY_CMD: &str = "paplay";
+
+/// the command that plays the sound : a sound file is played with paplay,
+/// anything else is a command line, e.g. "canberra-gtk-play -i bell"
+pub fn sound_cmd(sound: &str) -> Vec<String> {
+    if Path::new(sound).is_file() {
+        vec![PLAY_CMD.to_string(), sound.to_string()]
+    } else {
+        str_cmd_to_array(sound)
+    }
+}
+
+/// play the sound_on_match or sound_on_fail of the config after a shape, if any, without
+/// waiting for it
+pub fn gesture_feedback(config: &Config, matched: bool) {
+    let sound = if matched {
+        &config.sound_on_match
+    } else {
+        &config.sound_on_fail
+    };
+    if let Some(sound) = sound {
+        let cmd = sound_cmd(sound);
+        if !cmd.is_empty() {
+            process_cmd(cmd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::sound::sound_cmd;
+
+    #[test]
+    fn test_sound_cmd() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"RIFF").unwrap();
+        let path = file.path().to_str().unwrap();
+        assert_eq!(sound_cmd(path), vec!["paplay", path]);
+        assert_eq!(
+            sound_cmd("canberra-gtk-play -i bell"),
+            vec!["canberra-gtk-play", "-i", "bell"]
+        );
+        assert_eq!(
+            sound_cmd("/nonexistent/ok.oga"),
+            vec!["/nonexistent/ok.oga"]
+        );
+    }
+}
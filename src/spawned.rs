@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use log::{info, warn};
+use rustix::process::{kill_process_group, Pid, Signal};
+
+lazy_static! {
+    // the running binding commands of the daemon
+    static ref SPAWNED: SpawnedCommands = SpawnedCommands::default();
+}
+
+/// The pids of running commands, each one leads its process group
+#[derive(Debug, Default)]
+pub struct SpawnedCommands {
+    pids: Mutex<HashSet<u32>>,
+}
+
+impl SpawnedCommands {
+    pub fn track(&self, pid: u32) {
+        self.pids.lock().unwrap().insert(pid);
+    }
+
+    pub fn untrack(&self, pid: u32) {
+        self.pids.lock().unwrap().remove(&pid);
+    }
+
+    pub fn pids(&self) -> Vec<u32> {
+        self.pids.lock().unwrap().iter().copied().collect()
+    }
+
+    /// send SIGTERM to the process groups of all the commands, return the number of
+    /// groups signaled
+    pub fn kill(&self) -> usize {
+        let pids: Vec<u32> = self.pids.lock().unwrap().drain().collect();
+        let mut killed = 0;
+        for pid in pids {
+            // SAFETY: the pid comes from Child::id, the group is the one of process_group(0)
+            let Some(group) = (unsafe { Pid::from_raw(pid as _) }) else {
+                continue;
+            };
+            match kill_process_group(group, Signal::Term) {
+                Ok(()) => killed += 1,
+                Err(err) => warn!("couldn't kill the process group {pid} : {err}"),
+            }
+        }
+        killed
+    }
+}
+
+/// track a spawned command until it exits, for `kill_spawned`
+pub fn track(pid: u32) {
+    SPAWNED.track(pid);
+}
+
+/// the command has exited, after its wait
+pub fn untrack(pid: u32) {
+    SPAWNED.untrack(pid);
+}
+
+/// the pids of the running commands
+pub fn spawned() -> Vec<u32> {
+    SPAWNED.pids()
+}
+
+/// send SIGTERM to the process groups of all the running commands, return the number of
+/// groups signaled
+pub fn kill_spawned() -> usize {
+    let killed = SPAWNED.kill();
+    info!("KillSpawned : {killed} command(s) terminated");
+    killed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    use crate::spawned::SpawnedCommands;
+
+    #[test]
+    fn test_kill_spawned() {
+        let spawned = SpawnedCommands::default();
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        spawned.track(child.id());
+        assert_eq!(spawned.pids(), vec![child.id()]);
+        assert_eq!(spawned.kill(), 1);
+        assert!(spawned.pids().is_empty());
+        let status = child.wait().unwrap();
+        assert!(!status.success());
+        assert_eq!(spawned.kill(), 0);
+    }
+}
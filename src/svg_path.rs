@@ -0,0 +1,217 @@
+use anyhow::{anyhow, bail};
+
+use crate::event::{Point, PointHistory};
+
+// the number of points sampled along the path
+const SVG_PATH_SAMPLES: usize = 100;
+// the sampled points are scaled to the size of the normalized recorded shapes
+const SVG_PATH_SIZE: f64 = 1000.0;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Command(char),
+    Number(f64),
+}
+
+fn tokenize(path: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let chars: Vec<char> = path.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() || c == ',' {
+            i += 1;
+        } else if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            tokens.push(Token::Command(c));
+            i += 1;
+        } else {
+            let start = i;
+            if chars[i] == '-' || chars[i] == '+' {
+                i += 1;
+            }
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                i += 1;
+                if i < chars.len() && (chars[i] == '-' || chars[i] == '+') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let number: String = chars[start..i].iter().collect();
+            let value = number
+                .parse::<f64>()
+                .map_err(|_| anyhow!("invalid number {number:?} at {start}"))?;
+            tokens.push(Token::Number(value));
+        }
+    }
+    Ok(tokens)
+}
+
+fn next_number(tokens: &[Token], i: &mut usize, command: char) -> anyhow::Result<f64> {
+    match tokens.get(*i) {
+        Some(Token::Number(value)) => {
+            *i += 1;
+            Ok(*value)
+        }
+        _ => Err(anyhow!("missing number after the command {command:?}")),
+    }
+}
+
+/// the vertices of a single stroke path made of M, L, H, V and Z commands
+/// (and their relative lower case versions)
+fn path_to_vertices(path: &str) -> anyhow::Result<Vec<(f64, f64)>> {
+    let tokens = tokenize(path)?;
+    let mut vertices: Vec<(f64, f64)> = vec![];
+    let mut command: Option<char> = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let c = match tokens[i] {
+            Token::Command(c) => {
+                i += 1;
+                if matches!(c, 'M' | 'm') && !vertices.is_empty() {
+                    bail!("a shape is a single stroke : only the first command can be a M");
+                }
+                c
+            }
+            // the coordinates after a command repeat it, a M is followed by implicit L
+            Token::Number(_) => match command {
+                Some('M') => 'L',
+                Some('m') => 'l',
+                Some(c) => c,
+                None => bail!("the path must start with a M command"),
+            },
+        };
+        if vertices.is_empty() && !matches!(c, 'M' | 'm') {
+            bail!("the path must start with a M command");
+        }
+        let current = vertices.last().copied().unwrap_or((0.0, 0.0));
+        let vertex = match c {
+            'M' | 'L' => (
+                next_number(&tokens, &mut i, c)?,
+                next_number(&tokens, &mut i, c)?,
+            ),
+            'm' | 'l' => (
+                current.0 + next_number(&tokens, &mut i, c)?,
+                current.1 + next_number(&tokens, &mut i, c)?,
+            ),
+            'H' => (next_number(&tokens, &mut i, c)?, current.1),
+            'h' => (current.0 + next_number(&tokens, &mut i, c)?, current.1),
+            'V' => (current.0, next_number(&tokens, &mut i, c)?),
+            'v' => (current.0, current.1 + next_number(&tokens, &mut i, c)?),
+            'Z' | 'z' => vertices[0],
+            _ => bail!("unsupported command {c:?}, only M, L, H, V and Z are supported"),
+        };
+        vertices.push(vertex);
+        command = Some(c);
+    }
+    Ok(vertices)
+}
+
+/// Sample an SVG path (e.g. `M0,0 L100,0 L100,100`) into evenly spaced points,
+/// scaled like the normalized recorded shapes.
+pub fn svg_path_to_points(path: &str) -> anyhow::Result<PointHistory> {
+    let vertices = path_to_vertices(path)?;
+    let segment_lengths: Vec<f64> = vertices
+        .windows(2)
+        .map(|w| ((w[1].0 - w[0].0).powi(2) + (w[1].1 - w[0].1).powi(2)).sqrt())
+        .collect();
+    let total_length: f64 = segment_lengths.iter().sum();
+    if total_length == 0.0 {
+        bail!("the path has no length");
+    }
+
+    let mut samples = Vec::with_capacity(SVG_PATH_SAMPLES);
+    let mut segment = 0;
+    let mut segment_start = 0.0;
+    for i in 0..SVG_PATH_SAMPLES {
+        let distance = total_length * (i as f64) / ((SVG_PATH_SAMPLES - 1) as f64);
+        while segment < segment_lengths.len() - 1
+            && segment_start + segment_lengths[segment] < distance
+        {
+            segment_start += segment_lengths[segment];
+            segment += 1;
+        }
+        let (from, to) = (vertices[segment], vertices[segment + 1]);
+        let ratio = if segment_lengths[segment] > 0.0 {
+            ((distance - segment_start) / segment_lengths[segment]).min(1.0)
+        } else {
+            0.0
+        };
+        samples.push((
+            from.0 + (to.0 - from.0) * ratio,
+            from.1 + (to.1 - from.1) * ratio,
+        ));
+    }
+
+    let min_x = samples.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    let max_x = samples
+        .iter()
+        .map(|p| p.0)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let min_y = samples.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let max_y = samples
+        .iter()
+        .map(|p| p.1)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let scale = SVG_PATH_SIZE / (max_x - min_x).max(max_y - min_y);
+    Ok(PointHistory::from_points(
+        samples
+            .iter()
+            .map(|(x, y)| Point {
+                x: ((x - min_x) * scale).round() as i32,
+                y: ((y - min_y) * scale).round() as i32,
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compare_angles::compare_angles_with_offset;
+    use crate::event::{Point, PointHistory};
+    use crate::points_to_angles::points_to_angles;
+    use crate::svg_path::svg_path_to_points;
+
+    #[test]
+    fn test_svg_path_to_points() {
+        let points = svg_path_to_points("M0,0 L100,0 L100,100").unwrap();
+        assert_eq!(points.len(), 100);
+        assert_eq!(points.first().unwrap(), &Point { x: 0, y: 0 });
+        assert_eq!(points.last().unwrap(), &Point { x: 1000, y: 1000 });
+        assert_eq!(points[50], Point { x: 1000, y: 10 });
+
+        // the same L with the relative, H and V commands
+        assert_eq!(svg_path_to_points("m 0 0 h 100 v 100").unwrap(), points);
+        assert_eq!(svg_path_to_points("M0,0 100,0 100,100").unwrap(), points);
+    }
+
+    #[test]
+    fn test_svg_path_matches_the_drawn_shape() {
+        let mut drawn = PointHistory::new();
+        (0..20).for_each(|i| drawn.push(Point { x: 0, y: i * 10 }));
+        (0..20).for_each(|i| drawn.push(Point { x: i * 10, y: 200 }));
+        let svg = svg_path_to_points("M0,0 V200 H200").unwrap();
+        let diff = compare_angles_with_offset(&points_to_angles(&drawn), &points_to_angles(&svg));
+        assert!(diff < 0.2, "diff = {diff}");
+    }
+
+    #[test]
+    fn test_svg_path_closed() {
+        let points = svg_path_to_points("M0,0 L100,0 L100,100 Z").unwrap();
+        assert_eq!(points.last().unwrap(), &Point { x: 0, y: 0 });
+    }
+
+    #[test]
+    fn test_svg_path_errors() {
+        assert!(svg_path_to_points("").is_err());
+        assert!(svg_path_to_points("M0,0").is_err());
+        assert!(svg_path_to_points("L0,0 L10,10").is_err());
+        assert!(svg_path_to_points("M0,0 C10,10 20,20 30,30").is_err());
+        assert!(svg_path_to_points("M0,0 L10").is_err());
+        assert!(svg_path_to_points("M0,0 L10,10 M20,20 L30,30").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+use std::f64::consts::{FRAC_PI_4, PI};
+
+use crate::event::{Point, PointHistory};
+
+// the number of segments of the polygon of the circles, before the resampling
+const ARC_SEGMENTS: usize = 64;
+
+/// The canonical shapes drawn by the tests, in screen coordinates (y down)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    // a stroke to the right
+    Line,
+    // down then right
+    L,
+    // from the top right, counterclockwise (up left first) to the bottom right
+    C,
+    // from the right, counterclockwise (up first) around to the start
+    Circle,
+    // right, down left, right
+    Z,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 5] = [Shape::Line, Shape::L, Shape::C, Shape::Circle, Shape::Z];
+
+    /// the vertices of the path of the shape in a size x size box
+    fn vertices(self, size: f64) -> Vec<(f64, f64)> {
+        let arc = |from: f64, to: f64| {
+            let radius = size / 2.0;
+            (0..=ARC_SEGMENTS)
+                .map(|i| {
+                    let angle = from + (to - from) * i as f64 / ARC_SEGMENTS as f64;
+                    (radius + radius * angle.cos(), radius - radius * angle.sin())
+                })
+                .collect()
+        };
+        match self {
+            Shape::Line => vec![(0.0, 0.0), (size, 0.0)],
+            Shape::L => vec![(0.0, 0.0), (0.0, size), (size, size)],
+            Shape::C => arc(FRAC_PI_4, 2.0 * PI - FRAC_PI_4),
+            Shape::Circle => arc(0.0, 2.0 * PI),
+            Shape::Z => vec![(0.0, 0.0), (size, 0.0), (0.0, size), (size, size)],
+        }
+    }
+}
+
+/// the points of the shape in a size x size box, count points evenly spaced along
+/// the path like a steady drag
+pub fn shape_points(shape: Shape, size: i32, count: usize) -> PointHistory {
+    let vertices = shape.vertices(size as f64);
+    // dense enough that the resampling only interpolates along the straight segments
+    let mut points = vec![];
+    for w in vertices.windows(2) {
+        let ((from_x, from_y), (to_x, to_y)) = (w[0], w[1]);
+        for i in 0..count {
+            let t = i as f64 / count as f64;
+            points.push(Point {
+                x: (from_x + (to_x - from_x) * t).round() as i32,
+                y: (from_y + (to_y - from_y) * t).round() as i32,
+            });
+        }
+    }
+    let (last_x, last_y) = vertices[vertices.len() - 1];
+    points.push(Point {
+        x: last_x.round() as i32,
+        y: last_y.round() as i32,
+    });
+    PointHistory::from_points(points).downsample(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compare_angles::compare_angles_with_offset;
+    use crate::event::Point;
+    use crate::points_to_angles::{angles_to_directions, points_to_angles, Direction};
+    use crate::svg_path::svg_path_to_points;
+    use crate::test_shapes::{shape_points, Shape};
+
+    #[test]
+    fn test_shape_points() {
+        for shape in Shape::ALL {
+            let points = shape_points(shape, 200, 50);
+            assert_eq!(points.len(), 50, "{shape:?}");
+            assert!(
+                points
+                    .iter()
+                    .all(|p| (0..=200).contains(&p.x) && (0..=200).contains(&p.y)),
+                "{shape:?}"
+            );
+        }
+        let line = shape_points(Shape::Line, 100, 11);
+        assert_eq!(line.first(), Some(&Point { x: 0, y: 0 }));
+        assert_eq!(line[5], Point { x: 50, y: 0 });
+        assert_eq!(line.last(), Some(&Point { x: 100, y: 0 }));
+        let circle = shape_points(Shape::Circle, 100, 40);
+        assert_eq!(circle.first(), circle.last());
+    }
+
+    #[test]
+    fn test_shape_directions() {
+        let directions =
+            |shape| angles_to_directions(&points_to_angles(&shape_points(shape, 400, 100)));
+        assert_eq!(directions(Shape::Line), vec![Direction::Right]);
+        assert_eq!(
+            directions(Shape::L),
+            vec![Direction::Down, Direction::Right]
+        );
+        assert_eq!(
+            directions(Shape::Z),
+            vec![Direction::Right, Direction::DownLeft, Direction::Right]
+        );
+        // the moving average of points_to_angles smooths the short final up right turn
+        assert_eq!(
+            directions(Shape::C),
+            vec![
+                Direction::UpLeft,
+                Direction::Left,
+                Direction::DownLeft,
+                Direction::Down,
+                Direction::DownRight,
+                Direction::Right
+            ]
+        );
+        let circle = directions(Shape::Circle);
+        assert_eq!(circle.first(), Some(&Direction::Up));
+        assert!(circle.len() >= 7, "{circle:?}");
+    }
+
+    #[test]
+    fn test_shape_templates() {
+        let templates = [
+            (Shape::Line, "M0,0 H100"),
+            (Shape::L, "M0,0 V100 H100"),
+            (Shape::Z, "M0,0 H100 L0,100 H100"),
+        ];
+        for (shape, _) in templates {
+            let angles = points_to_angles(&shape_points(shape, 300, 80));
+            let diffs: Vec<(Shape, f64)> = templates
+                .iter()
+                .map(|(template, path)| {
+                    let template_angles = points_to_angles(&svg_path_to_points(path).unwrap());
+                    (
+                        *template,
+                        compare_angles_with_offset(&angles, &template_angles),
+                    )
+                })
+                .collect();
+            let closest = diffs
+                .iter()
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .unwrap();
+            assert_eq!(closest.0, shape, "{diffs:?}");
+        }
+    }
+}
@@ -5,12 +5,12 @@ use log::trace;
 use crate::event::PointHistory;
 use crate::grab;
 
-pub fn trace_svg(point_history: &PointHistory, angles: &Vec<f64>) {
+pub fn trace_svg(point_history: &PointHistory, angles: &[f64]) {
     let points;
     {
         let ph = point_history;
 
-        points = grab::normalize_points(&ph, true);
+        points = grab::normalize_points(ph, true);
     }
     let histo_str = points
         .iter()